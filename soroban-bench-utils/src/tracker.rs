@@ -61,6 +61,9 @@ mod cpu {
             self.0.reset().expect("perf_event::Counter::reset");
             self.0.enable().expect("perf_event::Counter::enable");
         }
+        pub fn count(&mut self) -> u64 {
+            self.0.read().expect("perf_event::Counter::read")
+        }
         pub fn end_and_count(&mut self) -> u64 {
             self.0.disable().expect("perf_event::Counter::disable");
             let tandc = self
@@ -102,6 +105,10 @@ mod cpu {
             self.0 = Self::get();
         }
 
+        pub fn count(&mut self) -> u64 {
+            Self::get() - self.0
+        }
+
         pub fn end_and_count(&mut self) -> u64 {
             let curr = Self::get();
             curr - self.0
@@ -117,6 +124,9 @@ mod cpu {
             InstructionCounter
         }
         pub fn begin(&mut self) {}
+        pub fn count(&mut self) -> u64 {
+            0
+        }
         pub fn end_and_count(&mut self) -> u64 {
             0
         }
@@ -169,6 +179,12 @@ impl<'a> HostTracker<'a> {
         self.cpu_insn_counter.begin();
     }
 
+    /// Returns the cpu instructions counted since `start`, without stopping
+    /// the measurement.
+    pub fn cpu_insns_so_far(&mut self) -> u64 {
+        self.cpu_insn_counter.count()
+    }
+
     pub fn stop(mut self) -> (u64, u64, u64) {
         // collect the metrics
         let cpu_insns = self.cpu_insn_counter.end_and_count();
@@ -1,17 +1,17 @@
 use itertools::MultiUnzip;
 use proc_macro2::{Literal, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
-use syn::{spanned::Spanned, Error, FnArg, Type};
+use syn::{spanned::Spanned, Error, Expr, FnArg, Type};
 
 pub fn derive_contract_function_set<'a>(
     ty: &Type,
-    methods: impl Iterator<Item = &'a syn::ImplItemFn>,
+    methods: impl Iterator<Item = (&'a syn::ImplItemFn, Option<Expr>)>,
 ) -> TokenStream2 {
     let mut errors = Vec::<Error>::new();
 
-    let (str_lits, func_calls): (Vec<_>, Vec<_>) = methods
+    let (str_lits, func_calls, min_protocols): (Vec<_>, Vec<_>, Vec<_>) = methods
         .enumerate()
-        .map(|(i, m)| {
+        .map(|(i, (m, min_protocol))| {
             let ident = &m.sig.ident;
             let name = ident.to_string();
             let str_lit = Literal::string(&name);
@@ -36,10 +36,41 @@ pub fn derive_contract_function_set<'a>(
                     }
                 }
             };
-            (str_lit, func_call)
+            (str_lit, func_call, min_protocol)
         })
         .multiunzip();
 
+    // Protocol-gated functions are excluded from the lookup for the older
+    // protocols, so that the lookup cost stays the same for these protocols.
+    // The gated functions must follow the remaining ones in order for the
+    // available functions to form a prefix of `FNS`, which is enforced by the
+    // const assertion below (ungated functions have protocol 0).
+    let available_fns = if min_protocols.iter().all(Option::is_none) {
+        quote! { FNS }
+    } else {
+        let min_protocols = min_protocols
+            .iter()
+            .map(|p| p.clone().unwrap_or_else(|| syn::parse_quote! { 0 }));
+        quote! {
+            {
+                const FN_MIN_PROTOCOLS: &'static [u32] = &[#(#min_protocols),*];
+                const _: () = {
+                    let mut i = 1;
+                    while i < FN_MIN_PROTOCOLS.len() {
+                        assert!(
+                            FN_MIN_PROTOCOLS[i - 1] <= FN_MIN_PROTOCOLS[i],
+                            "protocol-gated functions must be declared in non-decreasing protocol order"
+                        );
+                        i += 1;
+                    }
+                };
+                let protocol = host.get_ledger_protocol_version()?;
+                let num_fns = FN_MIN_PROTOCOLS.iter().take_while(|p| **p <= protocol).count();
+                &FNS[..num_fns]
+            }
+        }
+    };
+
     if !errors.is_empty() {
         let compile_errors = errors.iter().map(Error::to_compile_error);
         quote! { #(#compile_errors)* }
@@ -55,7 +86,8 @@ pub fn derive_contract_function_set<'a>(
                     use soroban_env_common::EnvBase;
                     use super::*;
                     const FNS: &'static [&'static str] = &[#(&#str_lits),*];
-                    match u32::from(host.symbol_index_in_strs(*func, FNS)?) as usize {
+                    let fns: &[&str] = #available_fns;
+                    match u32::from(host.symbol_index_in_strs(*func, fns)?) as usize {
                         #(#func_calls)*
                         _ => Err(host.err(crate::xdr::ScErrorType::Context, crate::xdr::ScErrorCode::MissingValue, "function does not exist", &[func.into()]))
                     }
@@ -8,7 +8,7 @@ use crate::derive_type::{derive_type_enum, derive_type_struct};
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
-    parse_macro_input, spanned::Spanned, DeriveInput, Error, ImplItem, ImplItemFn, ItemImpl,
+    parse_macro_input, spanned::Spanned, DeriveInput, Error, Expr, ImplItem, ImplItemFn, ItemImpl,
 };
 
 #[proc_macro_attribute]
@@ -45,13 +45,49 @@ fn get_methods(imp: &ItemImpl) -> impl Iterator<Item = &ImplItemFn> {
     })
 }
 
+const MIN_PROTOCOL_ATTR: &str = "min_protocol";
+
+/// Implements `BuiltinContract` for the type, dispatching the calls to its
+/// methods.
+///
+/// Methods can be annotated with `#[min_protocol(<version>)]` in order to
+/// only make them callable starting from the given protocol version. Such
+/// methods must be declared after all the methods without the annotation,
+/// in non-decreasing order of the protocol versions.
 #[proc_macro_attribute]
 pub fn contractimpl(_metadata: TokenStream, input: TokenStream) -> TokenStream {
-    let imp = parse_macro_input!(input as ItemImpl);
-    let ty = &imp.self_ty;
-    let all_methods: Vec<_> = get_methods(&imp).collect();
-
-    let cfs = derive_contract_function_set(ty, all_methods.into_iter());
+    let mut imp = parse_macro_input!(input as ItemImpl);
+    let mut errors = Vec::<Error>::new();
+    let min_protocols: Vec<Option<Expr>> = get_methods(&imp)
+        .map(|m| {
+            let attr = m
+                .attrs
+                .iter()
+                .find(|a| a.path().is_ident(MIN_PROTOCOL_ATTR))?;
+            match attr.parse_args::<Expr>() {
+                Ok(e) => Some(e),
+                Err(e) => {
+                    errors.push(e);
+                    None
+                }
+            }
+        })
+        .collect();
+    if !errors.is_empty() {
+        let compile_errors = errors.iter().map(Error::to_compile_error);
+        return quote! { #(#compile_errors)* }.into();
+    }
+    let cfs = derive_contract_function_set(
+        &imp.self_ty,
+        get_methods(&imp).zip(min_protocols),
+    );
+    // `min_protocol` is not a real attribute, so it has to be removed from
+    // the emitted methods.
+    for item in imp.items.iter_mut() {
+        if let ImplItem::Fn(m) = item {
+            m.attrs.retain(|a| !a.path().is_ident(MIN_PROTOCOL_ATTR));
+        }
+    }
     quote! {
         #imp
         #cfs
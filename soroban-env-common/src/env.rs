@@ -341,6 +341,7 @@ macro_rules! host_function_helper {
     =>
     {
         $(#[$attr])*
+        #[allow(clippy::too_many_arguments)]
         fn $fn_id(&self, $($arg:$type),*) -> Result<$ret, Self::Error>;
     };
 }
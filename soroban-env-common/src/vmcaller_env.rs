@@ -86,6 +86,7 @@ macro_rules! host_function_helper {
     =>
     {
         $(#[$attr])*
+        #[allow(clippy::too_many_arguments)]
         fn $fn_id(&self, vmcaller: &mut VmCaller<Self::VmUserState>, $($arg:$type),*) -> Result<$ret, Self::Error>;
     };
 }
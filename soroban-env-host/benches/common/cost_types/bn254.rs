@@ -1,17 +1,22 @@
 use crate::common::HostCostMeasurement;
-use ark_bn254::{Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine};
+use ark_bn254::{Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
 use ark_ff::UniformRand;
 use ark_serialize::CanonicalSerialize;
-use rand::{rngs::StdRng, RngCore};
+use rand::{rngs::StdRng, Rng, RngCore};
 use soroban_env_host::{
     cost_runner::{
         Bn254DecodeFpRun, Bn254DecodeFpSample, Bn254EncodeFpRun, Bn254EncodeFpSample,
-        Bn254FrFromU256Run, Bn254FrFromU256Sample, Bn254G1AddRun, Bn254G1AddSample,
-        Bn254G1CheckPointOnCurveRun, Bn254G1CheckPointOnCurveSample, Bn254G1MulRun,
-        Bn254G1MulSample, Bn254G1ProjectiveToAffineRun, Bn254G1ProjectiveToAffineSample,
+        Bn254FrAddRun, Bn254FrAddSubMulSample, Bn254FrFromU256Run, Bn254FrFromU256Sample,
+        Bn254FrInvRun, Bn254FrInvSample, Bn254FrMulRun, Bn254FrPowRun, Bn254FrPowSample,
+        Bn254FrSubRun, Bn254FrToU256Run, Bn254FrToU256Sample, Bn254G1AddRun, Bn254G1AddSample,
+        Bn254G1CheckPointOnCurveRun, Bn254G1CheckPointOnCurveSample, Bn254G1MsmRun,
+        Bn254G1MsmSample, Bn254G1MulRun, Bn254G1MulSample, Bn254G1ProjectiveToAffineRun,
+        Bn254G1ProjectiveToAffineSample, Bn254G2AddRun, Bn254G2AddSample,
         Bn254G2CheckPointInSubgroupRun, Bn254G2CheckPointInSubgroupSample,
-        Bn254G2CheckPointOnCurveRun, Bn254G2CheckPointOnCurveSample, Bn254PairingRun,
-        Bn254PairingSample,
+        Bn254G2CheckPointOnCurveRun, Bn254G2CheckPointOnCurveSample, Bn254G2MsmRun,
+        Bn254G2MsmSample, Bn254G2MulRun, Bn254G2MulSample, Bn254G2ProjectiveToAffineRun,
+        Bn254G2ProjectiveToAffineSample, Bn254HashToG1Run, Bn254HashToG1Sample, Bn254MapFpToG1Run,
+        Bn254MapFpToG1Sample, Bn254PairingRun, Bn254PairingSample,
     },
     xdr::ContractCostType::*,
     Host, TryIntoVal, U256Val, U256,
@@ -146,3 +151,130 @@ impl HostCostMeasurement for Bn254PairingMeasure {
         )
     }
 }
+
+pub(crate) struct Bn254G2ProjectiveToAffineMeasure;
+impl HostCostMeasurement for Bn254G2ProjectiveToAffineMeasure {
+    type Runner = Bn254G2ProjectiveToAffineRun;
+    fn new_random_case(
+        _host: &Host,
+        rng: &mut StdRng,
+        _input: u64,
+    ) -> Bn254G2ProjectiveToAffineSample {
+        Bn254G2ProjectiveToAffineSample(G2Projective::rand(rng))
+    }
+}
+
+pub(crate) struct Bn254G2AddMeasure;
+impl HostCostMeasurement for Bn254G2AddMeasure {
+    type Runner = Bn254G2AddRun;
+    fn new_random_case(_host: &Host, rng: &mut StdRng, _input: u64) -> Bn254G2AddSample {
+        Bn254G2AddSample(G2Affine::rand(rng), G2Affine::rand(rng))
+    }
+}
+
+pub(crate) struct Bn254G2MulMeasure;
+impl HostCostMeasurement for Bn254G2MulMeasure {
+    type Runner = Bn254G2MulRun;
+    fn new_random_case(_host: &Host, rng: &mut StdRng, _input: u64) -> Bn254G2MulSample {
+        Bn254G2MulSample(G2Affine::rand(rng), Fr::rand(rng))
+    }
+}
+
+pub(crate) struct Bn254G1MsmMeasure;
+impl HostCostMeasurement for Bn254G1MsmMeasure {
+    type Runner = Bn254G1MsmRun;
+    fn new_random_case(_host: &Host, rng: &mut StdRng, input: u64) -> Bn254G1MsmSample {
+        Bn254G1MsmSample(
+            (0..input).map(|_| G1Affine::rand(rng)).collect(),
+            (0..input).map(|_| Fr::rand(rng)).collect(),
+        )
+    }
+}
+
+pub(crate) struct Bn254G2MsmMeasure;
+impl HostCostMeasurement for Bn254G2MsmMeasure {
+    type Runner = Bn254G2MsmRun;
+    fn new_random_case(_host: &Host, rng: &mut StdRng, input: u64) -> Bn254G2MsmSample {
+        Bn254G2MsmSample(
+            (0..input).map(|_| G2Affine::rand(rng)).collect(),
+            (0..input).map(|_| Fr::rand(rng)).collect(),
+        )
+    }
+}
+
+pub(crate) struct Bn254MapFpToG1Measure;
+impl HostCostMeasurement for Bn254MapFpToG1Measure {
+    type Runner = Bn254MapFpToG1Run;
+    fn new_random_case(_host: &Host, rng: &mut StdRng, _input: u64) -> Bn254MapFpToG1Sample {
+        Bn254MapFpToG1Sample(Fq::rand(rng))
+    }
+}
+
+pub(crate) struct Bn254HashToG1Measure;
+impl HostCostMeasurement for Bn254HashToG1Measure {
+    type Runner = Bn254HashToG1Run;
+    const STEP_SIZE: u64 = 64;
+    fn new_random_case(_host: &Host, rng: &mut StdRng, input: u64) -> Bn254HashToG1Sample {
+        let len = Self::INPUT_BASE_SIZE + input * Self::STEP_SIZE;
+        let domain = "SOROBAN-V01-CS01-with-BN254G1_XMD:SHA-256_SVDW_RO_"
+            .as_bytes()
+            .to_vec();
+        let mut msg = vec![0u8; len as usize];
+        rng.fill(msg.as_mut_slice());
+        Bn254HashToG1Sample(domain, msg)
+    }
+}
+
+pub(crate) struct Bn254FrToU256Measure;
+impl HostCostMeasurement for Bn254FrToU256Measure {
+    type Runner = Bn254FrToU256Run;
+    fn new_random_case(_host: &Host, rng: &mut StdRng, _input: u64) -> Bn254FrToU256Sample {
+        Bn254FrToU256Sample(Fr::rand(rng))
+    }
+}
+
+pub(crate) struct Bn254FrAddMeasure;
+impl HostCostMeasurement for Bn254FrAddMeasure {
+    type Runner = Bn254FrAddRun;
+    fn new_random_case(_host: &Host, rng: &mut StdRng, _input: u64) -> Bn254FrAddSubMulSample {
+        Bn254FrAddSubMulSample(Fr::rand(rng), Fr::rand(rng))
+    }
+}
+
+pub(crate) struct Bn254FrSubMeasure;
+impl HostCostMeasurement for Bn254FrSubMeasure {
+    type Runner = Bn254FrSubRun;
+    fn new_random_case(_host: &Host, rng: &mut StdRng, _input: u64) -> Bn254FrAddSubMulSample {
+        Bn254FrAddSubMulSample(Fr::rand(rng), Fr::rand(rng))
+    }
+}
+
+pub(crate) struct Bn254FrMulMeasure;
+impl HostCostMeasurement for Bn254FrMulMeasure {
+    type Runner = Bn254FrMulRun;
+    fn new_random_case(_host: &Host, rng: &mut StdRng, _input: u64) -> Bn254FrAddSubMulSample {
+        Bn254FrAddSubMulSample(Fr::rand(rng), Fr::rand(rng))
+    }
+}
+
+pub(crate) struct Bn254FrPowMeasure;
+impl HostCostMeasurement for Bn254FrPowMeasure {
+    type Runner = Bn254FrPowRun;
+    fn new_random_case(_host: &Host, rng: &mut StdRng, input: u64) -> Bn254FrPowSample {
+        assert!(input <= 64);
+        let rhs = if input == 64 {
+            u64::MAX
+        } else {
+            (1 << input) - 1
+        };
+        Bn254FrPowSample(Fr::rand(rng), rhs)
+    }
+}
+
+pub(crate) struct Bn254FrInvMeasure;
+impl HostCostMeasurement for Bn254FrInvMeasure {
+    type Runner = Bn254FrInvRun;
+    fn new_random_case(_host: &Host, rng: &mut StdRng, _input: u64) -> Bn254FrInvSample {
+        Bn254FrInvSample(Fr::rand(rng))
+    }
+}
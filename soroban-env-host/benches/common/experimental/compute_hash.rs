@@ -0,0 +1,40 @@
+use crate::common::HostCostMeasurement;
+use rand::rngs::StdRng;
+use soroban_env_host::{
+    cost_runner::{ComputeBlake2bHashRun, ComputeBlake3HashRun, ComputeSha512HashRun},
+    Host,
+};
+
+// These measure the costs of hashing a variable-sized byte buffer. The input
+// value is the size of the buffer. They should be linear time.
+pub(crate) struct ComputeSha512HashMeasure;
+pub(crate) struct ComputeBlake2bHashMeasure;
+pub(crate) struct ComputeBlake3HashMeasure;
+
+fn new_buffer(size: u64) -> Vec<u8> {
+    (0..size).map(|n| n as u8).collect()
+}
+
+impl HostCostMeasurement for ComputeSha512HashMeasure {
+    type Runner = ComputeSha512HashRun;
+
+    fn new_random_case(_host: &Host, _rng: &mut StdRng, input: u64) -> Vec<u8> {
+        new_buffer(Self::INPUT_BASE_SIZE + input * Self::STEP_SIZE)
+    }
+}
+
+impl HostCostMeasurement for ComputeBlake2bHashMeasure {
+    type Runner = ComputeBlake2bHashRun;
+
+    fn new_random_case(_host: &Host, _rng: &mut StdRng, input: u64) -> Vec<u8> {
+        new_buffer(Self::INPUT_BASE_SIZE + input * Self::STEP_SIZE)
+    }
+}
+
+impl HostCostMeasurement for ComputeBlake3HashMeasure {
+    type Runner = ComputeBlake3HashRun;
+
+    fn new_random_case(_host: &Host, _rng: &mut StdRng, input: u64) -> Vec<u8> {
+        new_buffer(Self::INPUT_BASE_SIZE + input * Self::STEP_SIZE)
+    }
+}
@@ -1,4 +1,5 @@
 use crate::common::HostCostMeasurement;
+use ecdsa::RecoveryId;
 use elliptic_curve::scalar::IsHigh;
 use p256::ecdsa::SigningKey;
 use rand::{rngs::StdRng, RngCore};
@@ -19,10 +20,12 @@ impl HostCostMeasurement for EcdsaSecp256r1RecoverMeasure {
         let signer = SigningKey::from_bytes(&key_bytes.into()).unwrap();
         let mut msg_hash = [0u8; 32];
         rng.fill_bytes(&mut msg_hash);
-        let (mut sig, recovery_id) = signer.sign_prehash_recoverable(&msg_hash).unwrap();
+        let (mut sig, mut recovery_id) = signer.sign_prehash_recoverable(&msg_hash).unwrap();
         // in our host implementation, we are rejecting high `s`, we are doing it here too.
+        // Normalizing `s` negates `R`, so the y-parity of the recovery id flips.
         if bool::from(sig.s().is_high()) {
-            sig = sig.normalize_s().unwrap()
+            sig = sig.normalize_s().unwrap();
+            recovery_id = RecoveryId::new(!recovery_id.is_y_odd(), recovery_id.is_x_reduced());
         }
         EcdsaSecp256r1RecoverSample {
             msg_hash: Hash::from(msg_hash),
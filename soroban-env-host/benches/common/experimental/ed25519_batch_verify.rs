@@ -0,0 +1,32 @@
+use crate::common::HostCostMeasurement;
+use ed25519_dalek::{Signer, SigningKey};
+use rand::{rngs::StdRng, RngCore};
+use soroban_env_host::{
+    cost_runner::{Ed25519BatchVerifyRun, Ed25519BatchVerifySample},
+    Host,
+};
+
+// This measures the cost of verifying a batch of Ed25519 signatures of 32-byte
+// messages. The input value is the number of signatures, which the cost should
+// be linear in.
+pub(crate) struct Ed25519BatchVerifyMeasure;
+
+impl HostCostMeasurement for Ed25519BatchVerifyMeasure {
+    type Runner = Ed25519BatchVerifyRun;
+    const STEP_SIZE: u64 = 8;
+    const INPUT_BASE_SIZE: u64 = 2;
+
+    fn new_random_case(_host: &Host, rng: &mut StdRng, input: u64) -> Ed25519BatchVerifySample {
+        let n = Self::INPUT_BASE_SIZE + input * Self::STEP_SIZE;
+        let sigs = (0..n)
+            .map(|_| {
+                let signing_key = SigningKey::generate(rng);
+                let mut msg = vec![0u8; 32];
+                rng.fill_bytes(&mut msg);
+                let sig = signing_key.sign(&msg);
+                (signing_key.verifying_key(), msg, sig)
+            })
+            .collect();
+        Ed25519BatchVerifySample { sigs }
+    }
+}
@@ -1,15 +1,23 @@
 mod bls12_381;
+mod compute_hash;
 mod decode_secp256r1_sig;
 mod ecdsa_secp256k1_verify;
 mod ecdsa_secp256r1_recover;
+mod ed25519_batch_verify;
 mod ed25519_scalar_mul;
+mod poseidon;
 mod read_xdr;
+mod schnorr_secp256k1_verify;
 mod sec1_decode_point_compressed;
 
 pub(crate) use bls12_381::*;
+pub(crate) use compute_hash::*;
 pub(crate) use decode_secp256r1_sig::*;
 pub(crate) use ecdsa_secp256k1_verify::*;
 pub(crate) use ecdsa_secp256r1_recover::*;
+pub(crate) use ed25519_batch_verify::*;
 pub(crate) use ed25519_scalar_mul::*;
+pub(crate) use poseidon::*;
 pub(crate) use read_xdr::*;
+pub(crate) use schnorr_secp256k1_verify::*;
 pub(crate) use sec1_decode_point_compressed::*;
@@ -0,0 +1,64 @@
+use crate::common::HostCostMeasurement;
+use ark_bn254::Fr;
+use ark_ff::UniformRand;
+use rand::rngs::StdRng;
+use soroban_env_host::{
+    cost_runner::{
+        Poseidon2PermutationRun, Poseidon2PermutationSample, PoseidonPermutationRun,
+        PoseidonPermutationSample,
+    },
+    Host,
+};
+
+// The widely used BN254 parameter set: width 3, `x^5` S-box and 8 full
+// rounds. The number of partial rounds is varied by the input. The values of
+// the constants don't affect the cost, so they are random.
+const WIDTH: usize = 3;
+const SBOX_DEGREE: u32 = 5;
+const ROUNDS_F: u32 = 8;
+
+fn random_fr_vec(rng: &mut StdRng, len: usize) -> Vec<Fr> {
+    (0..len).map(|_| Fr::rand(rng)).collect()
+}
+
+pub(crate) struct PoseidonPermutationMeasure;
+impl HostCostMeasurement for PoseidonPermutationMeasure {
+    type Runner = PoseidonPermutationRun;
+
+    const STEP_SIZE: u64 = 8;
+
+    fn new_random_case(_host: &Host, rng: &mut StdRng, input: u64) -> PoseidonPermutationSample {
+        let rounds_p = (input * Self::STEP_SIZE) as u32;
+        PoseidonPermutationSample {
+            state: random_fr_vec(rng, WIDTH),
+            d: SBOX_DEGREE,
+            rounds_f: ROUNDS_F,
+            rounds_p,
+            mds: (0..WIDTH).map(|_| random_fr_vec(rng, WIDTH)).collect(),
+            round_constants: (0..ROUNDS_F + rounds_p)
+                .map(|_| random_fr_vec(rng, WIDTH))
+                .collect(),
+        }
+    }
+}
+
+pub(crate) struct Poseidon2PermutationMeasure;
+impl HostCostMeasurement for Poseidon2PermutationMeasure {
+    type Runner = Poseidon2PermutationRun;
+
+    const STEP_SIZE: u64 = 8;
+
+    fn new_random_case(_host: &Host, rng: &mut StdRng, input: u64) -> Poseidon2PermutationSample {
+        let rounds_p = (input * Self::STEP_SIZE) as u32;
+        Poseidon2PermutationSample {
+            state: random_fr_vec(rng, WIDTH),
+            d: SBOX_DEGREE,
+            rounds_f: ROUNDS_F,
+            rounds_p,
+            mat_internal_diag_m_1: random_fr_vec(rng, WIDTH),
+            round_constants: (0..ROUNDS_F + rounds_p)
+                .map(|_| random_fr_vec(rng, WIDTH))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::common::HostCostMeasurement;
+use k256::schnorr::SigningKey;
+use rand::{rngs::StdRng, RngCore};
+use soroban_env_host::{
+    cost_runner::{SchnorrSecp256k1VerifyRun, SchnorrSecp256k1VerifySample},
+    Host,
+};
+
+pub(crate) struct SchnorrSecp256k1VerifyMeasure {}
+
+impl HostCostMeasurement for SchnorrSecp256k1VerifyMeasure {
+    type Runner = SchnorrSecp256k1VerifyRun;
+    const STEP_SIZE: u64 = 64;
+
+    fn new_random_case(_host: &Host, rng: &mut StdRng, input: u64) -> SchnorrSecp256k1VerifySample {
+        let mut key_bytes = [0u8; 32];
+        rng.fill_bytes(&mut key_bytes);
+        let signer = SigningKey::from_bytes(&key_bytes).unwrap();
+        let mut msg = vec![0u8; (Self::INPUT_BASE_SIZE + input * Self::STEP_SIZE) as usize];
+        rng.fill_bytes(&mut msg);
+        let mut aux_rand = [0u8; 32];
+        rng.fill_bytes(&mut aux_rand);
+        let sig = signer.sign_raw(&msg, &aux_rand).unwrap();
+        SchnorrSecp256k1VerifySample {
+            pub_key: signer.verifying_key().to_bytes().into(),
+            msg,
+            sig: sig.to_bytes(),
+        }
+    }
+}
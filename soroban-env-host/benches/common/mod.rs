@@ -55,6 +55,7 @@ pub(crate) fn for_each_experimental_cost_measurement<B: Benchmark>(
         BTreeMap::new();
     call_bench::<B, Ed25519ScalarMulMeasure>(&mut params)?;
     call_bench::<B, VerifyEd25519SigMeasure>(&mut params)?;
+    call_bench::<B, Ed25519BatchVerifyMeasure>(&mut params)?;
     call_bench::<B, ReadXdrByteArrayMeasure>(&mut params)?;
     call_bench::<B, EcdsaSecp256k1VerifyMeasure>(&mut params)?;
     call_bench::<B, EcdsaSecp256r1RecoverMeasure>(&mut params)?;
@@ -71,6 +72,25 @@ pub(crate) fn for_each_experimental_cost_measurement<B: Benchmark>(
     call_bench::<B, Bls12381G2CheckPointOnCurveMeasure>(&mut params)?;
     call_bench::<B, Bls12381G2CheckPointInSubgroupMeasure>(&mut params)?;
     call_bench::<B, Bls12381G2ComputeYFromXMeasure>(&mut params)?;
+    call_bench::<B, Bn254G2ProjectiveToAffineMeasure>(&mut params)?;
+    call_bench::<B, Bn254G2AddMeasure>(&mut params)?;
+    call_bench::<B, Bn254G2MulMeasure>(&mut params)?;
+    call_bench::<B, Bn254G1MsmMeasure>(&mut params)?;
+    call_bench::<B, Bn254G2MsmMeasure>(&mut params)?;
+    call_bench::<B, Bn254MapFpToG1Measure>(&mut params)?;
+    call_bench::<B, Bn254HashToG1Measure>(&mut params)?;
+    call_bench::<B, Bn254FrToU256Measure>(&mut params)?;
+    call_bench::<B, Bn254FrAddMeasure>(&mut params)?;
+    call_bench::<B, Bn254FrSubMeasure>(&mut params)?;
+    call_bench::<B, Bn254FrMulMeasure>(&mut params)?;
+    call_bench::<B, Bn254FrPowMeasure>(&mut params)?;
+    call_bench::<B, Bn254FrInvMeasure>(&mut params)?;
+    call_bench::<B, PoseidonPermutationMeasure>(&mut params)?;
+    call_bench::<B, Poseidon2PermutationMeasure>(&mut params)?;
+    call_bench::<B, SchnorrSecp256k1VerifyMeasure>(&mut params)?;
+    call_bench::<B, ComputeSha512HashMeasure>(&mut params)?;
+    call_bench::<B, ComputeBlake2bHashMeasure>(&mut params)?;
+    call_bench::<B, ComputeBlake3HashMeasure>(&mut params)?;
 
     Ok(params)
 }
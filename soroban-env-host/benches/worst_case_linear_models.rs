@@ -3,14 +3,20 @@
 // You can optionally pass in args listing the {`ContractCostType`, `WasmInsnType`} combination to run with, e.g.
 // $ cargo bench --features bench --bench worst_case_linear_models -- MemCpy I64Rotr --nocapture
 // To run the experimental cost types: $ RUN_EXPERIMENT=1 cargo bench ...
+// To write the resulting `ContractCostParams` config setting entries into a
+// directory and report how they differ from the current params:
+// $ WRITE_XDR=<dir> [DIFF_THRESHOLD=<percent>] cargo bench ...
 mod common;
 use common::*;
 use soroban_env_host::{
-    budget::MeteredCostComponent,
+    budget::{Budget, MeteredCostComponent},
     cost_runner::{CostRunner, CostType, WasmInsnType},
-    xdr::ContractCostType,
+    xdr::{
+        ConfigSettingEntry, ContractCostParamEntry, ContractCostParams, ContractCostType, Limits,
+        WriteXdr,
+    },
 };
-use std::{collections::BTreeMap, fmt::Display, io::Write};
+use std::{collections::BTreeMap, fmt::Display, io::Write, path::Path};
 use tabwriter::{Alignment, TabWriter};
 
 struct WorstCaseLinearModels;
@@ -310,6 +316,157 @@ fn write_budget_params_code(
     )
 }
 
+// Cost types whose params are derived analytically rather than calibrated,
+// these (along with the `WasmInsnExec` cpu param, which is derived from the
+// wasm instruction tiers) keep their current params in the emitted XDR. See
+// `write_budget_params_code`.
+const ANALYTICAL_CPU_COST_TYPES: &[ContractCostType] = &[
+    ContractCostType::MemAlloc,
+    ContractCostType::MemCpy,
+    ContractCostType::MemCmp,
+];
+const ANALYTICAL_MEM_COST_TYPES: &[ContractCostType] = &[
+    ContractCostType::WasmInsnExec,
+    ContractCostType::MemAlloc,
+    ContractCostType::MemCpy,
+    ContractCostType::MemCmp,
+    ContractCostType::ValSer,
+    ContractCostType::ValDeser,
+];
+
+fn to_cost_param_entry(cm: MeteredCostComponent) -> ContractCostParamEntry {
+    ContractCostParamEntry::try_from(cm).expect("cost model params out of range")
+}
+
+// Builds the cpu and memory `ContractCostParams` from the calibrated models.
+// Cost types that have not been calibrated (or are analytical) keep the params
+// of `current`.
+fn build_cost_params(
+    params: &BTreeMap<CostType, (MeteredCostComponent, MeteredCostComponent)>,
+    wasm_tier_cost: &BTreeMap<WasmInsnTier, u64>,
+    current: &(ContractCostParams, ContractCostParams),
+) -> (ContractCostParams, ContractCostParams) {
+    let mut cpu_entries = current.0 .0.to_vec();
+    let mut mem_entries = current.1 .0.to_vec();
+    for ty in ContractCostType::VARIANTS.iter() {
+        let i = *ty as usize;
+        if *ty == ContractCostType::WasmInsnExec {
+            let base_cpu_per_fuel = wasm_tier_cost[&WasmInsnTier::BASE];
+            if base_cpu_per_fuel != 0 {
+                cpu_entries[i] = to_cost_param_entry(MeteredCostComponent {
+                    const_term: base_cpu_per_fuel,
+                    lin_term: Default::default(),
+                });
+            }
+        }
+        let Some((cpu, mem)) = params.get(&CostType::Contract(*ty)) else {
+            continue;
+        };
+        if *ty != ContractCostType::WasmInsnExec && !ANALYTICAL_CPU_COST_TYPES.contains(ty) {
+            cpu_entries[i] = to_cost_param_entry(*cpu);
+        }
+        if !ANALYTICAL_MEM_COST_TYPES.contains(ty) {
+            mem_entries[i] = to_cost_param_entry(*mem);
+        }
+    }
+    (
+        ContractCostParams(cpu_entries.try_into().unwrap()),
+        ContractCostParams(mem_entries.try_into().unwrap()),
+    )
+}
+
+fn write_cost_params_xdr(
+    dir: &Path,
+    cpu_params: &ContractCostParams,
+    mem_params: &ContractCostParams,
+) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let entries = [
+        (
+            "contract_cost_params_cpu_instructions.xdr",
+            ConfigSettingEntry::ContractCostParamsCpuInstructions(cpu_params.clone()),
+        ),
+        (
+            "contract_cost_params_memory_bytes.xdr",
+            ConfigSettingEntry::ContractCostParamsMemoryBytes(mem_params.clone()),
+        ),
+    ];
+    println!("");
+    for (file_name, entry) in entries {
+        let path = dir.join(file_name);
+        std::fs::write(&path, entry.to_xdr(Limits::none()).unwrap())?;
+        println!(
+            "{}: {}",
+            path.display(),
+            entry.to_xdr_base64(Limits::none()).unwrap()
+        );
+    }
+    Ok(())
+}
+
+fn relative_change(old: i64, new: i64) -> f64 {
+    (new as f64 - old as f64).abs() / (old.max(1) as f64)
+}
+
+// Reports the params that differ between `current` and `new`, flagging the
+// cost types where any term has changed by more than `threshold` (relative).
+fn write_cost_params_diff(
+    tw: &mut TabWriter<Vec<u8>>,
+    current: &(ContractCostParams, ContractCostParams),
+    new: &(ContractCostParams, ContractCostParams),
+    threshold: f64,
+) -> std::io::Result<()> {
+    writeln!(tw, "").unwrap();
+    writeln!(tw, "").unwrap();
+    writeln!(tw, "cost_type\tcpu_model_const_param\tcpu_model_lin_param\tmem_model_const_param\tmem_model_lin_param\tmax_change\tflagged").unwrap();
+    let mut flagged = vec![];
+    for ty in ContractCostType::VARIANTS.iter() {
+        let i = *ty as usize;
+        let (old_cpu, old_mem) = (&current.0 .0[i], &current.1 .0[i]);
+        let (new_cpu, new_mem) = (&new.0 .0[i], &new.1 .0[i]);
+        if old_cpu == new_cpu && old_mem == new_mem {
+            continue;
+        }
+        let max_change = [
+            relative_change(old_cpu.const_term, new_cpu.const_term),
+            relative_change(old_cpu.linear_term, new_cpu.linear_term),
+            relative_change(old_mem.const_term, new_mem.const_term),
+            relative_change(old_mem.linear_term, new_mem.linear_term),
+        ]
+        .into_iter()
+        .fold(0.0, f64::max);
+        let is_flagged = max_change > threshold;
+        if is_flagged {
+            flagged.push(*ty);
+        }
+        writeln!(
+            tw,
+            "{:?}\t{} -> {}\t{} -> {}\t{} -> {}\t{} -> {}\t{:.1}%\t{}",
+            ty,
+            old_cpu.const_term,
+            new_cpu.const_term,
+            old_cpu.linear_term,
+            new_cpu.linear_term,
+            old_mem.const_term,
+            new_mem.const_term,
+            old_mem.linear_term,
+            new_mem.linear_term,
+            max_change * 100.0,
+            if is_flagged { "*" } else { "" }
+        )
+        .unwrap();
+    }
+    tw.flush()?;
+    if !flagged.is_empty() {
+        println!(
+            "cost types changed by more than {:.1}%: {:?}",
+            threshold * 100.0,
+            flagged
+        );
+    }
+    Ok(())
+}
+
 fn extract_tier(
     params_wasm: &BTreeMap<CostType, (MeteredCostComponent, MeteredCostComponent)>,
     insn_tier: &[WasmInsnType],
@@ -406,5 +563,23 @@ fn main() -> std::io::Result<()> {
     if std::env::var("WRITE_PARAMS").is_ok() {
         write_budget_params_code(&params, &wasm_tier_cost);
     }
+
+    if let Ok(dir) = std::env::var("WRITE_XDR") {
+        let threshold = std::env::var("DIFF_THRESHOLD")
+            .ok()
+            .map(|v| v.parse::<f64>().ok())
+            .flatten()
+            .unwrap_or(10.0)
+            / 100.0;
+        let current = Budget::default().get_cost_params().unwrap();
+        let new = build_cost_params(&params, &wasm_tier_cost, &current);
+        write_cost_params_xdr(Path::new(&dir), &new.0, &new.1)?;
+
+        let mut tw = TabWriter::new(vec![])
+            .padding(5)
+            .alignment(Alignment::Right);
+        write_cost_params_diff(&mut tw, &current, &new, threshold)?;
+        eprintln!("{}", String::from_utf8(tw.into_inner().unwrap()).unwrap());
+    }
     Ok(())
 }
@@ -1,12 +1,16 @@
 mod dimension;
 mod limits;
 mod model;
+mod profiler;
+mod timeline;
 mod util;
 mod wasmi_helper;
 
 pub(crate) use limits::DepthLimiter;
 pub use limits::{DEFAULT_HOST_DEPTH_LIMIT, DEFAULT_XDR_RW_LIMITS};
 pub use model::{MeteredCostComponent, ScaledU64};
+pub use profiler::{BudgetProfile, BudgetProfileEntry};
+pub use timeline::{BudgetTimeline, BudgetTimelineEntry};
 pub(crate) use wasmi_helper::{get_wasmi_config, load_calibrated_fuel_costs};
 
 use std::{
@@ -22,6 +26,8 @@ use crate::{
 };
 
 use dimension::{BudgetDimension, IsCpu, IsShadowMode};
+use profiler::BudgetProfiler;
+use timeline::BudgetTimelineRecorder;
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CostTracker {
@@ -31,6 +37,14 @@ pub struct CostTracker {
     pub mem: u64,
 }
 
+/// A hook called with the cost type and the charged cpu of every charge made
+/// outside of shadow mode. Since the fuel consumed by Wasm is only charged
+/// once the execution stops, its start is reported as a charge of zero cpu to
+/// `WasmInsnExec`. The hook is called while the budget is borrowed, so it must
+/// not access the budget.
+#[cfg(test)]
+pub(crate) type ChargeHook = Rc<dyn Fn(ContractCostType, u64)>;
+
 #[derive(Clone)]
 struct BudgetTracker {
     // Tracker for each `CostType`
@@ -41,6 +55,12 @@ struct BudgetTracker {
     wasm_memory: u64,
     // Tracks the real time (in nsecs) spent on various `CostType`
     time_tracker: [u64; ContractCostType::variants().len()],
+    // Attributes the charges to the call stack, when profiling is enabled
+    profiler: Option<BudgetProfiler>,
+    // Records the stream of charges, when the timeline is enabled
+    timeline: Option<BudgetTimelineRecorder>,
+    #[cfg(test)]
+    charge_hook: Option<ChargeHook>,
 }
 
 impl Default for BudgetTracker {
@@ -51,6 +71,10 @@ impl Default for BudgetTracker {
             #[cfg(any(test, feature = "testutils", feature = "bench"))]
             wasm_memory: 0,
             time_tracker: [0; ContractCostType::variants().len()],
+            profiler: None,
+            timeline: None,
+            #[cfg(test)]
+            charge_hook: None,
         };
         for (ct, tracker) in ContractCostType::variants()
             .iter()
@@ -167,6 +191,12 @@ impl BudgetTracker {
             tracker.mem = 0;
         }
         self.wasm_memory = 0;
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.reset();
+        }
+        if let Some(timeline) = self.timeline.as_mut() {
+            timeline.reset();
+        }
     }
 
     fn track_time(&mut self, ty: ContractCostType, duration: u64) -> Result<(), HostError> {
@@ -259,6 +289,12 @@ impl BudgetImpl {
         )?;
         if !self.is_in_shadow_mode {
             tracker.cpu = tracker.cpu.saturating_add(cpu_charged);
+            if let Some(profiler) = self.tracker.profiler.as_mut() {
+                profiler.record(ty, iterations, cpu_charged, 0);
+            }
+            if let Some(timeline) = self.tracker.timeline.as_mut() {
+                timeline.record_cpu(ty, iterations, input, cpu_charged);
+            }
         }
         self.cpu_insns
             .check_budget_limit(IsShadowMode(self.is_in_shadow_mode))?;
@@ -272,12 +308,26 @@ impl BudgetImpl {
         )?;
         if !self.is_in_shadow_mode {
             tracker.mem = tracker.mem.saturating_add(mem_charged);
+            if let Some(profiler) = self.tracker.profiler.as_mut() {
+                profiler.record(ty, 0, 0, mem_charged);
+            }
+            if let Some(timeline) = self.tracker.timeline.as_mut() {
+                timeline.record_mem(mem_charged);
+            }
+            #[cfg(test)]
+            if let Some(hook) = self.tracker.charge_hook.as_ref() {
+                hook(ty, cpu_charged);
+            }
         }
         self.mem_bytes
             .check_budget_limit(IsShadowMode(self.is_in_shadow_mode))
     }
 
     fn get_wasmi_fuel_remaining(&self) -> Result<u64, HostError> {
+        #[cfg(test)]
+        if let (false, Some(hook)) = (self.is_in_shadow_mode, self.tracker.charge_hook.as_ref()) {
+            hook(ContractCostType::WasmInsnExec, 0);
+        }
         let cpu_remaining = self.cpu_insns.get_remaining();
         let cost_model = self
             .cpu_insns
@@ -1316,6 +1366,75 @@ impl Budget {
         Ok(self.0.try_borrow_or_err()?.is_in_shadow_mode)
     }
 
+    /// Runs a user provided closure under a sub-budget: the limits are lowered
+    /// such that at most `cpu_cap` cpu instructions and `mem_cap` memory bytes
+    /// can be charged on top of what has already been consumed (the sub-budget
+    /// is never larger than what remains of the current limits). The original
+    /// limits are restored once the closure returns, regardless of its result.
+    ///
+    /// Besides the result of the closure, returns whether the consumption has
+    /// crossed the limits of the sub-budget in either dimension (or a Vm has
+    /// run out of fuel within them), as checked before the original limits
+    /// are restored. Errors that merely look like budget exhaustion (e.g. XDR
+    /// limits) don't cross them.
+    ///
+    /// Sub-budgets nest naturally, as every nested sub-budget is carved from
+    /// the limits set by its parent. Running out of fuel within a sub-budget
+    /// that is capped by the original cpu limit also counts as running out of
+    /// fuel within the original limits.
+    pub(crate) fn with_sub_limits<T, F>(
+        &self,
+        cpu_cap: u64,
+        mem_cap: u64,
+        f: F,
+    ) -> Result<(Result<T, HostError>, bool), HostError>
+    where
+        F: FnOnce() -> Result<T, HostError>,
+    {
+        let (prev_cpu_limit, prev_mem_limit, prev_out_of_fuel) =
+            self.with_mut_budget(|mut b| {
+                let prev = (
+                    b.cpu_insns.limit,
+                    b.mem_bytes.limit,
+                    b.cpu_insns.out_of_fuel,
+                );
+                b.cpu_insns.limit = b.cpu_insns.total_count.saturating_add(cpu_cap).min(prev.0);
+                b.mem_bytes.limit = b.mem_bytes.total_count.saturating_add(mem_cap).min(prev.1);
+                b.cpu_insns.out_of_fuel = false;
+                Ok(prev)
+            })?;
+        let res = f();
+        let crossed = self.with_mut_budget(|mut b| {
+            let crossed = b.cpu_insns.total_count > b.cpu_insns.limit
+                || b.cpu_insns.out_of_fuel
+                || b.mem_bytes.total_count > b.mem_bytes.limit;
+            b.cpu_insns.out_of_fuel = prev_out_of_fuel
+                || (b.cpu_insns.out_of_fuel && b.cpu_insns.limit == prev_cpu_limit);
+            b.cpu_insns.limit = prev_cpu_limit;
+            b.mem_bytes.limit = prev_mem_limit;
+            Ok(crossed)
+        })?;
+        Ok((res, crossed))
+    }
+
+    /// Returns whether the budget consumption is still within the cpu and
+    /// memory limits.
+    pub(crate) fn is_within_limits(&self) -> Result<bool, HostError> {
+        let b = self.0.try_borrow_or_err()?;
+        Ok(b.cpu_insns.check_budget_limit(IsShadowMode(false)).is_ok()
+            && !b.cpu_insns.out_of_fuel
+            && b.mem_bytes.check_budget_limit(IsShadowMode(false)).is_ok())
+    }
+
+    /// Records that a Vm has trapped for running out of the fuel converted
+    /// from the cpu limit.
+    pub(crate) fn set_out_of_fuel(&self) -> Result<(), HostError> {
+        self.with_mut_budget(|mut b| {
+            b.cpu_insns.out_of_fuel = true;
+            Ok(())
+        })
+    }
+
     pub(crate) fn set_shadow_limits(&self, cpu: u64, mem: u64) -> Result<(), HostError> {
         self.0.try_borrow_mut_or_err()?.cpu_insns.shadow_limit = cpu;
         self.0.try_borrow_mut_or_err()?.mem_bytes.shadow_limit = mem;
@@ -1348,6 +1467,12 @@ impl Budget {
         self.0.try_borrow_or_err()?.tracker.get_time(ty)
     }
 
+    #[cfg(test)]
+    pub(crate) fn set_charge_hook(&self, hook: Option<ChargeHook>) -> Result<(), HostError> {
+        self.0.try_borrow_mut_or_err()?.tracker.charge_hook = hook;
+        Ok(())
+    }
+
     pub fn track_time(&self, ty: ContractCostType, duration: u64) -> Result<(), HostError> {
         self.0
             .try_borrow_mut_or_err()?
@@ -1375,6 +1500,11 @@ impl Budget {
         self.0.try_borrow_mut_or_err()?.get_wasmi_fuel_remaining()
     }
 
+    #[cfg(any(test, feature = "testutils"))]
+    pub(crate) fn get_wasmi_fuel_costs(&self) -> Result<wasmi::FuelCosts, HostError> {
+        Ok(self.0.try_borrow_or_err()?.fuel_costs)
+    }
+
     pub fn reset_default(&self) -> Result<(), HostError> {
         *self.0.try_borrow_mut_or_err()? = BudgetImpl::default();
         Ok(())
@@ -34,6 +34,12 @@ pub(crate) struct BudgetDimension {
 
     /// Similar to `total_count`, but towards the `shadow_limit`
     pub(crate) shadow_total_count: u64,
+
+    /// Set when a Vm has run out of fuel converted from this (cpu) dimension.
+    /// wasmi traps on a block whose fuel exceeds what remains and leaves the
+    /// remaining fuel unconsumed, so `total_count` stays within `limit` even
+    /// though the limit has been reached.
+    pub(crate) out_of_fuel: bool,
 }
 
 impl Default for BudgetDimension {
@@ -44,6 +50,7 @@ impl Default for BudgetDimension {
             total_count: 0,
             shadow_limit: 0,
             shadow_total_count: 0,
+            out_of_fuel: false,
         }
     }
 }
@@ -138,6 +145,7 @@ impl BudgetDimension {
     pub(crate) fn reset_count(&mut self) {
         self.total_count = 0;
         self.shadow_total_count = 0;
+        self.out_of_fuel = false;
     }
 
     pub(crate) fn check_budget_limit(&self, is_shadow: IsShadowMode) -> Result<(), HostError> {
@@ -0,0 +1,308 @@
+//! Opt-in attribution of budget charges to the call stack they happen in.
+//!
+//! When profiling is enabled, every non-shadow budget charge is recorded under
+//! the current stack of contract frames and host functions, followed by the
+//! charged cost type. The stack is maintained from the same lifecycle events
+//! that drive the [`TraceHook`](crate::TraceHook): contexts are pushed and
+//! popped on [`TraceEvent::PushCtx`] / [`TraceEvent::PopCtx`] and host
+//! functions on [`TraceEvent::EnvCall`] / [`TraceEvent::EnvRet`].
+//!
+//! The collected [`BudgetProfile`] can be rendered in the folded stack format
+//! understood by flamegraph tools (`flamegraph.pl`, `inferno-flamegraph`,
+//! speedscope etc.).
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::{
+    budget::Budget,
+    host::{error::TryBorrowOrErr, Frame, TraceEvent},
+    xdr::ContractCostType,
+    Host, HostError, SymbolStr, TryFromVal,
+};
+
+/// Budget charged for a single cost type under a single call stack.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BudgetProfileEntry {
+    /// Labels of the frames the charges happened in, outermost first. Contract
+    /// frames are labelled `<contract strkey>:<function>`, host functions
+    /// invoked by contracts by their name and the top-level host function
+    /// frames by their `HostFunctionType` name.
+    pub stack: Vec<String>,
+    pub cost_type: ContractCostType,
+    pub iterations: u64,
+    pub cpu_insns: u64,
+    pub mem_bytes: u64,
+}
+
+/// Budget charges collected while profiling is enabled, see
+/// [`Budget::enable_profiling`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BudgetProfile {
+    pub entries: Vec<BudgetProfileEntry>,
+}
+
+impl BudgetProfile {
+    /// Renders the CPU instructions in the folded stack format, i.e. one
+    /// `frame;...;frame;CostType count` line per entry.
+    pub fn folded_cpu_insns(&self) -> String {
+        self.folded(|e| e.cpu_insns)
+    }
+
+    /// Renders the memory bytes in the folded stack format, i.e. one
+    /// `frame;...;frame;CostType count` line per entry.
+    pub fn folded_mem_bytes(&self) -> String {
+        self.folded(|e| e.mem_bytes)
+    }
+
+    fn folded(&self, weight: impl Fn(&BudgetProfileEntry) -> u64) -> String {
+        let mut out = String::new();
+        for entry in self.entries.iter() {
+            let count = weight(entry);
+            // Zero-weight stacks are invisible in a flame graph anyway.
+            if count == 0 {
+                continue;
+            }
+            for frame in entry.stack.iter() {
+                out.push_str(frame);
+                out.push(';');
+            }
+            let _ = writeln!(out, "{} {}", entry.cost_type.name(), count);
+        }
+        out
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct ProfileCost {
+    iterations: u64,
+    cpu_insns: u64,
+    mem_bytes: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ProfileFrameKind {
+    Context,
+    EnvCall(&'static str),
+}
+
+#[derive(Clone)]
+struct ProfileFrame {
+    kind: ProfileFrameKind,
+    // Length of the profiler path before this frame was pushed.
+    parent_path_len: usize,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct BudgetProfiler {
+    stack: Vec<ProfileFrame>,
+    // `;`-separated labels of the frames in `stack`, used as the key of the
+    // charges recorded under the current stack.
+    path: String,
+    costs: BTreeMap<String, [ProfileCost; ContractCostType::variants().len()]>,
+}
+
+impl BudgetProfiler {
+    fn push(&mut self, kind: ProfileFrameKind, label: &str) {
+        let parent_path_len = self.path.len();
+        if !self.path.is_empty() {
+            self.path.push(';');
+        }
+        self.path.push_str(label);
+        self.stack.push(ProfileFrame {
+            kind,
+            parent_path_len,
+        });
+    }
+
+    fn truncate(&mut self, len: usize) {
+        if let Some(frame) = self.stack.get(len) {
+            self.path.truncate(frame.parent_path_len);
+            self.stack.truncate(len);
+        }
+    }
+
+    // Number of frames up to and including the innermost context.
+    fn context_depth(&self) -> usize {
+        self.stack
+            .iter()
+            .rposition(|f| f.kind == ProfileFrameKind::Context)
+            .map_or(0, |i| i + 1)
+    }
+
+    fn push_context(&mut self, label: &str) {
+        self.push(ProfileFrameKind::Context, label);
+    }
+
+    // Also pops any host function that has not emitted its `EnvRet` (e.g.
+    // because it has returned early with an error).
+    fn pop_context(&mut self) {
+        let depth = self.context_depth();
+        if depth > 0 {
+            self.truncate(depth - 1);
+        }
+    }
+
+    fn push_env_call(&mut self, fname: &'static str) {
+        self.push(ProfileFrameKind::EnvCall(fname), fname);
+    }
+
+    fn pop_env_call(&mut self, fname: &'static str) {
+        let depth = self.context_depth();
+        if let Some(i) = self.stack[depth..]
+            .iter()
+            .rposition(|f| f.kind == ProfileFrameKind::EnvCall(fname))
+        {
+            self.truncate(depth + i);
+        }
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        ty: ContractCostType,
+        iterations: u64,
+        cpu_insns: u64,
+        mem_bytes: u64,
+    ) {
+        // Wasm instructions are charged in bulk when the VM calls into the
+        // host, at which point the host function is already on the stack;
+        // they are attributed to the calling contract frame instead.
+        let path_len = if ty == ContractCostType::WasmInsnExec {
+            self.stack
+                .get(self.context_depth())
+                .map_or(self.path.len(), |f| f.parent_path_len)
+        } else {
+            self.path.len()
+        };
+        let path = &self.path[..path_len];
+        if !self.costs.contains_key(path) {
+            self.costs.insert(
+                path.to_string(),
+                [ProfileCost::default(); ContractCostType::variants().len()],
+            );
+        }
+        let Some(cost) = self
+            .costs
+            .get_mut(path)
+            .and_then(|costs| costs.get_mut(ty as usize))
+        else {
+            return;
+        };
+        cost.iterations = cost.iterations.saturating_add(iterations);
+        cost.cpu_insns = cost.cpu_insns.saturating_add(cpu_insns);
+        cost.mem_bytes = cost.mem_bytes.saturating_add(mem_bytes);
+    }
+
+    #[cfg(any(test, feature = "testutils", feature = "bench"))]
+    pub(crate) fn reset(&mut self) {
+        self.costs.clear();
+    }
+
+    fn profile(&self) -> BudgetProfile {
+        let mut entries = vec![];
+        for (path, costs) in self.costs.iter() {
+            let stack: Vec<String> = if path.is_empty() {
+                vec![]
+            } else {
+                path.split(';').map(String::from).collect()
+            };
+            for ct in ContractCostType::variants() {
+                let cost = costs[ct as usize];
+                if cost.iterations == 0 && cost.cpu_insns == 0 && cost.mem_bytes == 0 {
+                    continue;
+                }
+                entries.push(BudgetProfileEntry {
+                    stack: stack.clone(),
+                    cost_type: ct,
+                    iterations: cost.iterations,
+                    cpu_insns: cost.cpu_insns,
+                    mem_bytes: cost.mem_bytes,
+                });
+            }
+        }
+        BudgetProfile { entries }
+    }
+}
+
+impl Budget {
+    /// Starts attributing every budget charge to the contract frame, host
+    /// function and cost type it happens in, discarding any previously
+    /// collected profile. Charges made in shadow mode are not recorded.
+    ///
+    /// Profiling should be enabled before the profiled invocation starts. It
+    /// enables host tracing, which makes every host function call slower, and
+    /// is switched off by `reset_default`.
+    pub fn enable_profiling(&self) -> Result<(), HostError> {
+        self.with_mut_budget(|mut b| {
+            b.tracker.profiler = Some(BudgetProfiler::default());
+            Ok(())
+        })
+    }
+
+    /// Returns the charges collected since profiling has been enabled (or
+    /// since the last tracker reset), or `None` when profiling is disabled.
+    pub fn get_profile(&self) -> Result<Option<BudgetProfile>, HostError> {
+        Ok(self
+            .0
+            .try_borrow_or_err()?
+            .tracker
+            .profiler
+            .as_ref()
+            .map(|p| p.profile()))
+    }
+
+    pub(crate) fn is_profiling(&self) -> bool {
+        self.0
+            .try_borrow()
+            .is_ok_and(|b| b.tracker.profiler.is_some())
+    }
+
+    fn with_profiler<F>(&self, f: F) -> Result<(), HostError>
+    where
+        F: FnOnce(&mut BudgetProfiler),
+    {
+        if let Some(profiler) = self.0.try_borrow_mut_or_err()?.tracker.profiler.as_mut() {
+            f(profiler);
+        }
+        Ok(())
+    }
+}
+
+impl Host {
+    /// Keeps the call stack of the budget profiler in sync with the host, when
+    /// profiling is enabled.
+    pub(crate) fn update_budget_profiler(&self, event: &TraceEvent) -> Result<(), HostError> {
+        let budget = self.budget_ref();
+        if !budget.is_profiling() {
+            return Ok(());
+        }
+        match event {
+            TraceEvent::PushCtx(ctx) => {
+                let label = self.budget_profile_frame_label(&ctx.frame);
+                budget.with_profiler(|p| p.push_context(&label))
+            }
+            TraceEvent::PopCtx(..) => budget.with_profiler(|p| p.pop_context()),
+            TraceEvent::EnvCall(fname, _) => budget.with_profiler(|p| p.push_env_call(fname)),
+            TraceEvent::EnvRet(fname, _) => budget.with_profiler(|p| p.pop_env_call(fname)),
+            TraceEvent::Begin | TraceEvent::End => Ok(()),
+        }
+    }
+
+    fn budget_profile_frame_label(&self, frame: &Frame) -> String {
+        let (id, fn_name) = match frame {
+            Frame::ContractVM { vm, fn_name, .. } => (&vm.contract_id, *fn_name),
+            Frame::HostFunction(ty) => return ty.name().to_string(),
+            Frame::StellarAssetContract(id, fn_name, ..) => (id, *fn_name),
+            #[cfg(any(test, feature = "testutils"))]
+            Frame::TestContract(tc) => (&tc.id, tc.func),
+        };
+        // Reading a symbol object is metered, so the function name is read
+        // under the shadow budget in order to not distort the profile.
+        let mut fn_label = String::new();
+        self.budget_ref().with_shadow_mode(|| {
+            fn_label = SymbolStr::try_from_val(self, &fn_name)?.to_string();
+            Ok(())
+        });
+        let contract =
+            stellar_strkey::Strkey::Contract(stellar_strkey::Contract(id.0 .0)).to_string();
+        format!("{contract}:{fn_label}")
+    }
+}
@@ -0,0 +1,173 @@
+//! Opt-in recording of the stream of budget charges.
+//!
+//! Unlike the cost trackers (which only keep per-cost-type totals) and the
+//! [`TraceHook`](crate::TraceHook) (which only observes hashes of the budget
+//! state), the timeline keeps one entry for every non-shadow budget charge, in
+//! the order the charges happen. This makes it possible to find out where
+//! exactly a budget blowup happens and what led to it.
+//!
+//! The recorded [`BudgetTimeline`] can be exported to CSV or JSON.
+use std::fmt::Write;
+
+use crate::{
+    budget::Budget,
+    host::{error::TryBorrowOrErr, TraceEvent},
+    xdr::ContractCostType,
+    Host, HostError,
+};
+
+/// A single budget charge.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BudgetTimelineEntry {
+    /// Position of the charge in the timeline, starting at 0.
+    pub sequence: u64,
+    pub cost_type: ContractCostType,
+    pub iterations: u64,
+    pub input: Option<u64>,
+    pub cpu_insns: u64,
+    pub mem_bytes: u64,
+    /// Number of frames on the context stack when the charge happened: 0
+    /// outside of any invocation, and 1 more for the top-level host function
+    /// and every nested contract call.
+    pub depth: u32,
+}
+
+/// Budget charges recorded while the timeline is enabled, see
+/// [`Budget::enable_timeline`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BudgetTimeline {
+    pub entries: Vec<BudgetTimelineEntry>,
+}
+
+impl BudgetTimeline {
+    /// Renders the timeline as CSV, with a header line followed by one line
+    /// per charge. Charges without input have an empty `input` column.
+    pub fn to_csv(&self) -> String {
+        let mut out =
+            String::from("sequence,cost_type,iterations,input,cpu_insns,mem_bytes,depth\n");
+        for e in self.entries.iter() {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{}",
+                e.sequence,
+                e.cost_type.name(),
+                e.iterations,
+                e.input.map(|i| i.to_string()).unwrap_or_default(),
+                e.cpu_insns,
+                e.mem_bytes,
+                e.depth
+            );
+        }
+        out
+    }
+
+    /// Renders the timeline as a JSON array of objects, one per charge.
+    /// Charges without input have a `null` input.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("[");
+        for (i, e) in self.entries.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(
+                out,
+                "{{\"sequence\":{},\"cost_type\":\"{}\",\"iterations\":{},\"input\":{},\"cpu_insns\":{},\"mem_bytes\":{},\"depth\":{}}}",
+                e.sequence,
+                e.cost_type.name(),
+                e.iterations,
+                e.input.map(|i| i.to_string()).unwrap_or_else(|| "null".to_string()),
+                e.cpu_insns,
+                e.mem_bytes,
+                e.depth
+            );
+        }
+        out.push(']');
+        out
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct BudgetTimelineRecorder {
+    depth: u32,
+    entries: Vec<BudgetTimelineEntry>,
+}
+
+impl BudgetTimelineRecorder {
+    pub(crate) fn record_cpu(
+        &mut self,
+        ty: ContractCostType,
+        iterations: u64,
+        input: Option<u64>,
+        cpu_insns: u64,
+    ) {
+        self.entries.push(BudgetTimelineEntry {
+            sequence: self.entries.len() as u64,
+            cost_type: ty,
+            iterations,
+            input,
+            cpu_insns,
+            mem_bytes: 0,
+            depth: self.depth,
+        });
+    }
+
+    // Memory is charged right after cpu for the same charge, unless charging
+    // cpu has already exceeded the limit.
+    pub(crate) fn record_mem(&mut self, mem_bytes: u64) {
+        if let Some(e) = self.entries.last_mut() {
+            e.mem_bytes = mem_bytes;
+        }
+    }
+
+    #[cfg(any(test, feature = "testutils", feature = "bench"))]
+    pub(crate) fn reset(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl Budget {
+    /// Starts recording every budget charge into a timeline, discarding any
+    /// previously recorded one. Charges made in shadow mode are not recorded.
+    ///
+    /// The timeline grows with every charge, so it is meant for investigating
+    /// individual invocations. It should be enabled before the invocation
+    /// starts in order for the frame depths to be correct, and is switched off
+    /// by `reset_default`.
+    pub fn enable_timeline(&self) -> Result<(), HostError> {
+        self.with_mut_budget(|mut b| {
+            b.tracker.timeline = Some(BudgetTimelineRecorder::default());
+            Ok(())
+        })
+    }
+
+    /// Returns the charges recorded since the timeline has been enabled (or
+    /// since the last tracker reset), or `None` when it is disabled.
+    pub fn get_timeline(&self) -> Result<Option<BudgetTimeline>, HostError> {
+        Ok(self
+            .0
+            .try_borrow_or_err()?
+            .tracker
+            .timeline
+            .as_ref()
+            .map(|t| BudgetTimeline {
+                entries: t.entries.clone(),
+            }))
+    }
+}
+
+impl Host {
+    /// Keeps the frame depth of the budget timeline in sync with the host,
+    /// when the timeline is enabled.
+    pub(crate) fn update_budget_timeline(&self, event: &TraceEvent) -> Result<(), HostError> {
+        let delta: i32 = match event {
+            TraceEvent::PushCtx(..) => 1,
+            TraceEvent::PopCtx(..) => -1,
+            _ => return Ok(()),
+        };
+        let mut b = self.budget_ref().0.try_borrow_mut_or_err()?;
+        if let Some(timeline) = b.tracker.timeline.as_mut() {
+            timeline.depth = timeline.depth.saturating_add_signed(delta);
+        }
+        Ok(())
+    }
+}
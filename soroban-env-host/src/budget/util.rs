@@ -7,7 +7,11 @@
 use crate::{budget::Budget, HostError};
 
 #[cfg(any(test, feature = "testutils", feature = "bench"))]
-use crate::host::error::TryBorrowOrErr;
+use crate::{
+    budget::MeteredCostComponent,
+    host::error::TryBorrowOrErr,
+    xdr::{ContractCostParamEntry, ContractCostParams},
+};
 
 #[cfg(any(test, feature = "testutils"))]
 use crate::{budget::model::ScaledU64, xdr::ContractCostType};
@@ -107,6 +111,23 @@ impl Budget {
         Ok(())
     }
 
+    /// Returns the cpu and memory cost models of the budget in the form of
+    /// the `ContractCostParams` network settings they can be configured from.
+    pub fn get_cost_params(&self) -> Result<(ContractCostParams, ContractCostParams), HostError> {
+        let b = self.0.try_borrow_or_err()?;
+        let to_params = |models: &[MeteredCostComponent]| -> Result<ContractCostParams, HostError> {
+            let entries = models
+                .iter()
+                .map(|cm| ContractCostParamEntry::try_from(*cm))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(ContractCostParams(entries.try_into()?))
+        };
+        Ok((
+            to_params(&b.cpu_insns.cost_models)?,
+            to_params(&b.mem_bytes.cost_models)?,
+        ))
+    }
+
     pub fn get_shadow_cpu_insns_consumed(&self) -> Result<u64, HostError> {
         Ok(self.0.try_borrow_or_err()?.cpu_insns.shadow_total_count)
     }
@@ -8,10 +8,51 @@ pub(crate) mod storage_utils;
 use crate::host::{Host, HostError};
 use soroban_env_common::{Symbol, Val};
 
-pub(crate) trait BuiltinContract {
+/// A contract implemented natively in Rust and dispatched by the host.
+///
+/// Besides the built-in contracts, this can be implemented in tests (with
+/// `testutils` feature enabled) and registered via
+/// `Host::register_builtin_test_contract`.
+pub trait BuiltinContract {
+    /// Calls the contract function `func` with the provided `args`. Calls to
+    /// the functions that the contract doesn't define must fail with
+    /// `(ScErrorType::Context, ScErrorCode::MissingValue)` error.
     fn call(&self, func: &Symbol, host: &Host, args: &[Val]) -> Result<Val, HostError>;
 }
 
+// Adapts `BuiltinContract` to the `ContractFunctionSet` interface that is used
+// for dispatching the native test contracts.
+#[cfg(any(test, feature = "testutils"))]
+pub(crate) struct BuiltinContractFunctionSet(pub(crate) std::rc::Rc<dyn BuiltinContract>);
+
+#[cfg(any(test, feature = "testutils"))]
+impl crate::ContractFunctionSet for BuiltinContractFunctionSet {
+    fn call(&self, func: &Symbol, host: &Host, args: &[Val]) -> Option<Val> {
+        // Returning an error value makes the host fail the call, but loses the
+        // error details. The host dispatches via `try_call` instead.
+        crate::ContractFunctionSet::try_call(self, func, host, args)
+            .unwrap_or_else(|err| Some(err.error.into()))
+    }
+
+    fn try_call(&self, func: &Symbol, host: &Host, args: &[Val]) -> Result<Option<Val>, HostError> {
+        use crate::xdr::{ScErrorCode, ScErrorType};
+        match self.0.call(func, host, args) {
+            Ok(val) => Ok(Some(val)),
+            // Let the host handle the missing functions, e.g. a missing
+            // constructor is not an error.
+            Err(err)
+                if err.error.is_type(ScErrorType::Context)
+                    && err.error.is_code(ScErrorCode::MissingValue) =>
+            {
+                Ok(None)
+            }
+            // Propagate the error as is, so that e.g. running out of budget
+            // is not recoverable, same as for Wasm contracts.
+            Err(err) => Err(err),
+        }
+    }
+}
+
 pub(crate) use stellar_asset_contract::StellarAssetContract;
 
 pub(crate) mod account_contract;
@@ -487,6 +487,10 @@ impl MuxedAddress {
         }
     }
 
+    pub(crate) fn is_muxed(&self) -> bool {
+        matches!(self.object, MuxedAddressObjectHolder::MuxedAddress(_))
+    }
+
     pub(crate) fn id(&self) -> Result<Option<u64>, HostError> {
         match self.object {
             MuxedAddressObjectHolder::Address(_) => Ok(None),
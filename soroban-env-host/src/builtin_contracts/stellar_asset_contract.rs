@@ -8,7 +8,9 @@ mod metadata;
 pub(crate) mod public_types;
 mod storage_types;
 
-#[cfg(test)]
-pub(crate) mod test_stellar_asset_contract;
+#[cfg(any(test, feature = "testutils"))]
+pub mod sep41_conformance;
+#[cfg(any(test, feature = "testutils"))]
+pub mod test_stellar_asset_contract;
 
 pub(crate) use contract::StellarAssetContract;
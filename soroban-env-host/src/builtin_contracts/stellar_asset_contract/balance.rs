@@ -6,19 +6,21 @@ use crate::{
         contract_error::ContractError,
         stellar_asset_contract::{
             asset_info::{read_asset, read_asset_info},
+            metadata::render_trustline_asset_name,
             public_types::AssetInfo,
             storage_types::DataKey,
         },
     },
     err,
     host::metered_clone::MeteredClone,
+    ledger_info::get_account_balance_bounds,
     storage::Storage,
     xdr::{
-        AccountEntry, AccountEntryExt, AccountEntryExtensionV1Ext, AccountFlags, AccountId, Asset,
-        LedgerEntry, LedgerEntryData, LedgerKey, ScAddress, ScErrorCode, ScErrorType,
-        TrustLineAsset, TrustLineEntry, TrustLineEntryExt, TrustLineFlags,
+        AccountEntry, AccountFlags, AccountId, Asset, LedgerEntry, LedgerEntryData, LedgerKey,
+        ScAddress, ScErrorCode, ScErrorType, TrustLineAsset, TrustLineEntry, TrustLineEntryExt,
+        TrustLineFlags,
     },
-    Env, ErrorHandler, Host, HostError, StorageType, TryIntoVal,
+    Env, ErrorHandler, Host, HostError, StorageType, TryIntoVal, Val,
 };
 
 use super::storage_types::{BalanceValue, BALANCE_EXTEND_AMOUNT, BALANCE_TTL_THRESHOLD};
@@ -491,15 +493,27 @@ fn read_trustline_entry(
     lk: &Rc<LedgerKey>,
 ) -> Result<Rc<LedgerEntry>, HostError> {
     storage.try_get(&lk, &host, None)?.ok_or_else(|| {
-        let account_address = host.account_address_from_key(lk);
-        match account_address {
-            Ok(account_address) => host.error(
-                ContractError::TrustlineMissingError.into(),
-                "trustline entry is missing for account",
-                &[account_address],
-            ),
-            Err(e) => e,
-        }
+        let account_address = match host.account_address_from_key(lk) {
+            Ok(account_address) => account_address,
+            Err(e) => return e,
+        };
+        // The asset name is only needed for the diagnostics, so it's rendered
+        // in debug mode and doesn't affect the budget.
+        let mut asset_name = Val::VOID.to_val();
+        host.with_debug_mode_allowing_new_objects(
+            || {
+                if let LedgerKey::Trustline(tl_key) = lk.as_ref() {
+                    asset_name = render_trustline_asset_name(host, &tl_key.asset)?;
+                }
+                Ok(())
+            },
+            true,
+        );
+        host.error(
+            ContractError::TrustlineMissingError.into(),
+            "trustline entry is missing for account and asset",
+            &[account_address, asset_name],
+        )
     })
 }
 
@@ -594,23 +608,8 @@ fn get_min_max_account_balance(e: &Host, ae: &AccountEntry) -> Result<(i64, i64)
         ));
     }
 
-    let base_reserve = e.with_ledger_info(|li| Ok(li.base_reserve))? as i64;
-    if let AccountEntryExt::V1(ext1) = &ae.ext {
-        let net_entries = if let AccountEntryExtensionV1Ext::V2(ext2) = &ext1.ext {
-            2i64 + (ae.num_sub_entries as i64) + (ext2.num_sponsoring as i64)
-                - (ext2.num_sponsored as i64)
-        } else {
-            2i64 + ae.num_sub_entries as i64
-        };
-        let min_balance = net_entries * base_reserve + ext1.liabilities.selling;
-        let max_balance = i64::MAX - ext1.liabilities.buying;
-        Ok((min_balance, max_balance))
-    } else {
-        let net_entries = 2i64 + (ae.num_sub_entries as i64);
-        let min_balance = net_entries * base_reserve;
-        let max_balance = i64::MAX;
-        Ok((min_balance, max_balance))
-    }
+    let base_reserve = e.with_ledger_info(|li| Ok(li.base_reserve))?;
+    Ok(get_account_balance_bounds(ae, base_reserve))
 }
 
 // Metering: covered by components.
@@ -809,15 +808,15 @@ fn set_trustline_authorization(
     })
 }
 
-fn is_asset_auth_required(e: &Host) -> Result<bool, HostError> {
+pub(crate) fn is_asset_auth_required(e: &Host) -> Result<bool, HostError> {
     is_asset_issuer_flag_set(e, AccountFlags::RequiredFlag)
 }
 
-fn is_asset_clawback_enabled(e: &Host) -> Result<bool, HostError> {
+pub(crate) fn is_asset_clawback_enabled(e: &Host) -> Result<bool, HostError> {
     is_asset_issuer_flag_set(e, AccountFlags::ClawbackEnabledFlag)
 }
 
-fn is_asset_auth_revocable(e: &Host) -> Result<bool, HostError> {
+pub(crate) fn is_asset_auth_revocable(e: &Host) -> Result<bool, HostError> {
     is_asset_issuer_flag_set(e, AccountFlags::RevocableFlag)
 }
 
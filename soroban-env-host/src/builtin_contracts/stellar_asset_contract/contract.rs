@@ -9,18 +9,19 @@ use crate::{
             allowance::{read_allowance, spend_allowance, write_allowance},
             asset_info::{has_asset_info, read_asset_info, validate_asset, write_asset_info},
             balance::{
-                check_clawbackable, is_authorized, read_balance, receive_balance, spend_balance,
-                spend_balance_no_authorization_check, write_authorization,
+                check_clawbackable, is_asset_auth_required, is_asset_auth_revocable,
+                is_asset_clawback_enabled, is_authorized, read_balance, receive_balance,
+                spend_balance, spend_balance_no_authorization_check, write_authorization,
             },
             event,
-            metadata::{read_name, read_symbol, set_metadata, DECIMAL},
-            public_types::{AlphaNum12AssetInfo, AlphaNum4AssetInfo, AssetInfo},
+            metadata::{read_name, read_symbol, render_issuer_strkey, set_metadata, DECIMAL},
+            public_types::{AlphaNum12AssetInfo, AlphaNum4AssetInfo, AssetInfo, AssetInfoDetails},
             storage_types::{INSTANCE_EXTEND_AMOUNT, INSTANCE_TTL_THRESHOLD},
         },
     },
     err,
     host::{metered_clone::MeteredClone, Host},
-    xdr::Asset,
+    xdr::{Asset, ScErrorCode, ScErrorType},
     BytesObject, Compare, Env, EnvBase, ErrorHandler, HostError, TryFromVal, TryIntoVal,
 };
 
@@ -28,6 +29,12 @@ use soroban_builtin_sdk_macros::contractimpl;
 
 pub(crate) struct StellarAssetContract;
 
+// `transfer` accepts muxed destinations since protocol 23; `mint` and
+// `transfer_from` accept them starting from this protocol version.
+const MUXED_MINT_AND_TRANSFER_FROM_MIN_PROTOCOL: u32 = 25;
+
+const ASSET_INFO_MIN_PROTOCOL: u32 = 25;
+
 fn check_nonnegative_amount(e: &Host, amount: i128) -> Result<(), HostError> {
     if amount < 0 {
         Err(err!(
@@ -41,6 +48,24 @@ fn check_nonnegative_amount(e: &Host, amount: i128) -> Result<(), HostError> {
     }
 }
 
+fn check_muxed_destination_supported(e: &Host, to_mux: &MuxedAddress) -> Result<(), HostError> {
+    // Before the muxed destination support has been enabled the argument
+    // conversion would fail for the muxed addresses, so we return the same
+    // conversion error here.
+    if to_mux.is_muxed()
+        && e.get_ledger_protocol_version()? < MUXED_MINT_AND_TRANSFER_FROM_MIN_PROTOCOL
+    {
+        Err(e.err(
+            ScErrorType::Value,
+            ScErrorCode::UnexpectedType,
+            "muxed destination address is not supported in this protocol",
+            &[],
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 fn check_non_native(e: &Host) -> Result<(), HostError> {
     match read_asset_info(e)? {
         AssetInfo::Native => Err(e.error(
@@ -228,11 +253,13 @@ impl StellarAssetContract {
         e: &Host,
         spender: Address,
         from: Address,
-        to: Address,
+        to_mux: MuxedAddress,
         amount: i128,
     ) -> Result<(), HostError> {
         let _span = tracy_span!("SAC transfer_from");
+        check_muxed_destination_supported(e, &to_mux)?;
         check_nonnegative_amount(e, amount)?;
+        let to = to_mux.address()?;
         spender.require_auth()?;
 
         e.extend_current_contract_instance_and_code_ttl(
@@ -243,7 +270,7 @@ impl StellarAssetContract {
         spend_allowance(e, from.metered_clone(e)?, spender, amount)?;
         spend_balance(e, from.metered_clone(e)?, amount)?;
         receive_balance(e, to.metered_clone(e)?, amount)?;
-        event::transfer_maybe_with_issuer(e, from, to, None, amount)?;
+        event::transfer_maybe_with_issuer(e, from, to, to_mux.id()?, amount)?;
         Ok(())
     }
 
@@ -332,9 +359,11 @@ impl StellarAssetContract {
     }
 
     // Metering: covered by components
-    pub(crate) fn mint(e: &Host, to: Address, amount: i128) -> Result<(), HostError> {
+    pub(crate) fn mint(e: &Host, to_mux: MuxedAddress, amount: i128) -> Result<(), HostError> {
         let _span = tracy_span!("SAC mint");
+        check_muxed_destination_supported(e, &to_mux)?;
         check_nonnegative_amount(e, amount)?;
+        let to = to_mux.address()?;
         check_not_issuer(e, &to)?;
 
         let admin = read_administrator(e)?;
@@ -346,7 +375,7 @@ impl StellarAssetContract {
         )?;
 
         receive_balance(e, to.metered_clone(e)?, amount)?;
-        event::mint(e, to, None, amount)?;
+        event::mint(e, to, to_mux.id()?, amount)?;
         Ok(())
     }
 
@@ -386,4 +415,31 @@ impl StellarAssetContract {
         let _span = tracy_span!("SAC symbol");
         read_symbol(e)
     }
+
+    #[min_protocol(ASSET_INFO_MIN_PROTOCOL)]
+    pub(crate) fn asset_info(e: &Host) -> Result<AssetInfoDetails, HostError> {
+        let _span = tracy_span!("SAC asset_info");
+        let (asset_type, issuer) = match read_asset_info(e)? {
+            AssetInfo::Native => ("native", None),
+            AssetInfo::AlphaNum4(asset) => ("credit_alphanum4", Some(asset.issuer)),
+            AssetInfo::AlphaNum12(asset) => ("credit_alphanum12", Some(asset.issuer)),
+        };
+        let (asset_code, issuer) = match issuer {
+            // Metadata symbol is the SEP-0011 asset code for non-native
+            // assets.
+            Some(issuer) => (
+                Some(read_symbol(e)?),
+                Some(render_issuer_strkey(e, issuer)?),
+            ),
+            None => (None, None),
+        };
+        Ok(AssetInfoDetails {
+            asset_type: String::try_from_val(e, &e.string_new_from_slice(asset_type.as_bytes())?)?,
+            asset_code,
+            issuer,
+            auth_required: is_asset_auth_required(e)?,
+            auth_revocable: is_asset_auth_revocable(e)?,
+            clawback_enabled: is_asset_clawback_enabled(e)?,
+        })
+    }
 }
@@ -2,8 +2,8 @@ use super::{asset_info::read_asset_info, public_types::AssetInfo};
 use crate::{
     builtin_contracts::base_types::{BytesN, String},
     host::Host,
-    xdr::{ScErrorCode, ScErrorType},
-    Env, EnvBase, HostError, StorageType, SymbolSmall, TryFromVal, TryIntoVal,
+    xdr::{AccountId, PublicKey, ScErrorCode, ScErrorType, TrustLineAsset, Uint256},
+    Env, EnvBase, HostError, StorageType, SymbolSmall, TryFromVal, TryIntoVal, Val,
 };
 use soroban_builtin_sdk_macros::contracttype;
 use std::fmt::Write;
@@ -160,6 +160,51 @@ fn render_sep0011_asset<const N: usize>(
     Ok((name, symbol))
 }
 
+/// Renders the issuer account strkey.
+pub(crate) fn render_issuer_strkey(e: &Host, issuer: BytesN<32>) -> Result<String, HostError> {
+    let strkey_len: u64 = 56;
+    // `PublicKey::to_string` does an allocation of its own, so charge for
+    // both the strkey and its copy.
+    e.charge_budget(
+        crate::xdr::ContractCostType::MemAlloc,
+        Some(strkey_len.saturating_mul(2)),
+    )?;
+    let strkey = ed25519::PublicKey(issuer.to_array()?).to_string();
+    String::try_from_val(e, &e.string_new_from_slice(strkey.as_bytes())?)
+}
+
+/// Renders the SEP-0011 name of the trustline asset (`native`, or
+/// `CODE:ISSUER`). This is only meant to be used for diagnostics in debug
+/// mode, so the string rendering is not metered.
+pub(crate) fn render_trustline_asset_name(
+    e: &Host,
+    asset: &TrustLineAsset,
+) -> Result<Val, HostError> {
+    let mut s = std::string::String::new();
+    let issuer = match asset {
+        TrustLineAsset::Native => {
+            s.push_str("native");
+            None
+        }
+        TrustLineAsset::CreditAlphanum4(a) => {
+            render_sep0011_asset_code(a.asset_code.as_slice(), &mut s)?;
+            Some(&a.issuer)
+        }
+        TrustLineAsset::CreditAlphanum12(a) => {
+            render_sep0011_asset_code(a.asset_code.as_slice(), &mut s)?;
+            Some(&a.issuer)
+        }
+        TrustLineAsset::PoolShare(_) => {
+            return Err((ScErrorType::Value, ScErrorCode::InvalidInput).into());
+        }
+    };
+    if let Some(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(issuer)))) = issuer {
+        s.push(':');
+        s.push_str(&ed25519::PublicKey(*issuer).to_string());
+    }
+    Ok(e.string_new_from_slice(s.as_bytes())?.into())
+}
+
 pub(crate) fn set_metadata(e: &Host) -> Result<(), HostError> {
     let name_and_symbol: (String, String) = match read_asset_info(e)? {
         AssetInfo::Native => {
@@ -27,6 +27,21 @@ pub(crate) enum AssetInfo {
     AlphaNum12(AlphaNum12AssetInfo),
 }
 
+/// Structured asset information returned by the `asset_info` function.
+#[derive(Clone)]
+#[contracttype]
+pub(crate) struct AssetInfoDetails {
+    /// `native`, `credit_alphanum4` or `credit_alphanum12`.
+    pub asset_type: String,
+    /// SEP-0011 asset code, `None` for the native asset.
+    pub asset_code: Option<String>,
+    /// Issuer account strkey, `None` for the native asset.
+    pub issuer: Option<String>,
+    pub auth_required: bool,
+    pub auth_revocable: bool,
+    pub clawback_enabled: bool,
+}
+
 impl TryFromVal<Host, AssetInfo> for Asset {
     type Error = HostError;
 
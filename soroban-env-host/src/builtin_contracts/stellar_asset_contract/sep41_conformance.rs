@@ -0,0 +1,349 @@
+//! Test harness for checking the token contracts against the SEP-41 token
+//! interface semantics implemented by the Stellar Asset Contract.
+//!
+//! Every SEP-41 function call is performed both on the reference SAC instance
+//! and on the candidate token contract, and the observable outcomes (results,
+//! emitted events and the authorizations required) are asserted to match.
+//!
+//! In order to check a Wasm token, register it in the test host, set up the
+//! reference token and the holder accounts via [`create_reference_token`],
+//! give `from` the same balance of the Wasm token and call
+//! [`Sep41ConformanceTest::run`].
+use std::fmt::Debug;
+
+use ed25519_dalek::SigningKey;
+
+use crate::{
+    builtin_contracts::{
+        stellar_asset_contract::test_stellar_asset_contract::TestStellarAssetContract,
+        testutils::{
+            create_account, new_ledger_entry_from_data, signing_key_to_account_id, TestSigner,
+        },
+    },
+    xdr::{
+        AccountFlags, AlphaNum4, Asset, AssetCode4, ContractEventBody, ContractEventType,
+        LedgerEntryData, ScAddress, ScVal, SorobanAuthorizedFunction, SorobanAuthorizedInvocation,
+        TrustLineAsset, TrustLineEntry, TrustLineEntryExt, TrustLineFlags,
+    },
+    Host, HostError,
+};
+
+/// Asset code of the reference token created by [`create_reference_token`].
+pub const REFERENCE_ASSET_CODE: [u8; 4] = *b"SEP4";
+
+/// Observable outcome of a single token function call.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Sep41CallOutcome<T> {
+    /// Result of the call. SEP-41 doesn't standardize the error codes, so
+    /// the failures are only compared by presence.
+    pub(crate) result: Option<T>,
+    /// `(topics, data)` pairs of the events emitted by the token contract.
+    pub(crate) events: Vec<(Vec<ScVal>, ScVal)>,
+    /// Authorizations that have been authenticated during the call.
+    pub(crate) auth: Vec<(ScAddress, SorobanAuthorizedInvocation)>,
+}
+
+/// Harness comparing a candidate token contract against the reference
+/// Stellar Asset Contract instance.
+pub struct Sep41ConformanceTest<'a> {
+    host: &'a Host,
+    reference: TestStellarAssetContract<'a>,
+    candidate: TestStellarAssetContract<'a>,
+}
+
+impl<'a> Sep41ConformanceTest<'a> {
+    /// Creates a harness comparing `candidate` against the `reference` SAC
+    /// instance. Both tokens must be deployed to `host`.
+    ///
+    /// Note, that this enables invocation metering on `host` and it stays
+    /// enabled after the harness is dropped. Invocation metering resets the
+    /// events and the authorization state on every top-level contract call.
+    pub fn new(
+        host: &'a Host,
+        reference: TestStellarAssetContract<'a>,
+        candidate: TestStellarAssetContract<'a>,
+    ) -> Self {
+        // Invocation metering resets the events on every contract call, which
+        // allows attributing the events to the individual calls.
+        host.enable_invocation_metering();
+        Self {
+            host,
+            reference,
+            candidate,
+        }
+    }
+
+    /// Calls `f` on both tokens and asserts that the call outcomes match.
+    /// Returns the reference result.
+    pub(crate) fn check<T, F>(&self, function_name: &str, f: F) -> Option<T>
+    where
+        T: Debug + PartialEq,
+        F: Fn(&TestStellarAssetContract<'a>) -> Result<T, HostError>,
+    {
+        let reference_address = self.reference.address.to_sc_address().unwrap();
+        let mut reference = self.call(&self.reference, &reference_address, &f);
+        let candidate = self.call(&self.candidate, &reference_address, &f);
+
+        // SAC appends the SEP-0011 asset name to the SEP-41 event topics.
+        // The candidate is allowed to append its own extra topics as well.
+        assert_eq!(
+            reference.events.len(),
+            candidate.events.len(),
+            "SEP-41 conformance: `{function_name}` emitted different number of events"
+        );
+        for ((ref_topics, _), (candidate_topics, _)) in
+            reference.events.iter_mut().zip(candidate.events.iter())
+        {
+            ref_topics.pop();
+            assert!(
+                candidate_topics.len() >= ref_topics.len()
+                    && candidate_topics[..ref_topics.len()] == ref_topics[..],
+                "SEP-41 conformance: `{function_name}` event topics mismatch, \
+                 expected prefix {ref_topics:?}, got {candidate_topics:?}"
+            );
+        }
+        for ((_, ref_data), (_, candidate_data)) in
+            reference.events.iter().zip(candidate.events.iter())
+        {
+            assert_eq!(
+                ref_data, candidate_data,
+                "SEP-41 conformance: `{function_name}` event data mismatch"
+            );
+        }
+        assert_eq!(
+            reference.result, candidate.result,
+            "SEP-41 conformance: `{function_name}` result mismatch"
+        );
+        assert_eq!(
+            reference.auth, candidate.auth,
+            "SEP-41 conformance: `{function_name}` authorization mismatch"
+        );
+        reference.result
+    }
+
+    /// Drives both tokens through the whole SEP-41 function set, using the
+    /// Stellar accounts of the provided keys as the token holders.
+    ///
+    /// `from` must hold the same balance of at least 1000 units in both
+    /// tokens, and `to` and `spender` must be able to receive the tokens. None
+    /// of the addresses may be the token admin or issuer.
+    pub fn run(&self, from: &SigningKey, to: &SigningKey, spender: &SigningKey) {
+        self.run_with_signers(
+            &TestSigner::account(from),
+            &TestSigner::account(to),
+            &TestSigner::account(spender),
+        );
+    }
+
+    /// Same as `run`, but allows arbitrary signers for the holders.
+    pub(crate) fn run_with_signers(
+        &self,
+        from: &TestSigner,
+        to: &TestSigner,
+        spender: &TestSigner,
+    ) {
+        let host = self.host;
+        let from_address = from.address(host);
+        let to_address = to.address(host);
+        let spender_address = spender.address(host);
+        let ledger_seq = host.with_ledger_info(|li| Ok(li.sequence_number)).unwrap();
+
+        let balance = self
+            .check("balance", |t| t.balance(from_address.clone()))
+            .unwrap();
+        assert!(balance >= 1000, "`from` balance is too low for the test");
+        self.check("balance", |t| t.balance(to_address.clone()));
+        self.check("allowance", |t| {
+            t.allowance(from_address.clone(), spender_address.clone())
+        });
+
+        // Transfers.
+        self.check("transfer", |t| t.transfer(from, to_address.clone(), 100));
+        self.check("transfer", |t| t.transfer(from, to_address.clone(), 0));
+        self.check("transfer", |t| t.transfer(from, to_address.clone(), -1));
+        self.check("transfer", |t| {
+            t.transfer(from, to_address.clone(), balance - 99)
+        });
+        self.check("balance", |t| t.balance(from_address.clone()));
+        self.check("balance", |t| t.balance(to_address.clone()));
+
+        // Allowances.
+        self.check("approve", |t| {
+            t.approve(from, spender_address.clone(), 200, ledger_seq + 100)
+        });
+        self.check("allowance", |t| {
+            t.allowance(from_address.clone(), spender_address.clone())
+        });
+        self.check("approve", |t| {
+            t.approve(from, spender_address.clone(), 200, ledger_seq - 1)
+        });
+        self.check("approve", |t| {
+            t.approve(from, spender_address.clone(), -1, ledger_seq + 100)
+        });
+        self.check("transfer_from", |t| {
+            t.transfer_from(spender, from_address.clone(), to_address.clone(), 150)
+        });
+        self.check("allowance", |t| {
+            t.allowance(from_address.clone(), spender_address.clone())
+        });
+        self.check("transfer_from", |t| {
+            t.transfer_from(spender, from_address.clone(), to_address.clone(), 51)
+        });
+
+        // Burns.
+        self.check("burn", |t| t.burn(from, 10));
+        self.check("burn", |t| t.burn(from, -1));
+        self.check("burn_from", |t| {
+            t.burn_from(spender, from_address.clone(), 50)
+        });
+        self.check("burn_from", |t| {
+            t.burn_from(spender, from_address.clone(), 1)
+        });
+        self.check("allowance", |t| {
+            t.allowance(from_address.clone(), spender_address.clone())
+        });
+        self.check("balance", |t| t.balance(from_address.clone()));
+        self.check("balance", |t| t.balance(to_address.clone()));
+
+        // Token metadata is token-specific, so only the presence of the
+        // values is checked.
+        self.check("decimals", |t| t.decimals().map(|_| ()));
+        self.check("name", |t| t.name().map(|_| ()));
+        self.check("symbol", |t| t.symbol().map(|_| ()));
+    }
+
+    fn call<T, F>(
+        &self,
+        token: &TestStellarAssetContract<'a>,
+        reference_address: &ScAddress,
+        f: &F,
+    ) -> Sep41CallOutcome<T>
+    where
+        F: Fn(&TestStellarAssetContract<'a>) -> Result<T, HostError>,
+    {
+        let result = f(token).ok();
+        let contract_id = self
+            .host
+            .contract_id_from_address(token.address.as_object())
+            .unwrap();
+        let events = self
+            .host
+            .get_contract_events()
+            .unwrap()
+            .0
+            .into_iter()
+            .filter(|e| {
+                !e.failed_call
+                    && e.event.type_ == ContractEventType::Contract
+                    && e.event.contract_id.as_ref() == Some(&contract_id)
+            })
+            .map(|e| match e.event.body {
+                ContractEventBody::V0(v0) => (v0.topics.to_vec(), v0.data),
+            })
+            .collect();
+        // Authorizations refer to the token address, so normalize it to the
+        // reference address in order to make the outcomes comparable.
+        let token_address = token.address.to_sc_address().unwrap();
+        let auth = self
+            .host
+            .get_authenticated_authorizations()
+            .unwrap()
+            .into_iter()
+            .map(|(address, mut invocation)| {
+                replace_contract_address(&mut invocation, &token_address, reference_address);
+                (address, invocation)
+            })
+            .collect();
+        Sep41CallOutcome {
+            result,
+            events,
+            auth,
+        }
+    }
+}
+
+fn replace_contract_address(
+    invocation: &mut SorobanAuthorizedInvocation,
+    from: &ScAddress,
+    to: &ScAddress,
+) {
+    if let SorobanAuthorizedFunction::ContractFn(args) = &mut invocation.function {
+        if &args.contract_address == from {
+            args.contract_address = to.clone();
+        }
+    }
+    let mut sub_invocations = invocation.sub_invocations.to_vec();
+    for sub_invocation in sub_invocations.iter_mut() {
+        replace_contract_address(sub_invocation, from, to);
+    }
+    invocation.sub_invocations = sub_invocations.try_into().unwrap();
+}
+
+/// Deploys the reference Stellar Asset Contract for a
+/// [`REFERENCE_ASSET_CODE`] asset issued by `issuer`.
+///
+/// The accounts of `issuer` and every holder are created in the host storage,
+/// and every holder gets an authorized trustline with the provided balance.
+/// The holders must not have the accounts yet.
+pub fn create_reference_token<'a>(
+    host: &'a Host,
+    issuer: &SigningKey,
+    holders: &[(&SigningKey, i64)],
+) -> TestStellarAssetContract<'a> {
+    let issuer_id = signing_key_to_account_id(issuer);
+    create_account(
+        host,
+        &issuer_id,
+        vec![(issuer, 100)],
+        10_000_000,
+        1,
+        [1, 0, 0, 0],
+        None,
+        None,
+        AccountFlags::RevocableFlag as u32,
+    );
+    let asset_code = AssetCode4(REFERENCE_ASSET_CODE);
+    for (holder, balance) in holders {
+        let account_id = signing_key_to_account_id(holder);
+        create_account(
+            host,
+            &account_id,
+            vec![(*holder, 1)],
+            0,
+            1,
+            [1, 0, 0, 0],
+            None,
+            None,
+            0,
+        );
+        let asset = TrustLineAsset::CreditAlphanum4(AlphaNum4 {
+            asset_code: asset_code.clone(),
+            issuer: issuer_id.clone(),
+        });
+        let key = host
+            .to_trustline_key(account_id.clone(), asset.clone())
+            .unwrap();
+        let trustline = TrustLineEntry {
+            account_id,
+            asset,
+            balance: *balance,
+            limit: i64::MAX,
+            flags: TrustLineFlags::AuthorizedFlag as u32,
+            ext: TrustLineEntryExt::V0,
+        };
+        host.add_ledger_entry(
+            &key,
+            &new_ledger_entry_from_data(LedgerEntryData::Trustline(trustline)),
+            None,
+        )
+        .unwrap();
+    }
+    TestStellarAssetContract::new_from_asset(
+        host,
+        Asset::CreditAlphanum4(AlphaNum4 {
+            asset_code,
+            issuer: issuer_id,
+        }),
+    )
+    .unwrap()
+}
@@ -1,23 +1,32 @@
+#[cfg(test)]
 use crate::{
     builtin_contracts::{
-        base_types::{Address, MuxedAddress, String},
-        testutils::{authorize_single_invocation, ContractTypeVec, TestSigner},
+        base_types::MuxedAddress, stellar_asset_contract::public_types::AssetInfoDetails,
     },
     events::HostEvent,
-    xdr::{
-        Asset, ContractEvent, ContractEventBody, ContractEventType, ContractEventV0,
-        ExtensionPoint, Limited, WriteXdr,
+    xdr::{ContractEvent, ContractEventBody, ContractEventType, ContractEventV0, ExtensionPoint},
+    Val,
+};
+use crate::{
+    builtin_contracts::{
+        base_types::{Address, String},
+        testutils::{authorize_single_invocation, ContractTypeVec, TestSigner},
     },
-    Env, Host, HostError, Symbol, TryFromVal, TryIntoVal, Val, DEFAULT_XDR_RW_LIMITS,
+    xdr::{Asset, Limited, WriteXdr},
+    AddressObject, Env, Host, HostError, Symbol, TryFromVal, TryIntoVal, DEFAULT_XDR_RW_LIMITS,
 };
 
-pub(crate) struct TestStellarAssetContract<'a> {
+/// Client for calling the Stellar Asset Contract (or any token contract
+/// implementing the same interface) in tests.
+pub struct TestStellarAssetContract<'a> {
     pub(crate) address: Address,
     host: &'a Host,
 }
 
 impl<'a> TestStellarAssetContract<'a> {
-    pub(crate) fn new_from_asset(host: &'a Host, asset: Asset) -> Result<Self, HostError> {
+    /// Deploys the Stellar Asset Contract for `asset` and creates a client
+    /// for it.
+    pub fn new_from_asset(host: &'a Host, asset: Asset) -> Result<Self, HostError> {
         use crate::EnvBase;
         let mut asset_bytes_vec = Limited::new(vec![], DEFAULT_XDR_RW_LIMITS);
         // Note: only asset creation should be return error, otherwise
@@ -32,6 +41,24 @@ impl<'a> TestStellarAssetContract<'a> {
         })
     }
 
+    /// Creates a client for an arbitrary token contract that implements the
+    /// same interface as the Stellar Asset Contract.
+    pub(crate) fn new_from_address(host: &'a Host, address: Address) -> Self {
+        Self { address, host }
+    }
+
+    /// Creates a client for an arbitrary token contract deployed at
+    /// `address` (e.g. a Wasm token registered in the test host).
+    pub fn new_from_address_object(
+        host: &'a Host,
+        address: AddressObject,
+    ) -> Result<Self, HostError> {
+        Ok(Self::new_from_address(
+            host,
+            Address::try_from_val(host, &address)?,
+        ))
+    }
+
     pub(crate) fn allowance(&self, from: Address, spender: Address) -> Result<i128, HostError> {
         Ok(self
             .host
@@ -66,6 +93,7 @@ impl<'a> TestStellarAssetContract<'a> {
             .try_into()?)
     }
 
+    #[cfg(test)]
     pub(crate) fn test_event(&self, topics: ContractTypeVec, data: Val) -> HostEvent {
         let event = ContractEvent {
             ext: ExtensionPoint::V0,
@@ -120,6 +148,7 @@ impl<'a> TestStellarAssetContract<'a> {
             .try_into_val(self.host)?)
     }
 
+    #[cfg(test)]
     pub(crate) fn authorized(&self, addr: Address) -> Result<bool, HostError> {
         Ok(self
             .host
@@ -144,6 +173,7 @@ impl<'a> TestStellarAssetContract<'a> {
         )
     }
 
+    #[cfg(test)]
     pub(crate) fn transfer_muxed(
         &self,
         from: &TestSigner,
@@ -171,6 +201,21 @@ impl<'a> TestStellarAssetContract<'a> {
         )
     }
 
+    #[cfg(test)]
+    pub(crate) fn transfer_from_muxed(
+        &self,
+        spender: &TestSigner,
+        from: Address,
+        to: MuxedAddress,
+        amount: i128,
+    ) -> Result<(), HostError> {
+        self.call_with_single_signer(
+            spender,
+            "transfer_from",
+            test_vec![self.host, spender.address(self.host), from, to, amount],
+        )
+    }
+
     pub(crate) fn burn(&self, from: &TestSigner, amount: i128) -> Result<(), HostError> {
         self.call_with_single_signer(
             from,
@@ -192,6 +237,7 @@ impl<'a> TestStellarAssetContract<'a> {
         )
     }
 
+    #[cfg(test)]
     pub(crate) fn set_authorized(
         &self,
         admin: &TestSigner,
@@ -205,6 +251,7 @@ impl<'a> TestStellarAssetContract<'a> {
         )
     }
 
+    #[cfg(test)]
     pub(crate) fn mint(
         &self,
         admin: &TestSigner,
@@ -214,6 +261,17 @@ impl<'a> TestStellarAssetContract<'a> {
         self.call_with_single_signer(admin, "mint", test_vec![self.host, to, amount])
     }
 
+    #[cfg(test)]
+    pub(crate) fn mint_muxed(
+        &self,
+        admin: &TestSigner,
+        to: MuxedAddress,
+        amount: i128,
+    ) -> Result<(), HostError> {
+        self.call_with_single_signer(admin, "mint", test_vec![self.host, to, amount])
+    }
+
+    #[cfg(test)]
     pub(crate) fn clawback(
         &self,
         admin: &TestSigner,
@@ -223,6 +281,7 @@ impl<'a> TestStellarAssetContract<'a> {
         self.call_with_single_signer(admin, "clawback", test_vec![self.host, from, amount])
     }
 
+    #[cfg(test)]
     pub(crate) fn set_admin(
         &self,
         admin: &TestSigner,
@@ -231,6 +290,7 @@ impl<'a> TestStellarAssetContract<'a> {
         self.call_with_single_signer(admin, "set_admin", test_vec![self.host, new_admin])
     }
 
+    #[cfg(test)]
     pub(crate) fn admin(&self) -> Result<Address, HostError> {
         self.host
             .call(
@@ -271,4 +331,15 @@ impl<'a> TestStellarAssetContract<'a> {
             )?
             .try_into_val(self.host)
     }
+
+    #[cfg(test)]
+    pub(crate) fn asset_info(&self) -> Result<AssetInfoDetails, HostError> {
+        self.host
+            .call(
+                self.address.clone().into(),
+                Symbol::try_from_val(self.host, &"asset_info")?,
+                test_vec![self.host].into(),
+            )?
+            .try_into_val(self.host)
+    }
 }
@@ -1,8 +1,16 @@
-use ark_bn254::{Bn254, Fq, Fr, G1Affine, G1Projective, G2Affine};
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
 use ark_ec::pairing::PairingOutput;
 
 use crate::{
-    cost_runner::{CostRunner, CostType},
+    budget::CostTracker,
+    cost_runner::{
+        CostRunner, CostType,
+        ExperimentalCostType::{
+            Bn254FrAddSub, Bn254FrInv, Bn254FrMul, Bn254FrPow, Bn254FrToU256, Bn254G1Msm,
+            Bn254G2Add, Bn254G2Msm, Bn254G2Mul, Bn254G2ProjectiveToAffine, Bn254HashToG1,
+            Bn254MapFpToG1,
+        },
+    },
     crypto::bn254::BN254_FP_SERIALIZED_SIZE,
     impl_const_cost_runner_for_bls_consume_sample, impl_const_cost_runner_for_bls_deref_sample,
     impl_lin_cost_runner_for_bls_deref_sample,
@@ -26,6 +34,25 @@ pub struct Bn254G1MulRun;
 pub struct Bn254PairingRun;
 pub struct Bn254FrFromU256Run;
 
+// The BN254 G2 arithmetic, G2 MSM, hashing to G1 and the scalar field
+// arithmetic (apart from `Bn254FrFromU256`) have no cost types of their own
+// yet and are charged with the cost types that upper-bound them. These
+// experimental runners measure the actual BN254 costs, which is what the
+// borrowed cost types need to be checked against.
+pub struct Bn254G2ProjectiveToAffineRun;
+pub struct Bn254G2AddRun;
+pub struct Bn254G2MulRun;
+pub struct Bn254G1MsmRun;
+pub struct Bn254G2MsmRun;
+pub struct Bn254MapFpToG1Run;
+pub struct Bn254HashToG1Run;
+pub struct Bn254FrToU256Run;
+pub struct Bn254FrAddRun;
+pub struct Bn254FrSubRun;
+pub struct Bn254FrMulRun;
+pub struct Bn254FrPowRun;
+pub struct Bn254FrInvRun;
+
 #[derive(Clone)]
 pub struct Bn254G1ProjectiveToAffineSample(pub G1Projective);
 #[derive(Clone)]
@@ -46,6 +73,28 @@ pub struct Bn254G2CheckPointOnCurveSample(pub G2Affine, pub ContractCostType);
 pub struct Bn254G2CheckPointInSubgroupSample(pub G2Affine);
 #[derive(Clone)]
 pub struct Bn254FrFromU256Sample(pub U256Val);
+#[derive(Clone)]
+pub struct Bn254G2ProjectiveToAffineSample(pub G2Projective);
+#[derive(Clone)]
+pub struct Bn254G2AddSample(pub G2Affine, pub G2Affine);
+#[derive(Clone)]
+pub struct Bn254G2MulSample(pub G2Affine, pub Fr);
+#[derive(Clone)]
+pub struct Bn254G1MsmSample(pub Vec<G1Affine>, pub Vec<Fr>);
+#[derive(Clone)]
+pub struct Bn254G2MsmSample(pub Vec<G2Affine>, pub Vec<Fr>);
+#[derive(Clone)]
+pub struct Bn254MapFpToG1Sample(pub Fq);
+#[derive(Clone)]
+pub struct Bn254HashToG1Sample(pub Vec<u8>, pub Vec<u8>);
+#[derive(Clone)]
+pub struct Bn254FrToU256Sample(pub Fr);
+#[derive(Clone)]
+pub struct Bn254FrAddSubMulSample(pub Fr, pub Fr);
+#[derive(Clone)]
+pub struct Bn254FrPowSample(pub Fr, pub u64);
+#[derive(Clone)]
+pub struct Bn254FrInvSample(pub Fr);
 
 impl_const_cost_runner_for_bls_consume_sample!(
     Bn254G1ProjectiveToAffineRun,
@@ -180,3 +229,249 @@ impl_const_cost_runner_for_bls_deref_sample!(
     bool,
     pt
 );
+
+// experimental
+
+macro_rules! impl_experiment_const_cost_runner_for_bn254_consume_sample {
+    ($runner: ident, $cost: ident, $host_fn: ident, $sample: ident, $rt: ty, $($arg: ident),*) => {
+        impl CostRunner for $runner {
+            const COST_TYPE: CostType = CostType::Experimental($cost);
+
+            const RUN_ITERATIONS: u64 = 1;
+
+            type SampleType = $sample;
+
+            type RecycledType = (Option<$sample>, Option<$rt>);
+
+            fn run_iter(host: &Host, _iter: u64, sample: $sample) -> Self::RecycledType {
+                let $sample($( $arg ),*) = sample;
+                let res = host.$host_fn($($arg),*).unwrap();
+                black_box((None, Some(res)))
+            }
+
+            fn run_baseline_iter(
+                host: &Host,
+                _iter: u64,
+                sample: $sample,
+            ) -> Self::RecycledType {
+                black_box(
+                    host.charge_budget(ContractCostType::Int256AddSub, None)
+                        .unwrap(),
+                );
+                black_box((Some(sample), None))
+            }
+
+            fn get_tracker(_host: &Host, _sample: &$sample) -> CostTracker {
+                CostTracker {
+                    iterations: Self::RUN_ITERATIONS,
+                    inputs: None,
+                    cpu: 0,
+                    mem: 0,
+                }
+            }
+        }
+    };
+}
+
+macro_rules! impl_experiment_lin_cost_runner_for_bn254_msm {
+    ($runner: ident, $cost: ident, $host_fn: ident, $sample: ident, $rt: ty) => {
+        impl CostRunner for $runner {
+            const COST_TYPE: CostType = CostType::Experimental($cost);
+
+            const RUN_ITERATIONS: u64 = 100;
+
+            type SampleType = $sample;
+
+            type RecycledType = ($sample, Option<$rt>);
+
+            fn run_iter(host: &Host, _iter: u64, sample: $sample) -> Self::RecycledType {
+                let $sample(vp, vs) = &sample;
+                let res = host.$host_fn(vp, vs).unwrap();
+                black_box((sample, Some(res)))
+            }
+
+            fn run_baseline_iter(host: &Host, _iter: u64, sample: $sample) -> Self::RecycledType {
+                black_box(
+                    host.charge_budget(ContractCostType::Int256AddSub, None)
+                        .unwrap(),
+                );
+                black_box((sample, None))
+            }
+
+            fn get_tracker(_host: &Host, sample: &$sample) -> CostTracker {
+                CostTracker {
+                    iterations: Self::RUN_ITERATIONS,
+                    inputs: Some(sample.0.len() as u64),
+                    cpu: 0,
+                    mem: 0,
+                }
+            }
+        }
+    };
+}
+
+impl_experiment_const_cost_runner_for_bn254_consume_sample!(
+    Bn254G2ProjectiveToAffineRun,
+    Bn254G2ProjectiveToAffine,
+    bn254_g2_projective_into_affine,
+    Bn254G2ProjectiveToAffineSample,
+    G2Affine,
+    p0
+);
+impl_experiment_const_cost_runner_for_bn254_consume_sample!(
+    Bn254G2AddRun,
+    Bn254G2Add,
+    bn254_g2_add_internal,
+    Bn254G2AddSample,
+    G2Projective,
+    p0,
+    p1
+);
+impl_experiment_const_cost_runner_for_bn254_consume_sample!(
+    Bn254G2MulRun,
+    Bn254G2Mul,
+    bn254_g2_mul_internal,
+    Bn254G2MulSample,
+    G2Projective,
+    p0,
+    scalar
+);
+
+impl_experiment_const_cost_runner_for_bn254_consume_sample!(
+    Bn254MapFpToG1Run,
+    Bn254MapFpToG1,
+    bn254_map_fp_to_g1_internal,
+    Bn254MapFpToG1Sample,
+    G1Affine,
+    fp
+);
+
+impl_experiment_const_cost_runner_for_bn254_consume_sample!(
+    Bn254FrToU256Run,
+    Bn254FrToU256,
+    bn254_fr_to_u256val,
+    Bn254FrToU256Sample,
+    U256Val,
+    scalar
+);
+
+crate::impl_experiment_const_cost_runner_for_bls_deref_sample!(
+    Bn254FrAddRun,
+    Bn254FrAddSub,
+    bn254_fr_add_internal,
+    Bn254FrAddSubMulSample,
+    (),
+    lhs,
+    rhs
+);
+crate::impl_experiment_const_cost_runner_for_bls_deref_sample!(
+    Bn254FrSubRun,
+    Bn254FrAddSub,
+    bn254_fr_sub_internal,
+    Bn254FrAddSubMulSample,
+    (),
+    lhs,
+    rhs
+);
+crate::impl_experiment_const_cost_runner_for_bls_deref_sample!(
+    Bn254FrMulRun,
+    Bn254FrMul,
+    bn254_fr_mul_internal,
+    Bn254FrAddSubMulSample,
+    (),
+    lhs,
+    rhs
+);
+crate::impl_experiment_const_cost_runner_for_bls_deref_sample!(
+    Bn254FrInvRun,
+    Bn254FrInv,
+    bn254_fr_inv_internal,
+    Bn254FrInvSample,
+    Fr,
+    lhs
+);
+
+impl_experiment_lin_cost_runner_for_bn254_msm!(
+    Bn254G1MsmRun,
+    Bn254G1Msm,
+    bn254_g1_msm_internal,
+    Bn254G1MsmSample,
+    G1Projective
+);
+impl_experiment_lin_cost_runner_for_bn254_msm!(
+    Bn254G2MsmRun,
+    Bn254G2Msm,
+    bn254_g2_msm_internal,
+    Bn254G2MsmSample,
+    G2Projective
+);
+
+impl CostRunner for Bn254HashToG1Run {
+    const COST_TYPE: CostType = CostType::Experimental(Bn254HashToG1);
+
+    const RUN_ITERATIONS: u64 = 100;
+
+    type SampleType = Bn254HashToG1Sample;
+
+    type RecycledType = (Bn254HashToG1Sample, Option<G1Affine>);
+
+    fn run_iter(host: &Host, _iter: u64, sample: Bn254HashToG1Sample) -> Self::RecycledType {
+        let Bn254HashToG1Sample(domain, msg) = &sample;
+        let res = host.bn254_hash_to_g1_internal(domain, msg).unwrap();
+        black_box((sample, Some(res)))
+    }
+
+    fn run_baseline_iter(
+        host: &Host,
+        _iter: u64,
+        sample: Bn254HashToG1Sample,
+    ) -> Self::RecycledType {
+        black_box(
+            host.charge_budget(ContractCostType::Int256AddSub, None)
+                .unwrap(),
+        );
+        black_box((sample, None))
+    }
+
+    fn get_tracker(_host: &Host, sample: &Bn254HashToG1Sample) -> CostTracker {
+        CostTracker {
+            iterations: Self::RUN_ITERATIONS,
+            inputs: Some(sample.1.len() as u64),
+            cpu: 0,
+            mem: 0,
+        }
+    }
+}
+
+impl CostRunner for Bn254FrPowRun {
+    const COST_TYPE: CostType = CostType::Experimental(Bn254FrPow);
+
+    const RUN_ITERATIONS: u64 = 1;
+
+    type SampleType = Bn254FrPowSample;
+
+    type RecycledType = (Option<Bn254FrPowSample>, Option<Fr>);
+
+    fn run_iter(host: &Host, _iter: u64, sample: Bn254FrPowSample) -> Self::RecycledType {
+        let Bn254FrPowSample(lhs, rhs) = &sample;
+        let res = host.bn254_fr_pow_internal(lhs, rhs).unwrap();
+        black_box((Some(sample), Some(res)))
+    }
+
+    fn run_baseline_iter(host: &Host, _iter: u64, sample: Bn254FrPowSample) -> Self::RecycledType {
+        black_box(
+            host.charge_budget(ContractCostType::Int256AddSub, None)
+                .unwrap(),
+        );
+        black_box((Some(sample), None))
+    }
+
+    fn get_tracker(_host: &Host, sample: &Bn254FrPowSample) -> CostTracker {
+        CostTracker {
+            iterations: Self::RUN_ITERATIONS,
+            inputs: Some(64 - sample.1.leading_zeros() as u64),
+            cpu: 0,
+            mem: 0,
+        }
+    }
+}
@@ -0,0 +1,67 @@
+// SHA-512, BLAKE2b and BLAKE3 are charged as SHA-256 over their full input
+// length until they have cost types of their own. These runners measure
+// their actual costs, linear in the input length, which those cost types
+// are to be calibrated from.
+use super::ExperimentalCostType::*;
+use crate::{
+    budget::CostTracker,
+    cost_runner::{CostRunner, CostType},
+    xdr::ContractCostType,
+    Host, U32Val,
+};
+use std::hint::black_box;
+
+pub struct ComputeSha512HashRun;
+pub struct ComputeBlake2bHashRun;
+pub struct ComputeBlake3HashRun;
+
+macro_rules! impl_experiment_linear_cost_runner_for_hash {
+    ($runner: ident, $cost: ident, $host_fn: ident $(, $arg: expr)*) => {
+        impl CostRunner for $runner {
+            const COST_TYPE: CostType = CostType::Experimental($cost);
+
+            type SampleType = Vec<u8>;
+
+            type RecycledType = (Option<Vec<u8>>, Vec<u8>);
+
+            fn run_iter(host: &Host, _iter: u64, sample: Vec<u8>) -> Self::RecycledType {
+                let hash = black_box(host.$host_fn(sample.as_slice() $(, $arg)*).unwrap());
+                (Some(hash), sample)
+            }
+
+            fn run_baseline_iter(host: &Host, _iter: u64, sample: Vec<u8>) -> Self::RecycledType {
+                black_box(
+                    host.charge_budget(ContractCostType::Int256AddSub, None)
+                        .unwrap(),
+                );
+                black_box((None, sample))
+            }
+
+            fn get_tracker(_host: &Host, sample: &Vec<u8>) -> CostTracker {
+                CostTracker {
+                    iterations: Self::RUN_ITERATIONS,
+                    inputs: Some(sample.len() as u64),
+                    cpu: 0,
+                    mem: 0,
+                }
+            }
+        }
+    };
+}
+
+impl_experiment_linear_cost_runner_for_hash!(
+    ComputeSha512HashRun,
+    ComputeSha512Hash,
+    sha512_hash_from_bytes
+);
+impl_experiment_linear_cost_runner_for_hash!(
+    ComputeBlake2bHashRun,
+    ComputeBlake2bHash,
+    blake2b_hash_from_bytes,
+    U32Val::from(32)
+);
+impl_experiment_linear_cost_runner_for_hash!(
+    ComputeBlake3HashRun,
+    ComputeBlake3Hash,
+    blake3_hash_from_bytes
+);
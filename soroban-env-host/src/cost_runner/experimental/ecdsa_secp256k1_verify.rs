@@ -31,7 +31,7 @@ impl CostRunner for EcdsaSecp256k1VerifyRun {
 
     fn run_iter(host: &crate::Host, _iter: u64, sample: Self::SampleType) -> Self::RecycledType {
         black_box(
-            host.ecdsa_secp256k1_verify_signature(&sample.pub_key, &sample.msg_hash, &sample.sig)
+            host.secp256k1_verify_signature(&sample.pub_key, &sample.msg_hash, &sample.sig)
                 .unwrap(),
         );
         black_box(sample)
@@ -32,8 +32,12 @@ impl CostRunner for EcdsaSecp256r1RecoverRun {
 
     fn run_iter(host: &crate::Host, _iter: u64, sample: Self::SampleType) -> Self::RecycledType {
         black_box(
-            host.ecdsa_secp256r1_recover_key(&sample.msg_hash, &sample.sig, sample.recovery_id)
-                .unwrap(),
+            host.recover_key_ecdsa_secp256r1_internal(
+                &sample.msg_hash,
+                &sample.sig,
+                sample.recovery_id,
+            )
+            .unwrap(),
         );
         black_box(sample)
     }
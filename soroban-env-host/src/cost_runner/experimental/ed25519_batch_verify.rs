@@ -0,0 +1,61 @@
+use crate::{
+    budget::CostTracker,
+    cost_runner::{CostRunner, CostType},
+};
+use ed25519_dalek::{Signature, VerifyingKey};
+use std::hint::black_box;
+
+use super::ExperimentalCostType;
+
+pub struct Ed25519BatchVerifyRun;
+
+#[derive(Clone)]
+pub struct Ed25519BatchVerifySample {
+    pub sigs: Vec<(VerifyingKey, Vec<u8>, Signature)>,
+}
+
+// Measures `verify_sig_ed25519_batch` past the input decoding, with the number
+// of signatures as the input. The host charges every signature of the batch as
+// three `VerifyEd25519Sig` (the torsion checks, the challenge and its share of
+// the combined check) and two `ComputeEd25519PubKey`, which this is compared
+// against.
+impl CostRunner for Ed25519BatchVerifyRun {
+    const COST_TYPE: CostType = CostType::Experimental(ExperimentalCostType::Ed25519BatchVerify);
+
+    const RUN_ITERATIONS: u64 = 1;
+
+    type SampleType = Ed25519BatchVerifySample;
+
+    type RecycledType = Self::SampleType;
+
+    fn run_iter(host: &crate::Host, _iter: u64, sample: Self::SampleType) -> Self::RecycledType {
+        let entries: Vec<_> = sample
+            .sigs
+            .iter()
+            .map(|(key, msg, sig)| {
+                host.ed25519_batch_entry(msg, key, sig)
+                    .unwrap()
+                    .expect("torsion-free key")
+            })
+            .collect();
+        black_box(host.ed25519_batch_check(&entries).unwrap());
+        black_box(sample)
+    }
+
+    fn get_tracker(_host: &crate::Host, sample: &Self::SampleType) -> CostTracker {
+        CostTracker {
+            iterations: Self::RUN_ITERATIONS,
+            inputs: Some(sample.sigs.len() as u64),
+            cpu: 0,
+            mem: 0,
+        }
+    }
+
+    fn run_baseline_iter(
+        _host: &crate::Host,
+        _iter: u64,
+        sample: Self::SampleType,
+    ) -> Self::RecycledType {
+        black_box(sample)
+    }
+}
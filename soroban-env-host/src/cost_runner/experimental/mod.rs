@@ -1,17 +1,25 @@
 mod bls12_381;
+mod compute_hash;
 mod decode_secp256r1_sig;
 mod ecdsa_secp256k1_verify;
 mod ecdsa_secp256r1_recover;
+mod ed25519_batch_verify;
 mod ed25519_scalar_mut;
+mod poseidon;
 mod read_xdr;
+mod schnorr_secp256k1_verify;
 mod sec1_decode_point_compressed;
 
 pub use bls12_381::*;
+pub use compute_hash::*;
 pub use decode_secp256r1_sig::*;
 pub use ecdsa_secp256k1_verify::*;
 pub use ecdsa_secp256r1_recover::*;
+pub use ed25519_batch_verify::*;
 pub use ed25519_scalar_mut::*;
+pub use poseidon::*;
 pub use read_xdr::*;
+pub use schnorr_secp256k1_verify::*;
 pub use sec1_decode_point_compressed::*;
 
 use crate::xdr::Name;
@@ -20,6 +28,7 @@ use core::fmt;
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ExperimentalCostType {
     EdwardsPointCurve25519ScalarMul,
+    Ed25519BatchVerify,
     ReadXdrByteArray,
     EcdsaSecp256r1Recover,
     Sec1DecodePointCompressed,
@@ -32,6 +41,24 @@ pub enum ExperimentalCostType {
     Bls12381Fp2DeserializeUncompressed,
     Bls12381G1ComputeYFromX,
     Bls12381G2ComputeYFromX,
+    Bn254G2ProjectiveToAffine,
+    Bn254G2Add,
+    Bn254G2Mul,
+    Bn254G1Msm,
+    Bn254G2Msm,
+    Bn254MapFpToG1,
+    Bn254HashToG1,
+    Bn254FrToU256,
+    Bn254FrAddSub,
+    Bn254FrMul,
+    Bn254FrPow,
+    Bn254FrInv,
+    PoseidonPermutation,
+    Poseidon2Permutation,
+    SchnorrSecp256k1Verify,
+    ComputeSha512Hash,
+    ComputeBlake2bHash,
+    ComputeBlake3Hash,
 }
 
 impl Name for ExperimentalCostType {
@@ -40,6 +67,7 @@ impl Name for ExperimentalCostType {
             ExperimentalCostType::EdwardsPointCurve25519ScalarMul => {
                 "EdwardsPointCurve25519ScalarMul"
             }
+            ExperimentalCostType::Ed25519BatchVerify => "Ed25519BatchVerify",
             ExperimentalCostType::ReadXdrByteArray => "ReadXdrByteArray",
             ExperimentalCostType::EcdsaSecp256r1Recover => "EcdsaSecp256r1Recover",
             ExperimentalCostType::Sec1DecodePointCompressed => "Sec1DecodePointCompressed",
@@ -62,6 +90,24 @@ impl Name for ExperimentalCostType {
             }
             ExperimentalCostType::Bls12381G1ComputeYFromX => "Bls12381G1ComputeYFromX",
             ExperimentalCostType::Bls12381G2ComputeYFromX => "Bls12381G2ComputeYFromX",
+            ExperimentalCostType::Bn254G2ProjectiveToAffine => "Bn254G2ProjectiveToAffine",
+            ExperimentalCostType::Bn254G2Add => "Bn254G2Add",
+            ExperimentalCostType::Bn254G2Mul => "Bn254G2Mul",
+            ExperimentalCostType::Bn254G1Msm => "Bn254G1Msm",
+            ExperimentalCostType::Bn254G2Msm => "Bn254G2Msm",
+            ExperimentalCostType::Bn254MapFpToG1 => "Bn254MapFpToG1",
+            ExperimentalCostType::Bn254HashToG1 => "Bn254HashToG1",
+            ExperimentalCostType::Bn254FrToU256 => "Bn254FrToU256",
+            ExperimentalCostType::Bn254FrAddSub => "Bn254FrAddSub",
+            ExperimentalCostType::Bn254FrMul => "Bn254FrMul",
+            ExperimentalCostType::Bn254FrPow => "Bn254FrPow",
+            ExperimentalCostType::Bn254FrInv => "Bn254FrInv",
+            ExperimentalCostType::PoseidonPermutation => "PoseidonPermutation",
+            ExperimentalCostType::Poseidon2Permutation => "Poseidon2Permutation",
+            ExperimentalCostType::SchnorrSecp256k1Verify => "SchnorrSecp256k1Verify",
+            ExperimentalCostType::ComputeSha512Hash => "ComputeSha512Hash",
+            ExperimentalCostType::ComputeBlake2bHash => "ComputeBlake2bHash",
+            ExperimentalCostType::ComputeBlake3Hash => "ComputeBlake3Hash",
         }
     }
 }
@@ -0,0 +1,135 @@
+// The Poseidon permutations are charged for their constituent field
+// operations. These runners measure the actual permutation costs as a
+// function of the number of rounds, to check the per-operation charges
+// against.
+use ark_bn254::Fr;
+
+use super::ExperimentalCostType::*;
+use crate::{
+    budget::CostTracker,
+    cost_runner::{CostRunner, CostType},
+    crypto::poseidon::PoseidonParams,
+    xdr::ContractCostType,
+    Host,
+};
+use std::hint::black_box;
+
+pub struct PoseidonPermutationRun;
+pub struct Poseidon2PermutationRun;
+
+#[derive(Clone)]
+pub struct PoseidonPermutationSample {
+    pub state: Vec<Fr>,
+    pub d: u32,
+    pub rounds_f: u32,
+    pub rounds_p: u32,
+    pub mds: Vec<Vec<Fr>>,
+    pub round_constants: Vec<Vec<Fr>>,
+}
+
+#[derive(Clone)]
+pub struct Poseidon2PermutationSample {
+    pub state: Vec<Fr>,
+    pub d: u32,
+    pub rounds_f: u32,
+    pub rounds_p: u32,
+    pub mat_internal_diag_m_1: Vec<Fr>,
+    pub round_constants: Vec<Vec<Fr>>,
+}
+
+fn sample_params(t: usize, d: u32, rounds_f: u32, rounds_p: u32) -> PoseidonParams {
+    PoseidonParams {
+        t: t as u32,
+        d,
+        rounds_f,
+        rounds_p,
+    }
+}
+
+impl CostRunner for PoseidonPermutationRun {
+    const COST_TYPE: CostType = CostType::Experimental(PoseidonPermutation);
+
+    const RUN_ITERATIONS: u64 = 10;
+
+    type SampleType = PoseidonPermutationSample;
+
+    type RecycledType = Self::SampleType;
+
+    fn run_iter(host: &Host, _iter: u64, mut sample: Self::SampleType) -> Self::RecycledType {
+        let params = sample_params(
+            sample.state.len(),
+            sample.d,
+            sample.rounds_f,
+            sample.rounds_p,
+        );
+        host.poseidon_permutation_internal(
+            &mut sample.state,
+            &params,
+            &sample.mds,
+            &sample.round_constants,
+        )
+        .unwrap();
+        black_box(sample)
+    }
+
+    fn run_baseline_iter(host: &Host, _iter: u64, sample: Self::SampleType) -> Self::RecycledType {
+        black_box(
+            host.charge_budget(ContractCostType::Int256AddSub, None)
+                .unwrap(),
+        );
+        black_box(sample)
+    }
+
+    fn get_tracker(_host: &Host, sample: &Self::SampleType) -> CostTracker {
+        CostTracker {
+            iterations: Self::RUN_ITERATIONS,
+            inputs: Some(sample.round_constants.len() as u64),
+            cpu: 0,
+            mem: 0,
+        }
+    }
+}
+
+impl CostRunner for Poseidon2PermutationRun {
+    const COST_TYPE: CostType = CostType::Experimental(Poseidon2Permutation);
+
+    const RUN_ITERATIONS: u64 = 10;
+
+    type SampleType = Poseidon2PermutationSample;
+
+    type RecycledType = Self::SampleType;
+
+    fn run_iter(host: &Host, _iter: u64, mut sample: Self::SampleType) -> Self::RecycledType {
+        let params = sample_params(
+            sample.state.len(),
+            sample.d,
+            sample.rounds_f,
+            sample.rounds_p,
+        );
+        host.poseidon2_permutation_internal(
+            &mut sample.state,
+            &params,
+            &sample.mat_internal_diag_m_1,
+            &sample.round_constants,
+        )
+        .unwrap();
+        black_box(sample)
+    }
+
+    fn run_baseline_iter(host: &Host, _iter: u64, sample: Self::SampleType) -> Self::RecycledType {
+        black_box(
+            host.charge_budget(ContractCostType::Int256AddSub, None)
+                .unwrap(),
+        );
+        black_box(sample)
+    }
+
+    fn get_tracker(_host: &Host, sample: &Self::SampleType) -> CostTracker {
+        CostTracker {
+            iterations: Self::RUN_ITERATIONS,
+            inputs: Some(sample.round_constants.len() as u64),
+            cpu: 0,
+            mem: 0,
+        }
+    }
+}
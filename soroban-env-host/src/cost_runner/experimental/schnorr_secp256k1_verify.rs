@@ -0,0 +1,52 @@
+use crate::{
+    budget::CostTracker,
+    cost_runner::{CostRunner, CostType},
+};
+use std::hint::black_box;
+
+use super::ExperimentalCostType;
+
+pub struct SchnorrSecp256k1VerifyRun;
+
+#[derive(Clone)]
+pub struct SchnorrSecp256k1VerifySample {
+    pub pub_key: [u8; 32],
+    pub msg: Vec<u8>,
+    pub sig: [u8; 64],
+}
+
+impl CostRunner for SchnorrSecp256k1VerifyRun {
+    const COST_TYPE: CostType =
+        CostType::Experimental(ExperimentalCostType::SchnorrSecp256k1Verify);
+
+    const RUN_ITERATIONS: u64 = 1;
+
+    type SampleType = SchnorrSecp256k1VerifySample;
+
+    type RecycledType = Self::SampleType;
+
+    fn run_iter(host: &crate::Host, _iter: u64, sample: Self::SampleType) -> Self::RecycledType {
+        black_box(
+            host.verify_sig_schnorr_secp256k1_internal(&sample.pub_key, &sample.msg, &sample.sig)
+                .unwrap(),
+        );
+        black_box(sample)
+    }
+
+    fn get_tracker(_host: &crate::Host, sample: &Self::SampleType) -> CostTracker {
+        CostTracker {
+            iterations: Self::RUN_ITERATIONS,
+            inputs: Some(sample.msg.len() as u64),
+            cpu: 0,
+            mem: 0,
+        }
+    }
+
+    fn run_baseline_iter(
+        _host: &crate::Host,
+        _iter: u64,
+        sample: Self::SampleType,
+    ) -> Self::RecycledType {
+        black_box(sample)
+    }
+}
@@ -20,7 +20,7 @@ use ark_ec::{
     short_weierstrass::{Affine, Projective, SWCurveConfig},
     AffineRepr, CurveConfig, CurveGroup,
 };
-use ark_ff::{field_hashers::DefaultFieldHasher, BigInteger, Field, PrimeField};
+use ark_ff::{field_hashers::DefaultFieldHasher, BigInt, BigInteger, Field, PrimeField};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
 use num_traits::Zero;
 use sha2::Sha256;
@@ -341,6 +341,36 @@ impl Host {
         Ok(fr)
     }
 
+    // Unlike `fr_from_u256val`, rejects the values that are not less than the
+    // scalar field modulus instead of reducing them.
+    pub(crate) fn canonical_fr_from_u256val(
+        &self,
+        sv: U256Val,
+        tag: &str,
+    ) -> Result<Fr, HostError> {
+        self.charge_budget(ContractCostType::Bls12381FrFromU256, None)?;
+        let bytes = if let Ok(small) = U256Small::try_from(sv) {
+            U256::from(u64::from(small)).to_le_bytes()
+        } else {
+            let obj: U256Object = sv.try_into()?;
+            self.visit_obj(obj, |u: &U256| Ok(u.to_le_bytes()))?
+        };
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(chunk);
+            *limb = u64::from_le_bytes(word);
+        }
+        Fr::from_bigint(BigInt::new(limbs)).ok_or_else(|| {
+            self.err(
+                ScErrorType::Crypto,
+                ScErrorCode::InvalidInput,
+                format!("bls12-381 {tag}: scalar is not less than the field modulus").as_str(),
+                &[sv.to_val()],
+            )
+        })
+    }
+
     pub(crate) fn fr_to_u256val(&self, scalar: Fr) -> Result<U256Val, HostError> {
         self.charge_budget(ContractCostType::Bls12381FrToU256, None)?;
         // The `into_bigint` carries the majority of the cost. It performs the
@@ -753,6 +783,33 @@ impl Host {
         }
     }
 
+    // Verifies the KZG proof that the polynomial committed to by `commitment`
+    // evaluates to `y` at `z` given the `[tau]G2` point of the trusted setup,
+    // i.e. checks `e(commitment - [y]G1, -G2) * e(proof, [tau]G2 - [z]G2) == 1`.
+    // This is `verify_kzg_proof_impl` of the EIP-4844 consensus specs.
+    pub(crate) fn kzg_verify_point_evaluation_internal(
+        &self,
+        commitment: G1Affine,
+        z: Fr,
+        y: Fr,
+        proof: G1Affine,
+        tau_g2: G2Affine,
+    ) -> Result<Bool, HostError> {
+        let y_g1 = self.g1_mul_internal(G1Affine::generator(), y)?;
+        let y_g1 = self.g1_projective_into_affine(y_g1)?;
+        let p_minus_y = self.g1_add_internal(commitment, -y_g1)?;
+        let p_minus_y = self.g1_projective_into_affine(p_minus_y)?;
+        let z_g2 = self.g2_mul_internal(G2Affine::generator(), z)?;
+        let z_g2 = self.g2_projective_into_affine(z_g2)?;
+        let x_minus_z = self.g2_add_internal(tau_g2, -z_g2)?;
+        let x_minus_z = self.g2_projective_into_affine(x_minus_z)?;
+        let output = self.pairing_internal(
+            &vec![p_minus_y, proof],
+            &vec![-G2Affine::generator(), x_minus_z],
+        )?;
+        self.check_pairing_output(&output)
+    }
+
     pub(crate) fn fr_add_internal(&self, lhs: &mut Fr, rhs: &Fr) -> Result<(), HostError> {
         self.charge_budget(ContractCostType::Bls12381FrAddSub, None)?;
         lhs.add_assign(rhs);
@@ -1,30 +1,87 @@
 use std::cmp::Ordering;
-use std::ops::{Add, Mul};
+use std::ops::{Add, AddAssign, Mul, MulAssign, SubAssign};
 
 use ark_bn254::{
-    g1::Config as G1Config, g2::Config as G2Config, Bn254, Fq12, Fr, G1Affine, G1Projective,
-    G2Affine,
+    g1::Config as G1Config, g2::Config as G2Config, Bn254, Fq, Fq12, Fr, G1Affine, G1Projective,
+    G2Affine, G2Projective,
 };
 use ark_ec::{
     pairing::{Pairing, PairingOutput},
+    scalar_mul::variable_base::VariableBaseMSM,
     short_weierstrass::{Affine, SWCurveConfig},
     CurveGroup,
 };
-use ark_ff::{Field, PrimeField};
+use ark_ff::{BigInteger, Field, MontFp, PrimeField};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use num_traits::Zero;
+use sha2::{Digest, Sha256};
 
 use crate::{
     budget::AsBudget,
     host_object::HostVec,
     xdr::{ContractCostType, ScBytes, ScErrorCode, ScErrorType},
-    Bool, BytesObject, Env, Host, HostError, TryFromVal, U256Object, U256Small, U256Val, Val,
-    VecObject, U256,
+    Bool, BytesObject, ConversionError, Env, ErrorHandler, Host, HostError, TryFromVal, U256Object,
+    U256Small, U256Val, Val, VecObject, U256,
 };
 
 pub(crate) const BN254_FP_SERIALIZED_SIZE: usize = 32;
 pub(crate) const BN254_FP2_SERIALIZED_SIZE: usize = BN254_FP_SERIALIZED_SIZE * 2;
 pub(crate) const BN254_G1_SERIALIZED_SIZE: usize = BN254_FP_SERIALIZED_SIZE * 2;
 pub(crate) const BN254_G2_SERIALIZED_SIZE: usize = BN254_FP2_SERIALIZED_SIZE * 2;
+pub(crate) const BN254_FR_SERIALIZED_SIZE: usize = 32;
+
+// Constants of the Shallue-van de Woestijne map (RFC 9380, section 6.6.1)
+// for the BN254 G1 curve `y^2 = x^3 + 3` with `Z = 1`.
+// `c1 = g(Z)`
+const SVDW_C1: Fq = MontFp!("4");
+// `c2 = -Z / 2`
+const SVDW_C2: Fq =
+    MontFp!("10944121435919637611123202872628637544348155578648911831344518947322613104291");
+// `c3 = sqrt(-g(Z) * (3 * Z^2 + 4 * A))`, with `sgn0(c3) == 0`
+const SVDW_C3: Fq = MontFp!("8815841940592487685674414971303048083897117035520822607866");
+// `c4 = -4 * g(Z) / (3 * Z^2 + 4 * A)`
+const SVDW_C4: Fq =
+    MontFp!("7296080957279758407415468581752425029565437052432607887563012631548408736189");
+const SVDW_Z: Fq = MontFp!("1");
+
+// Length of the field elements produced by `hash_to_field`:
+// `ceil((254 + 128) / 8)` bytes.
+const BN254_HASH_TO_FIELD_ELEM_LEN: usize = 48;
+
+// `expand_message_xmd` from RFC 9380 (section 5.3.1) with SHA-256, producing
+// the bytes of the two field elements hashed to by `hash_to_g1`. arkworks'
+// `DefaultFieldHasher` is not used, as it pads the first hash input to the
+// length of a field element rather than to the SHA-256 block size, which
+// only matches the RFC for 64-byte elements.
+fn bn254_expand_message_xmd(dst: &[u8], msg: &[u8]) -> [u8; 2 * BN254_HASH_TO_FIELD_ELEM_LEN] {
+    let update_dst_prime = |hasher: &mut Sha256| {
+        hasher.update(dst);
+        hasher.update([dst.len() as u8]);
+    };
+    let mut out = [0u8; 2 * BN254_HASH_TO_FIELD_ELEM_LEN];
+    let mut hasher = Sha256::new();
+    hasher.update([0u8; 64]);
+    hasher.update(msg);
+    hasher.update((out.len() as u16).to_be_bytes());
+    hasher.update([0u8]);
+    update_dst_prime(&mut hasher);
+    let b0 = hasher.finalize();
+    // `b_1 = H(b_0 || 1 || dst_prime)` and `b_i = H((b_0 ^ b_{i-1}) || i ||
+    // dst_prime)`, so starting from a zero `b_0` the same step covers both.
+    let mut bi = [0u8; 32];
+    for (i, chunk) in out.chunks_mut(32).enumerate() {
+        let mut hasher = Sha256::new();
+        for (b0, bi) in b0.iter().zip(bi.iter_mut()) {
+            *bi ^= b0;
+        }
+        hasher.update(bi);
+        hasher.update([i as u8 + 1]);
+        update_dst_prime(&mut hasher);
+        bi.copy_from_slice(&hasher.finalize());
+        chunk.copy_from_slice(&bi[..chunk.len()]);
+    }
+    out
+}
 
 #[inline(always)]
 fn units_of_fp<const EXPECTED_SIZE: usize>() -> u64 {
@@ -249,6 +306,271 @@ impl Host {
         Ok(p0.mul(scalar))
     }
 
+    // Charged as one `Bn254G1Mul` per pair and one `Bn254G1Add` to
+    // accumulate each product after the first. Pippenger shares the doublings
+    // across all the scalars, so this is an upper bound on the actual cost.
+    // Measured by the `Bn254G1Msm` experimental cost runner.
+    pub(crate) fn bn254_g1_msm_internal(
+        &self,
+        points: &[G1Affine],
+        scalars: &[Fr],
+    ) -> Result<G1Projective, HostError> {
+        if points.len() != scalars.len() || points.len() == 0 {
+            return Err(self.bn254_err_invalid_input(
+                format!(
+                    "bn254 G1 msm: invalid input vector lengths ({}, {})",
+                    points.len(),
+                    scalars.len()
+                )
+                .as_str(),
+            ));
+        }
+        let len = points.len() as u64;
+        self.as_budget()
+            .bulk_charge(ContractCostType::Bn254G1Mul, len, None)?;
+        self.as_budget()
+            .bulk_charge(ContractCostType::Bn254G1Add, len - 1, None)?;
+        Ok(G1Projective::msm_unchecked(points, scalars))
+    }
+
+    // Checks whether a well-formed point encoding is on the curve. Unlike
+    // `bn254_affine_deserialize`, a point that is not on the curve is not an
+    // error.
+    fn bn254_check_encoded_point_is_on_curve<const EXPECTED_SIZE: usize, P: SWCurveConfig>(
+        &self,
+        bo: BytesObject,
+        ct_curve: ContractCostType,
+        tag: &str,
+    ) -> Result<bool, HostError> {
+        let pt: Affine<P> = self.visit_obj(bo, |bytes: &ScBytes| {
+            self.bn254_deserialize_uncompressed_no_validate::<EXPECTED_SIZE, _>(
+                bytes.as_slice(),
+                tag,
+            )
+        })?;
+        self.bn254_check_point_is_on_curve(&pt, &ct_curve)
+    }
+
+    pub(crate) fn bn254_check_g1_bytesobj_is_on_curve(
+        &self,
+        bo: BytesObject,
+    ) -> Result<bool, HostError> {
+        self.bn254_check_encoded_point_is_on_curve::<BN254_G1_SERIALIZED_SIZE, G1Config>(
+            bo,
+            ContractCostType::Bn254G1CheckPointOnCurve,
+            "G1",
+        )
+    }
+
+    pub(crate) fn bn254_check_g2_bytesobj_is_on_curve(
+        &self,
+        bo: BytesObject,
+    ) -> Result<bool, HostError> {
+        self.bn254_check_encoded_point_is_on_curve::<BN254_G2_SERIALIZED_SIZE, G2Config>(
+            bo,
+            ContractCostType::Bn254G2CheckPointOnCurve,
+            "G2",
+        )
+    }
+
+    pub(crate) fn bn254_fp_deserialize_from_bytesobj(
+        &self,
+        bo: BytesObject,
+    ) -> Result<Fq, HostError> {
+        self.visit_obj(bo, |bytes: &ScBytes| {
+            self.bn254_deserialize_uncompressed_no_validate::<BN254_FP_SERIALIZED_SIZE, Fq>(
+                bytes.as_slice(),
+                "Fp",
+            )
+        })
+    }
+
+    // The straight-line Shallue-van de Woestijne map from RFC 9380 (section
+    // 6.6.1). The inputs are public, so the branches don't need to be constant
+    // time. Charging is done by the callers.
+    fn bn254_svdw_map_to_g1(&self, u: Fq) -> Result<G1Affine, HostError> {
+        let g = |x: Fq| x.square() * x + G1Config::COEFF_B;
+        let is_square = |x: Fq| !x.legendre().is_qnr();
+        let sgn0 = |x: Fq| x.into_bigint().is_odd();
+
+        let mut tv1 = u.square() * SVDW_C1;
+        let tv2 = Fq::ONE + tv1;
+        tv1 = Fq::ONE - tv1;
+        let tv3 = (tv1 * tv2).inverse().unwrap_or(Fq::zero());
+        let tv4 = u * tv1 * tv3 * SVDW_C3;
+        let x1 = SVDW_C2 - tv4;
+        let x2 = SVDW_C2 + tv4;
+        let x = if is_square(g(x1)) {
+            x1
+        } else if is_square(g(x2)) {
+            x2
+        } else {
+            (tv2.square() * tv3).square() * SVDW_C4 + SVDW_Z
+        };
+        // One of `g(x1)`, `g(x2)` and `g(x3)` is always a square, so this
+        // cannot fail unless the map constants are wrong.
+        let mut y = g(x).sqrt().ok_or_else(|| {
+            self.err(
+                ScErrorType::Crypto,
+                ScErrorCode::InternalError,
+                "bn254 map-to-curve: no square root",
+                &[],
+            )
+        })?;
+        if sgn0(u) != sgn0(y) {
+            y = -y;
+        }
+        Ok(G1Affine::new_unchecked(x, y))
+    }
+
+    // Charged as `Bn254G1Mul`. In the worst case the SVDW map does an
+    // inversion, two Legendre symbols and a square root (a single
+    // exponentiation as `p = 3 mod 4`), i.e. four 254-bit exponentiations in
+    // the base field. A scalar multiplication does 254 doublings and up to
+    // as many additions of points, each of which costs several field
+    // multiplications, so it bounds the map. Measured by the `Bn254MapFpToG1`
+    // experimental cost runner.
+    pub(crate) fn bn254_map_fp_to_g1_internal(&self, fp: Fq) -> Result<G1Affine, HostError> {
+        self.charge_budget(ContractCostType::Bn254G1Mul, None)?;
+        self.bn254_svdw_map_to_g1(fp)
+    }
+
+    // Hashes to G1 following the RFC 9380 ciphersuite
+    // `BN254G1_XMD:SHA-256_SVDW_RO_`. G1 has cofactor 1, so clearing the
+    // cofactor is a no-op.
+    //
+    // Charged as the SHA-256 calls of `expand_message_xmd`, the two
+    // map-to-curve calls as in `bn254_map_fp_to_g1_internal` and the
+    // `Bn254G1Add` of their results. Measured by the `Bn254HashToG1`
+    // experimental cost runner.
+    pub(crate) fn bn254_hash_to_g1_internal(
+        &self,
+        domain: &[u8],
+        msg: &[u8],
+    ) -> Result<G1Affine, HostError> {
+        let dst_len = domain.len();
+        if dst_len == 0 || dst_len > 255 {
+            return Err(self.err(
+                ScErrorType::Crypto,
+                ScErrorCode::InvalidInput,
+                format!(
+                    "bn254 hash_to_curve: invalid input dst length {dst_len}, must be > 0 and < 256"
+                )
+                .as_str(),
+                &[],
+            ));
+        }
+        // Produces two field elements of `ceil((254 + 128) / 8) = 48` bytes
+        // each, i.e. the expander runs 3 rounds of SHA-256, far below its
+        // limits. Every call hashes the domain followed by its length byte.
+        // The first one hashes the 64-byte zero block, the message, the
+        // 2-byte output length and a zero byte, and each round hashes a
+        // 32-byte digest and its 1-byte index.
+        let dst_prime_len = dst_len as u64 + 1;
+        self.as_budget().bulk_charge(
+            ContractCostType::ComputeSha256Hash,
+            1,
+            Some(64 + msg.len() as u64 + 3 + dst_prime_len),
+        )?;
+        self.as_budget().bulk_charge(
+            ContractCostType::ComputeSha256Hash,
+            3,
+            Some(33 + dst_prime_len),
+        )?;
+        self.as_budget()
+            .bulk_charge(ContractCostType::Bn254G1Mul, 2, None)?;
+        self.charge_budget(ContractCostType::Bn254G1Add, None)?;
+        let uniform_bytes = bn254_expand_message_xmd(domain, msg);
+        let (u0, u1) = uniform_bytes.split_at(BN254_HASH_TO_FIELD_ELEM_LEN);
+        let q0 = self.bn254_svdw_map_to_g1(Fq::from_be_bytes_mod_order(u0))?;
+        let q1 = self.bn254_svdw_map_to_g1(Fq::from_be_bytes_mod_order(u1))?;
+        Ok(q0.add(q1).into_affine())
+    }
+
+    pub(crate) fn bn254_g2_affine_deserialize_from_bytesobj(
+        &self,
+        bo: BytesObject,
+        subgroup_check: bool,
+    ) -> Result<G2Affine, HostError> {
+        self.bn254_affine_deserialize::<BN254_G2_SERIALIZED_SIZE, G2Config>(
+            bo,
+            ContractCostType::Bn254G2CheckPointOnCurve,
+            subgroup_check,
+            "G2",
+        )
+    }
+
+    // Charged as `Bls12381G2ProjectiveToAffine`: a single `Fp2` inversion
+    // and a few multiplications, over the 4-limb BN254 base field instead of
+    // the 6-limb BLS12-381 one. Measured by the `Bn254G2ProjectiveToAffine`
+    // experimental cost runner.
+    pub(crate) fn bn254_g2_projective_into_affine(
+        &self,
+        g2: G2Projective,
+    ) -> Result<G2Affine, HostError> {
+        self.charge_budget(ContractCostType::Bls12381G2ProjectiveToAffine, None)?;
+        Ok(g2.into_affine())
+    }
+
+    pub(crate) fn bn254_g2_affine_serialize_uncompressed(
+        &self,
+        g2: &G2Affine,
+    ) -> Result<BytesObject, HostError> {
+        let mut buf = [0u8; BN254_G2_SERIALIZED_SIZE];
+
+        self.bn254_serialize_uncompressed_into_slice::<BN254_G2_SERIALIZED_SIZE, _>(
+            g2, &mut buf, "G2",
+        )?;
+        self.add_host_object(self.scbytes_from_slice(&buf)?)
+    }
+
+    pub(crate) fn bn254_g2_projective_serialize_uncompressed(
+        &self,
+        g2: G2Projective,
+    ) -> Result<BytesObject, HostError> {
+        let g2_affine = self.bn254_g2_projective_into_affine(g2)?;
+        self.bn254_g2_affine_serialize_uncompressed(&g2_affine)
+    }
+
+    // Charged as `Bls12381G2Add`: the same mixed addition formula over the
+    // smaller `Fp2`. Measured by the `Bn254G2Add` experimental cost runner.
+    pub(crate) fn bn254_g2_add_internal(
+        &self,
+        p0: G2Affine,
+        p1: G2Affine,
+    ) -> Result<G2Projective, HostError> {
+        self.charge_budget(ContractCostType::Bls12381G2Add, None)?;
+        Ok(p0.add(p1))
+    }
+
+    // Charged as `Bls12381G2Mul`: the scalars of both curves are 255 bits at
+    // most, so the double-and-add runs the same number of steps over the
+    // smaller `Fp2`. Measured by the `Bn254G2Mul` experimental cost runner.
+    pub(crate) fn bn254_g2_mul_internal(
+        &self,
+        p0: G2Affine,
+        scalar: Fr,
+    ) -> Result<G2Projective, HostError> {
+        self.charge_budget(ContractCostType::Bls12381G2Mul, None)?;
+        Ok(p0.mul(scalar))
+    }
+
+    // Charged as `Bls12381G2Msm` per pair: the Pippenger buckets are sized by
+    // the (equally long) scalars, and every bucket addition is done in the
+    // smaller `Fp2`. Measured by the `Bn254G2Msm` experimental cost runner.
+    pub(crate) fn bn254_g2_msm_internal(
+        &self,
+        points: &[G2Affine],
+        scalars: &[Fr],
+    ) -> Result<G2Projective, HostError> {
+        self.msm_internal(
+            points,
+            scalars,
+            &ContractCostType::Bls12381G2Msm,
+            "bn254 G2",
+        )
+    }
+
     pub(crate) fn bn254_checked_g1_vec_from_vecobj(
         &self,
         vp: VecObject,
@@ -286,6 +608,98 @@ impl Host {
         Ok(fr)
     }
 
+    // Charged as `Bn254FrFromU256`, the same Montgomery conversion in the
+    // other direction, plus the allocation of the big-endian bytes. Measured
+    // by the `Bn254FrToU256` experimental cost runner.
+    pub(crate) fn bn254_fr_to_u256val(&self, scalar: Fr) -> Result<U256Val, HostError> {
+        self.charge_budget(ContractCostType::Bn254FrFromU256, None)?;
+        self.charge_budget(
+            ContractCostType::MemAlloc,
+            Some(BN254_FR_SERIALIZED_SIZE as u64),
+        )?;
+        let bytes: [u8; 32] = scalar
+            .into_bigint()
+            .to_bytes_be()
+            .try_into()
+            .map_err(|_| HostError::from(ConversionError))?;
+        let u = U256::from_be_bytes(bytes);
+        self.map_err(U256Val::try_from_val(self, &u))
+    }
+
+    // The scalar field has no BN254 cost types of its own. `Fr` shares the
+    // 4-limb Montgomery representation of the BN254 base field, so an
+    // addition or subtraction is bounded by `Bn254EncodeFp` (a Montgomery
+    // reduction and the serialization of the result), and a multiplication
+    // by `Bn254DecodeFp` (a Montgomery multiplication and the parsing of the
+    // input). Measured by the `Bn254FrAddSub` and `Bn254FrMul` experimental
+    // cost runners.
+    pub(crate) fn bn254_fr_add_internal(&self, lhs: &mut Fr, rhs: &Fr) -> Result<(), HostError> {
+        self.charge_budget(ContractCostType::Bn254EncodeFp, None)?;
+        lhs.add_assign(rhs);
+        Ok(())
+    }
+
+    pub(crate) fn bn254_fr_sub_internal(&self, lhs: &mut Fr, rhs: &Fr) -> Result<(), HostError> {
+        self.charge_budget(ContractCostType::Bn254EncodeFp, None)?;
+        lhs.sub_assign(rhs);
+        Ok(())
+    }
+
+    pub(crate) fn bn254_fr_mul_internal(&self, lhs: &mut Fr, rhs: &Fr) -> Result<(), HostError> {
+        self.charge_budget(ContractCostType::Bn254DecodeFp, None)?;
+        lhs.mul_assign(rhs);
+        Ok(())
+    }
+
+    // Exponentiation and inversion have no BN254 counterpart to bound them
+    // and are charged with the BLS12-381 `Fr` cost types: both scalar fields
+    // are 4-limb Montgomery fields handled by the same `ark_ff` routines,
+    // and the 254-bit BN254 modulus takes one step less than the 255-bit
+    // BLS12-381 one. Measured by the `Bn254FrPow` and `Bn254FrInv`
+    // experimental cost runners.
+    pub(crate) fn bn254_fr_pow_internal(&self, lhs: &Fr, rhs: &u64) -> Result<Fr, HostError> {
+        self.charge_budget(
+            ContractCostType::Bls12381FrPow,
+            Some(64 - rhs.leading_zeros() as u64),
+        )?;
+        Ok(lhs.pow(&[*rhs]))
+    }
+
+    pub(crate) fn bn254_fr_inv_internal(&self, lhs: &Fr) -> Result<Fr, HostError> {
+        if lhs.is_zero() {
+            return Err(self.bn254_err_invalid_input("bn254 scalar inversion input is zero"));
+        }
+        self.charge_budget(ContractCostType::Bls12381FrInv, None)?;
+        // `inverse()` returns `None` only if the rhs is zero, which we have
+        // checked upfront, so this cannot fail.
+        lhs.inverse().ok_or_else(|| {
+            self.err(
+                ScErrorType::Crypto,
+                ScErrorCode::InternalError,
+                "bn254 scalar inversion failed",
+                &[],
+            )
+        })
+    }
+
+    pub(crate) fn bn254_fr_vec_from_vecobj(&self, vs: VecObject) -> Result<Vec<Fr>, HostError> {
+        let len: u32 = self.vec_len(vs)?.into();
+        let mut scalars: Vec<Fr> = vec![];
+        self.charge_budget(
+            ContractCostType::MemAlloc,
+            Some(len as u64 * BN254_FR_SERIALIZED_SIZE as u64),
+        )?;
+        scalars.reserve(len as usize);
+        let _ = self.visit_obj(vs, |vs: &HostVec| {
+            for s in vs.iter() {
+                let ss = self.bn254_fr_from_u256val(U256Val::try_from_val(self, s)?)?;
+                scalars.push(ss);
+            }
+            Ok(())
+        })?;
+        Ok(scalars)
+    }
+
     pub(crate) fn bn254_pairing_internal(
         &self,
         vp1: &Vec<G1Affine>,
@@ -0,0 +1,136 @@
+//! Merkle inclusion proof verification.
+//!
+//! Proofs are verified over 32-byte nodes, with a selectable hash function
+//! (`sha256` or `keccak256`) applied to the 64-byte concatenation of every
+//! pair of nodes. The pairs are ordered either positionally, where the bits
+//! of the leaf index (least significant first) tell whether the current node
+//! is the right (bit set) or the left (bit unset) child, or by sorting the
+//! two nodes bytewise, as done by OpenZeppelin's `MerkleProof`.
+//!
+//! The leaf is passed already hashed, since the leaf encoding (and the domain
+//! separation from the inner nodes) is application-specific.
+use crate::{
+    budget::AsBudget,
+    crypto::sha256_hash_from_bytes_raw,
+    host::metered_clone::MeteredContainer,
+    host_object::HostVec,
+    xdr::{ContractCostType, ScErrorCode, ScErrorType},
+    BytesObject, Env, Host, HostError, Symbol, SymbolStr, TryFromVal, TryIntoVal, Val, VecObject,
+};
+
+pub(crate) const MERKLE_NODE_SIZE: usize = 32;
+/// Positional proofs can't be deeper than the number of bits in the index.
+pub(crate) const MERKLE_MAX_POSITIONAL_DEPTH: usize = 64;
+
+/// Hash functions supported for the Merkle proofs, identified by the `hash`
+/// symbol passed to the host function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MerkleHash {
+    Sha256,
+    Keccak256,
+}
+
+impl Host {
+    fn merkle_err_invalid_input(&self, msg: &str, args: &[Val]) -> HostError {
+        self.err(ScErrorType::Crypto, ScErrorCode::InvalidInput, msg, args)
+    }
+
+    pub(crate) fn merkle_hash_from_symbol(&self, hash: Symbol) -> Result<MerkleHash, HostError> {
+        let hash_str: SymbolStr = hash.try_into_val(self)?;
+        match hash_str.as_ref() {
+            "sha256" => Ok(MerkleHash::Sha256),
+            "keccak256" => Ok(MerkleHash::Keccak256),
+            _ => Err(self.merkle_err_invalid_input(
+                "merkle: unsupported hash, expected `sha256` or `keccak256`",
+                &[hash.to_val()],
+            )),
+        }
+    }
+
+    pub(crate) fn merkle_node_from_bytesobj(
+        &self,
+        bo: BytesObject,
+    ) -> Result<[u8; MERKLE_NODE_SIZE], HostError> {
+        self.fixed_length_bytes_from_bytesobj_input::<[u8; MERKLE_NODE_SIZE], MERKLE_NODE_SIZE>(
+            "merkle node",
+            bo,
+        )
+    }
+
+    pub(crate) fn merkle_proof_from_vecobj(
+        &self,
+        vo: VecObject,
+    ) -> Result<Vec<[u8; MERKLE_NODE_SIZE]>, HostError> {
+        let len: u32 = self.vec_len(vo)?.into();
+        Vec::<[u8; MERKLE_NODE_SIZE]>::charge_bulk_init_cpy(len as u64, self)?;
+        let mut res: Vec<[u8; MERKLE_NODE_SIZE]> = Vec::with_capacity(len as usize);
+        self.visit_obj(vo, |hv: &HostVec| {
+            for v in hv.iter() {
+                res.push(self.merkle_node_from_bytesobj(BytesObject::try_from_val(self, v)?)?);
+            }
+            Ok(())
+        })?;
+        Ok(res)
+    }
+
+    fn merkle_hash_pair(
+        &self,
+        hash: MerkleHash,
+        left: &[u8; MERKLE_NODE_SIZE],
+        right: &[u8; MERKLE_NODE_SIZE],
+    ) -> Result<[u8; MERKLE_NODE_SIZE], HostError> {
+        let mut buf = [0u8; 2 * MERKLE_NODE_SIZE];
+        buf[..MERKLE_NODE_SIZE].copy_from_slice(left);
+        buf[MERKLE_NODE_SIZE..].copy_from_slice(right);
+        match hash {
+            MerkleHash::Sha256 => sha256_hash_from_bytes_raw(&buf, self),
+            MerkleHash::Keccak256 => self.keccak256_hash_from_bytes_raw(&buf),
+        }
+    }
+
+    // Metering: one 64-byte hash per proof level plus the final root
+    // comparison, i.e. linear in the proof depth.
+    pub(crate) fn merkle_verify_proof_internal(
+        &self,
+        hash: MerkleHash,
+        leaf: [u8; MERKLE_NODE_SIZE],
+        proof: &[[u8; MERKLE_NODE_SIZE]],
+        index: u64,
+        root: &[u8; MERKLE_NODE_SIZE],
+        sorted: bool,
+    ) -> Result<bool, HostError> {
+        if !sorted {
+            // Every index bit must correspond to a proof level, otherwise
+            // multiple indices would be accepted for the same leaf.
+            let depth = proof.len();
+            if depth > MERKLE_MAX_POSITIONAL_DEPTH
+                || (depth < MERKLE_MAX_POSITIONAL_DEPTH && index >> depth != 0)
+            {
+                return Err(self.merkle_err_invalid_input(
+                    "merkle: leaf index is out of range for the proof depth",
+                    &[
+                        Val::from_u32(depth as u32).into(),
+                        Val::from_u32((index >> 32) as u32).into(),
+                        Val::from_u32(index as u32).into(),
+                    ],
+                ));
+            }
+        }
+        let mut node = leaf;
+        for (level, sibling) in proof.iter().enumerate() {
+            let node_is_left = if sorted {
+                node <= *sibling
+            } else {
+                (index >> level) & 1 == 0
+            };
+            node = if node_is_left {
+                self.merkle_hash_pair(hash, &node, sibling)?
+            } else {
+                self.merkle_hash_pair(hash, sibling, &node)?
+            };
+        }
+        self.as_budget()
+            .charge(ContractCostType::MemCmp, Some(MERKLE_NODE_SIZE as u64))?;
+        Ok(node == *root)
+    }
+}
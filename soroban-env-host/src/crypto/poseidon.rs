@@ -0,0 +1,441 @@
+//! Poseidon and Poseidon2 permutations over the BN254 and BLS12-381 scalar
+//! fields.
+//!
+//! The permutations are parameterized by the caller: the state width `t`, the
+//! S-box degree `d`, the number of full (`rounds_f`) and partial (`rounds_p`)
+//! rounds, the round constants and the linear layer (the MDS matrix for
+//! Poseidon, the diagonal of the internal matrix minus one for Poseidon2).
+//! This allows using any of the standard parameter sets (e.g. the ones
+//! generated by the Grain LFSR of the reference implementations) without
+//! baking them into the host.
+//!
+//! The sponge constructions built on top of the permutations are left to the
+//! contracts, as they differ between the applications (padding, capacity and
+//! domain separation).
+use crate::{
+    budget::AsBudget,
+    host_object::HostVec,
+    xdr::{ContractCostType, ScErrorCode, ScErrorType},
+    Env, Host, HostError, Symbol, SymbolStr, TryFromVal, TryIntoVal, U256Val, U32Val, Val,
+    VecObject,
+};
+use ark_ff::{Field, PrimeField};
+
+pub(crate) const POSEIDON_MAX_WIDTH: u32 = 24;
+const POSEIDON_SUPPORTED_SBOX_DEGREES: [u32; 4] = [3, 5, 7, 11];
+const FR_SERIALIZED_SIZE: u64 = 32;
+
+/// Scalar fields supported by the Poseidon permutations, identified by the
+/// `field` symbol passed to the host functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PoseidonField {
+    Bn254,
+    Bls12381,
+}
+
+/// Conversions between the `U256Val` and the field elements, shared by both
+/// supported scalar fields.
+pub(crate) trait PoseidonFieldElement: PrimeField {
+    fn from_u256val(host: &Host, v: U256Val) -> Result<Self, HostError>;
+    fn to_u256val(host: &Host, f: Self) -> Result<U256Val, HostError>;
+}
+
+impl PoseidonFieldElement for ark_bn254::Fr {
+    fn from_u256val(host: &Host, v: U256Val) -> Result<Self, HostError> {
+        host.bn254_fr_from_u256val(v)
+    }
+
+    fn to_u256val(host: &Host, f: Self) -> Result<U256Val, HostError> {
+        host.bn254_fr_to_u256val(f)
+    }
+}
+
+impl PoseidonFieldElement for ark_bls12_381::Fr {
+    fn from_u256val(host: &Host, v: U256Val) -> Result<Self, HostError> {
+        host.fr_from_u256val(v)
+    }
+
+    fn to_u256val(host: &Host, f: Self) -> Result<U256Val, HostError> {
+        host.fr_to_u256val(f)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PoseidonParams {
+    pub(crate) t: u32,
+    pub(crate) d: u32,
+    pub(crate) rounds_f: u32,
+    pub(crate) rounds_p: u32,
+}
+
+impl PoseidonParams {
+    fn rounds(&self) -> u32 {
+        self.rounds_f + self.rounds_p
+    }
+
+    fn is_full_round(&self, r: u32) -> bool {
+        r < self.rounds_f / 2 || r >= self.rounds_f / 2 + self.rounds_p
+    }
+}
+
+impl Host {
+    fn poseidon_err_invalid_input(&self, msg: &str, args: &[Val]) -> HostError {
+        self.err(ScErrorType::Crypto, ScErrorCode::InvalidInput, msg, args)
+    }
+
+    pub(crate) fn poseidon_field_from_symbol(
+        &self,
+        field: Symbol,
+    ) -> Result<PoseidonField, HostError> {
+        let field_str: SymbolStr = field.try_into_val(self)?;
+        match field_str.as_ref() {
+            "BN254" => Ok(PoseidonField::Bn254),
+            "BLS12_381" => Ok(PoseidonField::Bls12381),
+            _ => Err(self.poseidon_err_invalid_input(
+                "poseidon: unsupported field, expected `BN254` or `BLS12_381`",
+                &[field.to_val()],
+            )),
+        }
+    }
+
+    pub(crate) fn poseidon_params_from_vals<F: PrimeField>(
+        &self,
+        t: U32Val,
+        d: U32Val,
+        rounds_f: U32Val,
+        rounds_p: U32Val,
+        poseidon2: bool,
+    ) -> Result<PoseidonParams, HostError> {
+        let params = PoseidonParams {
+            t: t.into(),
+            d: d.into(),
+            rounds_f: rounds_f.into(),
+            rounds_p: rounds_p.into(),
+        };
+        let t_is_valid = if poseidon2 {
+            // Poseidon2 external linear layer is only defined for these widths.
+            params.t == 2 || params.t == 3 || (params.t % 4 == 0 && params.t > 0)
+        } else {
+            params.t >= 2
+        };
+        if !t_is_valid || params.t > POSEIDON_MAX_WIDTH {
+            return Err(
+                self.poseidon_err_invalid_input("poseidon: unsupported state width", &[t.into()])
+            );
+        }
+        // The S-box `x^d` is a permutation only if `gcd(d, p - 1) == 1`. All
+        // the supported degrees are prime, so it's enough to check that `d`
+        // doesn't divide `p - 1`.
+        if !POSEIDON_SUPPORTED_SBOX_DEGREES.contains(&params.d)
+            || modulus_minus_one_rem::<F>(params.d) == 0
+        {
+            return Err(self.poseidon_err_invalid_input(
+                "poseidon: unsupported S-box degree for the field",
+                &[d.into()],
+            ));
+        }
+        if params.rounds_f == 0 || params.rounds_f % 2 != 0 {
+            return Err(self.poseidon_err_invalid_input(
+                "poseidon: number of full rounds must be even and non-zero",
+                &[rounds_f.into()],
+            ));
+        }
+        if params.rounds_f.checked_add(params.rounds_p).is_none() {
+            return Err(self.poseidon_err_invalid_input(
+                "poseidon: number of rounds overflows",
+                &[rounds_f.into(), rounds_p.into()],
+            ));
+        }
+        Ok(params)
+    }
+
+    pub(crate) fn poseidon_field_vec_from_vecobj<F: PoseidonFieldElement>(
+        &self,
+        vo: VecObject,
+        expected_len: u32,
+        tag: &str,
+    ) -> Result<Vec<F>, HostError> {
+        let len: u32 = self.vec_len(vo)?.into();
+        if len != expected_len {
+            return Err(self.poseidon_err_invalid_input(
+                format!("poseidon: invalid {tag} vector length").as_str(),
+                &[
+                    Val::from_u32(len).into(),
+                    Val::from_u32(expected_len).into(),
+                ],
+            ));
+        }
+        self.charge_budget(
+            ContractCostType::MemAlloc,
+            Some(len as u64 * FR_SERIALIZED_SIZE),
+        )?;
+        let mut res: Vec<F> = Vec::with_capacity(len as usize);
+        self.visit_obj(vo, |hv: &HostVec| {
+            for v in hv.iter() {
+                res.push(F::from_u256val(self, U256Val::try_from_val(self, v)?)?);
+            }
+            Ok(())
+        })?;
+        Ok(res)
+    }
+
+    pub(crate) fn poseidon_field_matrix_from_vecobj<F: PoseidonFieldElement>(
+        &self,
+        vo: VecObject,
+        rows: u32,
+        cols: u32,
+        tag: &str,
+    ) -> Result<Vec<Vec<F>>, HostError> {
+        let len: u32 = self.vec_len(vo)?.into();
+        if len != rows {
+            return Err(self.poseidon_err_invalid_input(
+                format!("poseidon: invalid number of {tag} rows").as_str(),
+                &[Val::from_u32(len).into(), Val::from_u32(rows).into()],
+            ));
+        }
+        self.charge_budget(
+            ContractCostType::MemAlloc,
+            Some(rows as u64 * std::mem::size_of::<Vec<F>>() as u64),
+        )?;
+        let mut res: Vec<Vec<F>> = Vec::with_capacity(rows as usize);
+        self.visit_obj(vo, |hv: &HostVec| {
+            for v in hv.iter() {
+                let row = VecObject::try_from_val(self, v)?;
+                res.push(self.poseidon_field_vec_from_vecobj(row, cols, tag)?);
+            }
+            Ok(())
+        })?;
+        Ok(res)
+    }
+
+    pub(crate) fn poseidon_field_vec_to_vecobj<F: PoseidonFieldElement>(
+        &self,
+        state: Vec<F>,
+    ) -> Result<VecObject, HostError> {
+        let mut vals: Vec<Val> = Vec::with_capacity(state.len());
+        for f in state {
+            vals.push(F::to_u256val(self, f)?.to_val());
+        }
+        self.add_host_object(HostVec::from_exact_iter(
+            vals.into_iter(),
+            self.budget_ref(),
+        )?)
+    }
+
+    // The permutations are charged up front for the field operations they
+    // consist of, using the BLS12-381 `Fr` cost types (the BN254 scalar field
+    // operations are equivalent in cost). The S-boxes are charged as
+    // exponentiations to the `d`-th power.
+    fn poseidon_charge_field_ops(
+        &self,
+        params: &PoseidonParams,
+        adds: u64,
+        muls: u64,
+    ) -> Result<(), HostError> {
+        let t = params.t as u64;
+        let sboxes = params.rounds_f as u64 * t + params.rounds_p as u64;
+        self.as_budget()
+            .bulk_charge(ContractCostType::Bls12381FrAddSub, adds, None)?;
+        self.as_budget()
+            .bulk_charge(ContractCostType::Bls12381FrMul, muls, None)?;
+        self.as_budget().bulk_charge(
+            ContractCostType::Bls12381FrPow,
+            sboxes,
+            Some(32 - params.d.leading_zeros() as u64),
+        )
+    }
+
+    /// Applies the Poseidon permutation to `state` in place.
+    pub(crate) fn poseidon_permutation_internal<F: PrimeField>(
+        &self,
+        state: &mut [F],
+        params: &PoseidonParams,
+        mds: &[Vec<F>],
+        round_constants: &[Vec<F>],
+    ) -> Result<(), HostError> {
+        let t = params.t as u64;
+        let rounds = params.rounds() as u64;
+        // Every round adds the round constants to the whole state and
+        // multiplies it by the MDS matrix.
+        let adds = rounds.saturating_mul(t + t * (t - 1));
+        let muls = rounds.saturating_mul(t * t);
+        self.poseidon_charge_field_ops(params, adds, muls)?;
+
+        let mut new_state = vec![F::zero(); state.len()];
+        for (r, rc) in round_constants.iter().enumerate() {
+            for (s, c) in state.iter_mut().zip(rc.iter()) {
+                s.add_assign(c);
+            }
+            if params.is_full_round(r as u32) {
+                for s in state.iter_mut() {
+                    *s = s.pow([params.d as u64]);
+                }
+            } else {
+                state[0] = state[0].pow([params.d as u64]);
+            }
+            for (ns, row) in new_state.iter_mut().zip(mds.iter()) {
+                *ns = F::zero();
+                for (m, s) in row.iter().zip(state.iter()) {
+                    let mut prod = *m;
+                    prod.mul_assign(s);
+                    ns.add_assign(&prod);
+                }
+            }
+            state.copy_from_slice(&new_state);
+        }
+        Ok(())
+    }
+
+    /// Applies the Poseidon2 permutation to `state` in place. The round
+    /// constants have a row of `t` elements per round, only the first element
+    /// of the row is used in the partial rounds.
+    pub(crate) fn poseidon2_permutation_internal<F: PrimeField>(
+        &self,
+        state: &mut [F],
+        params: &PoseidonParams,
+        mat_internal_diag_m_1: &[F],
+        round_constants: &[Vec<F>],
+    ) -> Result<(), HostError> {
+        let t = params.t as u64;
+        let rounds_f = params.rounds_f as u64;
+        let rounds_p = params.rounds_p as u64;
+        // The external linear layer is applied once before the rounds and
+        // once per full round. It consists of additions and doublings only.
+        let external_matmul_adds = if t < 4 { 2 * t } else { 6 * t };
+        let adds = (rounds_f + 1)
+            .saturating_mul(external_matmul_adds)
+            .saturating_add(rounds_f.saturating_mul(t))
+            .saturating_add(rounds_p.saturating_mul(2 * t + 1));
+        let muls = rounds_p.saturating_mul(t);
+        self.poseidon_charge_field_ops(params, adds, muls)?;
+
+        poseidon2_external_matmul(state);
+        for (r, rc) in round_constants.iter().enumerate() {
+            if params.is_full_round(r as u32) {
+                for (s, c) in state.iter_mut().zip(rc.iter()) {
+                    s.add_assign(c);
+                    *s = s.pow([params.d as u64]);
+                }
+                poseidon2_external_matmul(state);
+            } else {
+                state[0].add_assign(&rc[0]);
+                state[0] = state[0].pow([params.d as u64]);
+                poseidon2_internal_matmul(state, mat_internal_diag_m_1);
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn poseidon_permutation_impl<F: PoseidonFieldElement>(
+        &self,
+        input: VecObject,
+        params: PoseidonParams,
+        mds: VecObject,
+        round_constants: VecObject,
+    ) -> Result<VecObject, HostError> {
+        let mut state = self.poseidon_field_vec_from_vecobj::<F>(input, params.t, "input")?;
+        let mds = self.poseidon_field_matrix_from_vecobj::<F>(mds, params.t, params.t, "mds")?;
+        let round_constants = self.poseidon_field_matrix_from_vecobj::<F>(
+            round_constants,
+            params.rounds(),
+            params.t,
+            "round constants",
+        )?;
+        self.poseidon_permutation_internal(&mut state, &params, &mds, &round_constants)?;
+        self.poseidon_field_vec_to_vecobj(state)
+    }
+
+    pub(crate) fn poseidon2_permutation_impl<F: PoseidonFieldElement>(
+        &self,
+        input: VecObject,
+        params: PoseidonParams,
+        mat_internal_diag_m_1: VecObject,
+        round_constants: VecObject,
+    ) -> Result<VecObject, HostError> {
+        let mut state = self.poseidon_field_vec_from_vecobj::<F>(input, params.t, "input")?;
+        let mat_internal_diag_m_1 = self.poseidon_field_vec_from_vecobj::<F>(
+            mat_internal_diag_m_1,
+            params.t,
+            "internal matrix diagonal",
+        )?;
+        let round_constants = self.poseidon_field_matrix_from_vecobj::<F>(
+            round_constants,
+            params.rounds(),
+            params.t,
+            "round constants",
+        )?;
+        self.poseidon2_permutation_internal(
+            &mut state,
+            &params,
+            &mat_internal_diag_m_1,
+            &round_constants,
+        )?;
+        self.poseidon_field_vec_to_vecobj(state)
+    }
+}
+
+// Returns `(p - 1) mod d` for the field modulus `p`.
+fn modulus_minus_one_rem<F: PrimeField>(d: u32) -> u64 {
+    let d = d as u128;
+    let mut rem: u128 = 0;
+    for limb in F::MODULUS.as_ref().iter().rev() {
+        rem = ((rem << 64) | *limb as u128) % d;
+    }
+    ((rem + d - 1) % d) as u64
+}
+
+// Multiplies the 4-element chunk by the `M4` matrix
+// [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]], as specified in
+// the Poseidon2 paper.
+fn poseidon2_matmul_m4<F: Field>(x: &mut [F]) {
+    let t0 = x[0] + x[1];
+    let t1 = x[2] + x[3];
+    let t2 = x[1].double() + t1;
+    let t3 = x[3].double() + t0;
+    let t4 = t1.double().double() + t3;
+    let t5 = t0.double().double() + t2;
+    let t6 = t3 + t5;
+    let t7 = t2 + t4;
+    x[0] = t6;
+    x[1] = t5;
+    x[2] = t7;
+    x[3] = t4;
+}
+
+fn poseidon2_external_matmul<F: Field>(state: &mut [F]) {
+    match state.len() {
+        // For the widths 2 and 3 the external matrix is `circ(2, 1[, 1])`.
+        2 | 3 => {
+            let sum = state.iter().fold(F::zero(), |acc, s| acc + s);
+            for s in state.iter_mut() {
+                s.add_assign(&sum);
+            }
+        }
+        // For the width 4 the external matrix is `M4` itself.
+        4 => poseidon2_matmul_m4(state),
+        // For the larger widths divisible by 4 the external matrix is
+        // `circ(2 * M4, M4, ..., M4)`.
+        _ => {
+            for chunk in state.chunks_exact_mut(4) {
+                poseidon2_matmul_m4(chunk);
+            }
+            let mut stored = [F::zero(); 4];
+            for chunk in state.chunks_exact(4) {
+                for (st, c) in stored.iter_mut().zip(chunk.iter()) {
+                    st.add_assign(c);
+                }
+            }
+            for (i, s) in state.iter_mut().enumerate() {
+                s.add_assign(&stored[i % 4]);
+            }
+        }
+    }
+}
+
+fn poseidon2_internal_matmul<F: Field>(state: &mut [F], mat_internal_diag_m_1: &[F]) {
+    let sum = state.iter().fold(F::zero(), |acc, s| acc + s);
+    for (s, diag) in state.iter_mut().zip(mat_internal_diag_m_1.iter()) {
+        s.mul_assign(diag);
+        s.add_assign(&sum);
+    }
+}
@@ -318,40 +318,43 @@ fn add_footprint_only_ledger_changes(
 pub fn extract_rent_changes(ledger_changes: &[LedgerEntryChange]) -> Vec<LedgerEntryRentChange> {
     ledger_changes
         .iter()
-        .filter_map(|entry_change| {
-            // Rent changes are only relevant to non-removed entries with
-            // a ttl.
-            if let (Some(ttl_change), optional_encoded_new_value) =
-                (&entry_change.ttl_change, &entry_change.encoded_new_value)
-            {
-                let new_size_bytes_for_rent = if optional_encoded_new_value.is_some() {
-                    entry_change.new_entry_size_bytes_for_rent
-                } else {
-                    entry_change.old_entry_size_bytes_for_rent
-                };
+        .filter_map(extract_rent_change)
+        .collect()
+}
 
-                // Skip the entry if 1. it is not extended and 2. the entry size has not increased
-                if ttl_change.old_live_until_ledger >= ttl_change.new_live_until_ledger
-                    && entry_change.old_entry_size_bytes_for_rent >= new_size_bytes_for_rent
-                {
-                    return None;
-                }
-                Some(LedgerEntryRentChange {
-                    is_persistent: matches!(
-                        ttl_change.durability,
-                        ContractDataDurability::Persistent
-                    ),
-                    is_code_entry: matches!(ttl_change.entry_type, LedgerEntryType::ContractCode),
-                    old_size_bytes: entry_change.old_entry_size_bytes_for_rent,
-                    new_size_bytes: new_size_bytes_for_rent,
-                    old_live_until_ledger: ttl_change.old_live_until_ledger,
-                    new_live_until_ledger: ttl_change.new_live_until_ledger,
-                })
-            } else {
-                None
-            }
+/// Extracts the rent-related change from a single ledger change, if the
+/// change is meaningful.
+pub(crate) fn extract_rent_change(
+    entry_change: &LedgerEntryChange,
+) -> Option<LedgerEntryRentChange> {
+    // Rent changes are only relevant to non-removed entries with
+    // a ttl.
+    if let (Some(ttl_change), optional_encoded_new_value) =
+        (&entry_change.ttl_change, &entry_change.encoded_new_value)
+    {
+        let new_size_bytes_for_rent = if optional_encoded_new_value.is_some() {
+            entry_change.new_entry_size_bytes_for_rent
+        } else {
+            entry_change.old_entry_size_bytes_for_rent
+        };
+
+        // Skip the entry if 1. it is not extended and 2. the entry size has not increased
+        if ttl_change.old_live_until_ledger >= ttl_change.new_live_until_ledger
+            && entry_change.old_entry_size_bytes_for_rent >= new_size_bytes_for_rent
+        {
+            return None;
+        }
+        Some(LedgerEntryRentChange {
+            is_persistent: matches!(ttl_change.durability, ContractDataDurability::Persistent),
+            is_code_entry: matches!(ttl_change.entry_type, LedgerEntryType::ContractCode),
+            old_size_bytes: entry_change.old_entry_size_bytes_for_rent,
+            new_size_bytes: new_size_bytes_for_rent,
+            old_live_until_ledger: ttl_change.old_live_until_ledger,
+            new_live_until_ledger: ttl_change.new_live_until_ledger,
         })
-        .collect()
+    } else {
+        None
+    }
 }
 
 /// Helper for computing the size of the ledger entry to be used in rent
@@ -23,6 +23,7 @@ pub const MINIMUM_RENT_WRITE_FEE_PER_1KB: i64 = 1000;
 pub const CODE_ENTRY_RENT_DISCOUNT_FACTOR: i64 = 3;
 
 /// These are the resource upper bounds specified by the Soroban transaction.
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct TransactionResources {
     /// Number of CPU instructions.
     pub instructions: u32,
@@ -91,6 +92,7 @@ pub struct RentWriteFeeConfiguration {
 ///
 /// This represents the entry state before and after transaction has been
 /// applied.
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct LedgerEntryRentChange {
     /// Whether this is persistent or temporary entry.
     pub is_persistent: bool,
@@ -138,6 +140,55 @@ pub struct RentFeeConfiguration {
     pub temporary_rent_rate_denominator: i64,
 }
 
+/// Ledger entry to keep alive in `project_rent_fee`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RentProjectionEntry {
+    /// Whether this is persistent or temporary entry.
+    pub is_persistent: bool,
+    /// Whether this is a contract code entry.
+    pub is_code_entry: bool,
+    /// In-memory size of the entry in bytes.
+    pub size_bytes: u32,
+    /// Current live until ledger of the entry.
+    pub live_until_ledger: u32,
+}
+
+/// Projection of the Soroban state size, that `project_rent_fee` uses to
+/// compute the rent fee per 1KB at the future ledgers.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SorobanStateSizeProjection {
+    /// Configuration for computing the rent fee per 1KB from the state size
+    /// via `compute_rent_write_fee_per_1kb`.
+    pub rent_write_fee_configuration: RentWriteFeeConfiguration,
+    /// Soroban in-memory state size at the current ledger.
+    pub current_state_size_bytes: i64,
+    /// Expected change of the state size per ledger. Negative values model a
+    /// shrinking state.
+    pub state_size_growth_bytes_per_ledger: i64,
+}
+
+/// A single TTL extension of the entries projected by `project_rent_fee`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProjectedRentExtension {
+    /// Ledger at which the entries are extended.
+    pub ledger_seq: u32,
+    /// Number of the extended entries.
+    pub extended_entries: u32,
+    /// Rent fee per 1KB at `ledger_seq`.
+    pub fee_per_rent_1kb: i64,
+    /// Total rent fee of the extension, as computed by `compute_rent_fee`.
+    pub fee: i64,
+}
+
+/// Rent fee projected by `project_rent_fee`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RentProjection {
+    /// Total rent fee of all the extensions.
+    pub total_fee: i64,
+    /// The extensions necessary to keep the entries alive, in ledger order.
+    pub extensions: Vec<ProjectedRentExtension>,
+}
+
 /// Computes the resource fee for a transaction based on the resource
 /// consumption and the fee-related network configuration.
 ///
@@ -320,6 +371,100 @@ pub fn compute_rent_fee(
     fee
 }
 
+impl SorobanStateSizeProjection {
+    /// Returns the projected state size after `ledgers` ledgers from the
+    /// current one.
+    pub fn state_size_at(&self, ledgers: u32) -> i64 {
+        self.current_state_size_bytes
+            .saturating_add(
+                self.state_size_growth_bytes_per_ledger
+                    .saturating_mul(ledgers as i64),
+            )
+            .max(0)
+    }
+}
+
+/// Projects the total rent fee for keeping the provided ledger `entries`
+/// alive until `current_ledger_seq + horizon_ledgers` (inclusive).
+///
+/// The entries are assumed to be extended in a single transaction every
+/// `extension_period_ledgers` ledgers, starting at `current_ledger_seq`. Every
+/// extension covers the entries that wouldn't be live at the next extension
+/// (or at the end of the horizon), and is priced by `compute_rent_fee`. More
+/// frequent extensions pay more for writing the TTL entries, but follow the
+/// rent fee changes more closely. The period has to be within the maximum
+/// entry TTL of the network for the extensions to be valid.
+///
+/// When `state_size_projection` is provided, the rent fee per 1KB of every
+/// extension is computed from the projected Soroban state size at its ledger
+/// via `compute_rent_write_fee_per_1kb`, and `fee_per_rent_1kb` of
+/// `fee_config` is ignored. Otherwise the rent fee per 1KB from `fee_config`
+/// is used for all the extensions.
+///
+/// The entries are expected to be live at `current_ledger_seq`, with their
+/// size not changing over the horizon.
+pub fn project_rent_fee(
+    entries: &[RentProjectionEntry],
+    fee_config: &RentFeeConfiguration,
+    state_size_projection: Option<&SorobanStateSizeProjection>,
+    current_ledger_seq: u32,
+    horizon_ledgers: u32,
+    extension_period_ledgers: u32,
+) -> RentProjection {
+    let horizon_end = current_ledger_seq.saturating_add(horizon_ledgers);
+    let period = extension_period_ledgers.max(1);
+    let mut live_until: Vec<u32> = entries.iter().map(|e| e.live_until_ledger).collect();
+    let mut projection = RentProjection::default();
+    let mut ledger = current_ledger_seq;
+    while ledger <= horizon_end {
+        let extend_to = ledger.saturating_add(period).min(horizon_end);
+        let mut changes = vec![];
+        for (e, live_until) in entries.iter().zip(live_until.iter_mut()) {
+            if *live_until >= extend_to {
+                continue;
+            }
+            changes.push(LedgerEntryRentChange {
+                is_persistent: e.is_persistent,
+                is_code_entry: e.is_code_entry,
+                old_size_bytes: e.size_bytes,
+                new_size_bytes: e.size_bytes,
+                old_live_until_ledger: *live_until,
+                new_live_until_ledger: extend_to,
+            });
+            *live_until = extend_to;
+        }
+        if !changes.is_empty() {
+            let fee_per_rent_1kb = match state_size_projection {
+                Some(p) => compute_rent_write_fee_per_1kb(
+                    p.state_size_at(ledger - current_ledger_seq),
+                    &p.rent_write_fee_configuration,
+                ),
+                None => fee_config.fee_per_rent_1kb,
+            };
+            let extension_fee_config = RentFeeConfiguration {
+                fee_per_write_1kb: fee_config.fee_per_write_1kb,
+                fee_per_rent_1kb,
+                fee_per_write_entry: fee_config.fee_per_write_entry,
+                persistent_rent_rate_denominator: fee_config.persistent_rent_rate_denominator,
+                temporary_rent_rate_denominator: fee_config.temporary_rent_rate_denominator,
+            };
+            let fee = compute_rent_fee(&changes, &extension_fee_config, ledger);
+            projection.total_fee = projection.total_fee.saturating_add(fee);
+            projection.extensions.push(ProjectedRentExtension {
+                ledger_seq: ledger,
+                extended_entries: changes.len() as u32,
+                fee_per_rent_1kb,
+                fee,
+            });
+        }
+        if extend_to == horizon_end {
+            break;
+        }
+        ledger = ledger.saturating_add(period);
+    }
+    projection
+}
+
 // Size of half-open range (lo, hi], or None if lo>hi
 fn exclusive_ledger_diff(lo: u32, hi: u32) -> Option<u32> {
     hi.checked_sub(lo)
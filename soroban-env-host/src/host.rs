@@ -8,7 +8,7 @@ use crate::{
     events::{diagnostic::DiagnosticLevel, Events, InternalEventsBuffer},
     host_object::{HostMap, HostObject, HostVec, MuxedScAddress},
     impl_bignum_host_fns, impl_bignum_host_fns_rhs_u32, impl_bls12_381_fr_arith_host_fns,
-    impl_wrapping_obj_from_num, impl_wrapping_obj_to_num,
+    impl_bn254_fr_arith_host_fns, impl_wrapping_obj_from_num, impl_wrapping_obj_to_num,
     num::*,
     storage::Storage,
     vm::ModuleCache,
@@ -58,6 +58,7 @@ use self::{
     prng::Prng,
 };
 
+use crate::crypto::poseidon::PoseidonField;
 use crate::host::error::TryBorrowOrErr;
 #[cfg(any(test, feature = "testutils"))]
 pub use frame::ContractFunctionSet;
@@ -922,6 +923,10 @@ impl EnvBase for Host {
                 return false;
             }
         }
+        // The budget profiler tracks host function calls via the trace events.
+        if self.budget_ref().is_profiling() {
+            return true;
+        }
         match self.try_borrow_trace_hook() {
             Ok(hook) => hook.is_some(),
             Err(_) => false,
@@ -2503,33 +2508,67 @@ impl VmCallerEnv for Host {
                     "contract try_call failed",
                     &[func.to_val(), args.to_val()],
                 );
-                // Only allow to gracefully handle the recoverable errors.
-                // Non-recoverable errors should still cause guest to panic and
-                // abort execution.
-                if e.is_recoverable() {
-                    // Pass contract error _codes_ through, while switching
-                    // from Err(ce) to Ok(ce), i.e. recovering.
-                    if e.error.is_type(ScErrorType::Contract) {
-                        Ok(e.error.to_val())
-                    } else {
-                        // Narrow all the remaining host errors down to a single
-                        // error type. We don't want to expose the granular host
-                        // errors to the guest, consistently with how every
-                        // other host function works. This reduces the risk of
-                        // implementation being 'locked' into specific error
-                        // codes due to them being exposed to the guest and
-                        // hashed into blockchain.
-                        // The granular error codes are still observable with
-                        // diagnostic events.
-                        Ok(Error::from_type_and_code(
-                            ScErrorType::Context,
-                            ScErrorCode::InvalidAction,
-                        )
-                        .to_val())
-                    }
-                } else {
-                    Err(e)
+                self.recover_try_call_error(e)
+            }
+        }
+    }
+
+    fn try_call_with_budget(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        contract_address: AddressObject,
+        func: Symbol,
+        args: VecObject,
+        cpu_limit: U64Val,
+        mem_limit: U64Val,
+    ) -> Result<Val, HostError> {
+        #[cfg(any(test, feature = "testutils"))]
+        let _invocation_meter_scope = self.maybe_meter_invocation(
+            crate::host::invocation_metering::MeteringInvocation::contract_invocation_with_address_obj(
+                self,
+                contract_address,
+                func,
+            ),
+        );
+
+        let cpu_limit: u64 = cpu_limit.try_into_val(self)?;
+        let mem_limit: u64 = mem_limit.try_into_val(self)?;
+        let argvec = self.call_args_from_obj(args)?;
+        let contract_id = self.contract_id_from_address(contract_address)?;
+        // The callee (and everything it calls) runs under a sub-budget carved
+        // from the remaining budget. The frame of the callee is popped (and its
+        // changes rolled back on failure) before the sub-budget is released.
+        let (res, sub_limits_crossed) =
+            self.as_budget().with_sub_limits(cpu_limit, mem_limit, || {
+                self.call_n_internal(
+                    &contract_id,
+                    func,
+                    argvec.as_slice(),
+                    CallParams::default_external_call(),
+                )
+            })?;
+        match res {
+            Ok(rv) => Ok(rv),
+            Err(e) => {
+                self.error(
+                    e.error,
+                    "contract try_call_with_budget failed",
+                    &[func.to_val(), args.to_val()],
+                );
+                // Budget exhaustion is normally non-recoverable, but if the
+                // sub-budget has actually been exhausted and the budget is
+                // still within its own limits after the sub-budget has been
+                // released, then the caller can carry on. Other failures
+                // reported as budget exhaustion (such as XDR limits) stay
+                // non-recoverable.
+                if e.error.is_type(ScErrorType::Budget)
+                    && e.error.is_code(ScErrorCode::ExceededLimit)
+                    && sub_limits_crossed
+                    && self.as_budget().is_within_limits()?
+                {
+                    return Ok(e.error.to_val());
                 }
+                self.recover_try_call_error(e)
             }
         }
     }
@@ -2978,6 +3017,64 @@ impl VmCallerEnv for Host {
         self.add_host_object(self.scbytes_from_vec(hash)?)
     }
 
+    // Notes on metering: covered by components.
+    fn compute_hash_sha512(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        x: BytesObject,
+    ) -> Result<BytesObject, HostError> {
+        let hash = self.visit_obj(x, |bytes: &ScBytes| {
+            self.sha512_hash_from_bytes(bytes.as_slice())
+        })?;
+        self.add_host_object(self.scbytes_from_vec(hash)?)
+    }
+
+    // Notes on metering: covered by components.
+    fn compute_hash_blake2b(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        x: BytesObject,
+        output_len: U32Val,
+    ) -> Result<BytesObject, HostError> {
+        let hash = self.visit_obj(x, |bytes: &ScBytes| {
+            self.blake2b_hash_from_bytes(bytes.as_slice(), output_len)
+        })?;
+        self.add_host_object(self.scbytes_from_vec(hash)?)
+    }
+
+    // Notes on metering: covered by components.
+    fn compute_hash_blake3(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        x: BytesObject,
+    ) -> Result<BytesObject, HostError> {
+        let hash = self.visit_obj(x, |bytes: &ScBytes| {
+            self.blake3_hash_from_bytes(bytes.as_slice())
+        })?;
+        self.add_host_object(self.scbytes_from_vec(hash)?)
+    }
+
+    // Notes on metering: covered by components, one hash per proof level.
+    fn verify_merkle_proof(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        leaf: BytesObject,
+        proof: VecObject,
+        index: U64Val,
+        root: BytesObject,
+        hash: Symbol,
+        sorted: Bool,
+    ) -> Result<Bool, HostError> {
+        let hash = self.merkle_hash_from_symbol(hash)?;
+        let leaf = self.merkle_node_from_bytesobj(leaf)?;
+        let proof = self.merkle_proof_from_vecobj(proof)?;
+        let root = self.merkle_node_from_bytesobj(root)?;
+        let index: u64 = index.try_into_val(self)?;
+        let res =
+            self.merkle_verify_proof_internal(hash, leaf, &proof, index, &root, sorted.into())?;
+        Ok(res.into())
+    }
+
     // Notes on metering: covered by components.
     fn verify_sig_ed25519(
         &self,
@@ -2994,6 +3091,18 @@ impl VmCallerEnv for Host {
         Ok(res?.into())
     }
 
+    // Notes on metering: covered by components.
+    fn verify_sig_ed25519_batch(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        ks: VecObject,
+        xs: VecObject,
+        ss: VecObject,
+    ) -> Result<Void, HostError> {
+        self.verify_sig_ed25519_batch_internal(ks, xs, ss)?;
+        Ok(Val::VOID)
+    }
+
     fn recover_key_ecdsa_secp256k1(
         &self,
         _vmcaller: &mut VmCaller<Host>,
@@ -3022,6 +3131,51 @@ impl VmCallerEnv for Host {
         Ok(res.into())
     }
 
+    fn verify_sig_ecdsa_secp256k1(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        public_key: BytesObject,
+        msg_digest: BytesObject,
+        signature: BytesObject,
+    ) -> Result<Void, HostError> {
+        let pk = self.secp256k1_public_key_from_bytesobj_input(public_key)?;
+        let sig = self.ecdsa_signature_from_bytesobj_input::<k256::Secp256k1>(signature)?;
+        let msg_hash = self.hash_from_bytesobj_input("msg_digest", msg_digest)?;
+        let res = self.secp256k1_verify_signature(&pk, &msg_hash, &sig)?;
+        Ok(res.into())
+    }
+
+    fn recover_key_ecdsa_secp256r1(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        msg_digest: BytesObject,
+        signature: BytesObject,
+        recovery_id: U32Val,
+    ) -> Result<BytesObject, HostError> {
+        let sig = self.ecdsa_signature_from_bytesobj_input::<p256::NistP256>(signature)?;
+        let rid = self.secp256r1_recovery_id_from_u32val(recovery_id)?;
+        let hash = self.hash_from_bytesobj_input("msg_digest", msg_digest)?;
+        let rk = self.recover_key_ecdsa_secp256r1_internal(&hash, &sig, rid)?;
+        self.add_host_object(rk)
+    }
+
+    fn verify_sig_schnorr_secp256k1(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        public_key: BytesObject,
+        msg: BytesObject,
+        signature: BytesObject,
+    ) -> Result<Void, HostError> {
+        let pk =
+            self.fixed_length_bytes_from_bytesobj_input::<[u8; 32], 32>("public_key", public_key)?;
+        let sig =
+            self.fixed_length_bytes_from_bytesobj_input::<[u8; 64], 64>("signature", signature)?;
+        let res = self.visit_obj(msg, |msg: &ScBytes| {
+            self.verify_sig_schnorr_secp256k1_internal(&pk, msg.as_slice(), &sig)
+        });
+        Ok(res?.into())
+    }
+
     fn bls12_381_check_g1_is_in_subgroup(
         &self,
         _vmcaller: &mut VmCaller<Host>,
@@ -3192,6 +3346,23 @@ impl VmCallerEnv for Host {
         self.check_pairing_output(&output)
     }
 
+    fn bls12_381_kzg_verify_point_evaluation(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        commitment: BytesObject,
+        z: U256Val,
+        y: U256Val,
+        proof: BytesObject,
+        tau_g2: BytesObject,
+    ) -> Result<Bool, HostError> {
+        let commitment = self.g1_affine_deserialize_from_bytesobj(commitment, true)?;
+        let z = self.canonical_fr_from_u256val(z, "kzg z")?;
+        let y = self.canonical_fr_from_u256val(y, "kzg y")?;
+        let proof = self.g1_affine_deserialize_from_bytesobj(proof, true)?;
+        let tau_g2 = self.g2_affine_deserialize_from_bytesobj(tau_g2, true)?;
+        self.kzg_verify_point_evaluation_internal(commitment, z, y, proof, tau_g2)
+    }
+
     impl_bls12_381_fr_arith_host_fns!(bls12_381_fr_add, fr_add_internal);
     impl_bls12_381_fr_arith_host_fns!(bls12_381_fr_sub, fr_sub_internal);
     impl_bls12_381_fr_arith_host_fns!(bls12_381_fr_mul, fr_mul_internal);
@@ -3264,6 +3435,201 @@ impl VmCallerEnv for Host {
         self.bn254_check_pairing_output(&output)
     }
 
+    fn bn254_g1_msm(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        vp: VecObject,
+        vs: VecObject,
+    ) -> Result<BytesObject, HostError> {
+        let points = self.bn254_checked_g1_vec_from_vecobj(vp)?;
+        let scalars = self.bn254_fr_vec_from_vecobj(vs)?;
+        let res = self.bn254_g1_msm_internal(&points, &scalars)?;
+        self.bn254_g1_projective_serialize_uncompressed(res)
+    }
+
+    fn bn254_g2_add(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        p0: BytesObject,
+        p1: BytesObject,
+    ) -> Result<BytesObject, HostError> {
+        let p0 = self.bn254_g2_affine_deserialize_from_bytesobj(p0, false)?;
+        let p1 = self.bn254_g2_affine_deserialize_from_bytesobj(p1, false)?;
+        let res = self.bn254_g2_add_internal(p0, p1)?;
+        self.bn254_g2_projective_serialize_uncompressed(res)
+    }
+
+    fn bn254_g2_mul(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        p0: BytesObject,
+        scalar: U256Val,
+    ) -> Result<BytesObject, HostError> {
+        let p0 = self.bn254_g2_affine_deserialize_from_bytesobj(p0, true)?;
+        let scalar = self.bn254_fr_from_u256val(scalar)?;
+        let res = self.bn254_g2_mul_internal(p0, scalar)?;
+        self.bn254_g2_projective_serialize_uncompressed(res)
+    }
+
+    fn bn254_g2_msm(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        vp: VecObject,
+        vs: VecObject,
+    ) -> Result<BytesObject, HostError> {
+        let points = self.bn254_checked_g2_vec_from_vecobj(vp)?;
+        let scalars = self.bn254_fr_vec_from_vecobj(vs)?;
+        let res = self.bn254_g2_msm_internal(&points, &scalars)?;
+        self.bn254_g2_projective_serialize_uncompressed(res)
+    }
+
+    impl_bn254_fr_arith_host_fns!(bn254_fr_add, bn254_fr_add_internal);
+    impl_bn254_fr_arith_host_fns!(bn254_fr_sub, bn254_fr_sub_internal);
+    impl_bn254_fr_arith_host_fns!(bn254_fr_mul, bn254_fr_mul_internal);
+
+    fn bn254_fr_pow(
+        &self,
+        _vmcaller: &mut VmCaller<Self::VmUserState>,
+        lhs: U256Val,
+        rhs: U64Val,
+    ) -> Result<U256Val, Self::Error> {
+        let lhs = self.bn254_fr_from_u256val(lhs)?;
+        let rhs = rhs.try_into_val(self)?;
+        let res = self.bn254_fr_pow_internal(&lhs, &rhs)?;
+        self.bn254_fr_to_u256val(res)
+    }
+
+    fn bn254_fr_inv(
+        &self,
+        _vmcaller: &mut VmCaller<Self::VmUserState>,
+        lhs: U256Val,
+    ) -> Result<U256Val, Self::Error> {
+        let lhs = self.bn254_fr_from_u256val(lhs)?;
+        let res = self.bn254_fr_inv_internal(&lhs)?;
+        self.bn254_fr_to_u256val(res)
+    }
+
+    fn poseidon_permutation(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        input: VecObject,
+        field: Symbol,
+        t: U32Val,
+        d: U32Val,
+        rounds_f: U32Val,
+        rounds_p: U32Val,
+        mds: VecObject,
+        round_constants: VecObject,
+    ) -> Result<VecObject, HostError> {
+        match self.poseidon_field_from_symbol(field)? {
+            PoseidonField::Bn254 => {
+                let params = self
+                    .poseidon_params_from_vals::<ark_bn254::Fr>(t, d, rounds_f, rounds_p, false)?;
+                self.poseidon_permutation_impl::<ark_bn254::Fr>(input, params, mds, round_constants)
+            }
+            PoseidonField::Bls12381 => {
+                let params = self.poseidon_params_from_vals::<ark_bls12_381::Fr>(
+                    t, d, rounds_f, rounds_p, false,
+                )?;
+                self.poseidon_permutation_impl::<ark_bls12_381::Fr>(
+                    input,
+                    params,
+                    mds,
+                    round_constants,
+                )
+            }
+        }
+    }
+
+    fn poseidon2_permutation(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        input: VecObject,
+        field: Symbol,
+        t: U32Val,
+        d: U32Val,
+        rounds_f: U32Val,
+        rounds_p: U32Val,
+        mat_internal_diag_m_1: VecObject,
+        round_constants: VecObject,
+    ) -> Result<VecObject, HostError> {
+        match self.poseidon_field_from_symbol(field)? {
+            PoseidonField::Bn254 => {
+                let params = self
+                    .poseidon_params_from_vals::<ark_bn254::Fr>(t, d, rounds_f, rounds_p, true)?;
+                self.poseidon2_permutation_impl::<ark_bn254::Fr>(
+                    input,
+                    params,
+                    mat_internal_diag_m_1,
+                    round_constants,
+                )
+            }
+            PoseidonField::Bls12381 => {
+                let params = self.poseidon_params_from_vals::<ark_bls12_381::Fr>(
+                    t, d, rounds_f, rounds_p, true,
+                )?;
+                self.poseidon2_permutation_impl::<ark_bls12_381::Fr>(
+                    input,
+                    params,
+                    mat_internal_diag_m_1,
+                    round_constants,
+                )
+            }
+        }
+    }
+
+    fn bn254_check_g1_is_on_curve(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        pt: BytesObject,
+    ) -> Result<Bool, HostError> {
+        self.bn254_check_g1_bytesobj_is_on_curve(pt)
+            .map(|b| Bool::from(b))
+    }
+
+    fn bn254_check_g2_is_on_curve(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        pt: BytesObject,
+    ) -> Result<Bool, HostError> {
+        self.bn254_check_g2_bytesobj_is_on_curve(pt)
+            .map(|b| Bool::from(b))
+    }
+
+    fn bn254_check_g2_is_in_subgroup(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        pt: BytesObject,
+    ) -> Result<Bool, HostError> {
+        let pt = self.bn254_g2_affine_deserialize_from_bytesobj(pt, false)?;
+        self.bn254_check_g2_point_is_in_subgroup(&pt)
+            .map(|b| Bool::from(b))
+    }
+
+    fn bn254_map_fp_to_g1(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        fp: BytesObject,
+    ) -> Result<BytesObject, HostError> {
+        let fp = self.bn254_fp_deserialize_from_bytesobj(fp)?;
+        let g1 = self.bn254_map_fp_to_g1_internal(fp)?;
+        self.bn254_g1_affine_serialize_uncompressed(&g1)
+    }
+
+    fn bn254_hash_to_g1(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        mo: BytesObject,
+        dst: BytesObject,
+    ) -> Result<BytesObject, HostError> {
+        let g1 = self.visit_obj(mo, |msg: &ScBytes| {
+            self.visit_obj(dst, |dst: &ScBytes| {
+                self.bn254_hash_to_g1_internal(dst.as_slice(), msg.as_slice())
+            })
+        })?;
+        self.bn254_g1_affine_serialize_uncompressed(&g1)
+    }
+
     // endregion: "crypto" module functions
     // region: "test" module functions
 
@@ -3770,6 +4136,8 @@ impl Host {
     }
 
     pub(crate) fn call_any_lifecycle_hook(&self, event: TraceEvent) -> Result<(), HostError> {
+        self.update_budget_profiler(&event)?;
+        self.update_budget_timeline(&event)?;
         match &*self.try_borrow_trace_hook()? {
             Some(hook) => hook(self, event),
             None => Ok(()),
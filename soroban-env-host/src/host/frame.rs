@@ -50,6 +50,13 @@ pub(super) struct RollbackPoint {
 #[cfg(any(test, feature = "testutils"))]
 pub trait ContractFunctionSet {
     fn call(&self, func: &Symbol, host: &Host, args: &[Val]) -> Option<Val>;
+
+    /// Same as `call`, but allows failing the call with a `HostError`, that
+    /// is propagated to the caller as is (same as the errors of the host
+    /// functions called from Wasm).
+    fn try_call(&self, func: &Symbol, host: &Host, args: &[Val]) -> Result<Option<Val>, HostError> {
+        Ok(self.call(func, host, args))
+    }
 }
 
 #[cfg(any(test, feature = "testutils"))]
@@ -670,6 +677,8 @@ impl Host {
         match &instance.executable {
             ContractExecutable::Wasm(wasm_hash) => {
                 let vm = self.instantiate_vm(id, wasm_hash)?;
+                #[cfg(any(test, feature = "testutils"))]
+                let vm = self.maybe_instantiate_profiled_vm(vm, wasm_hash)?;
                 let relative_objects = Vec::new();
                 self.with_frame(
                     Frame::ContractVM {
@@ -930,15 +939,16 @@ impl Host {
                     // This is somewhat best-effort, but it's compiled-out when
                     // building a host for production use, so we're willing to
                     // be a bit forgiving.
-                    let closure = AssertUnwindSafe(move || cfs.call(&func, self, args));
-                    let res: Result<Option<Val>, PanicVal> =
+                    let closure = AssertUnwindSafe(move || cfs.try_call(&func, self, args));
+                    let res: Result<Result<Option<Val>, HostError>, PanicVal> =
                         crate::testutils::call_with_suppressed_panic_hook(closure);
                     match res {
-                        Ok(Some(val)) => {
+                        Ok(Err(err)) => Err(err),
+                        Ok(Ok(Some(val))) => {
                             self.fn_return_diagnostics(id, &func, &val);
                             Ok(val)
                         }
-                        Ok(None) => {
+                        Ok(Ok(None)) => {
                             if call_params.treat_missing_function_as_noop {
                                 Ok(Val::VOID.into())
                             } else {
@@ -1025,6 +1035,37 @@ impl Host {
         res
     }
 
+    /// Converts an error of a `try_call`-style call into the value returned to
+    /// the caller, or propagates it if it can't be recovered from.
+    pub(crate) fn recover_try_call_error(&self, e: HostError) -> Result<Val, HostError> {
+        // Only allow to gracefully handle the recoverable errors.
+        // Non-recoverable errors should still cause guest to panic and
+        // abort execution.
+        if e.is_recoverable() {
+            // Pass contract error _codes_ through, while switching
+            // from Err(ce) to Ok(ce), i.e. recovering.
+            if e.error.is_type(ScErrorType::Contract) {
+                Ok(e.error.to_val())
+            } else {
+                // Narrow all the remaining host errors down to a single
+                // error type. We don't want to expose the granular host
+                // errors to the guest, consistently with how every
+                // other host function works. This reduces the risk of
+                // implementation being 'locked' into specific error
+                // codes due to them being exposed to the guest and
+                // hashed into blockchain.
+                // The granular error codes are still observable with
+                // diagnostic events.
+                Ok(
+                    Error::from_type_and_code(ScErrorType::Context, ScErrorCode::InvalidAction)
+                        .to_val(),
+                )
+            }
+        } else {
+            Err(e)
+        }
+    }
+
     // Notes on metering: covered by the called components.
     fn invoke_function_and_return_val(&self, hf: HostFunction) -> Result<Val, HostError> {
         let hf_type = hf.discriminant();
@@ -2,18 +2,26 @@ use soroban_env_common::Env;
 
 use crate::{
     builtin_contracts::account_contract::ACCOUNT_CONTRACT_CHECK_AUTH_FN_NAME,
-    e2e_invoke::{encode_contract_events, entry_size_for_rent},
-    fees::{FeeConfiguration, DATA_SIZE_1KB_INCREMENT, INSTRUCTIONS_INCREMENT, TTL_ENTRY_SIZE},
+    e2e_invoke::{
+        encode_contract_events, entry_size_for_rent, extract_rent_change, LedgerEntryChange,
+        LedgerEntryLiveUntilChange,
+    },
+    fees::{
+        compute_rent_fee, FeeConfiguration, LedgerEntryRentChange, RentFeeConfiguration,
+        DATA_SIZE_1KB_INCREMENT, INSTRUCTIONS_INCREMENT, TTL_ENTRY_SIZE,
+    },
     ledger_info::get_key_durability,
     storage::{is_persistent_key, AccessType, Storage},
+    vm::{ProfiledModule, Vm, WasmProfiler},
     xdr::{
-        ContractDataDurability, ContractId, HostFunction, LedgerKey, ScAddress, ScErrorCode,
+        ContractDataDurability, ContractId, Hash, HostFunction, LedgerKey, ScAddress, ScErrorCode,
         ScErrorType, ScSymbol,
     },
     AddressObject, Symbol, SymbolStr, TryFromVal,
 };
+use std::{collections::BTreeMap, rc::Rc};
 
-use super::{metered_xdr::metered_write_xdr, Host, HostError};
+use super::{error::TryBorrowOrErr, metered_xdr::metered_write_xdr, Frame, Host, HostError};
 
 /// Represents the resources measured during an invocation.
 ///
@@ -198,6 +206,60 @@ pub struct DetailedInvocationResources {
     /// Resources for sub-calls made during the invocation, if any.
     /// Note, that not all the resources commute, i.e. the sum of the resources
     pub sub_call_resources: Vec<DetailedInvocationResources>,
+    /// Fuel consumed by the Wasm functions of the invoked contract (excluding
+    /// the sub-calls), hottest first. This is only populated when Wasm
+    /// profiling is enabled, see `Host::enable_wasm_profiling`.
+    pub wasm_profile: Vec<WasmFunctionProfile>,
+    /// Fuel consumed by the calls to the profiling hooks, which is included
+    /// in the fuel of the functions in `wasm_profile`.
+    pub wasm_profile_hook_fuel: u64,
+    /// Storage resources of every ledger entry in the footprint. This is only
+    /// populated for the root invocation and only when ledger entry metering
+    /// is enabled, see `Host::enable_ledger_entry_metering`.
+    pub ledger_entries: Vec<LedgerEntryResources>,
+}
+
+/// Storage resources attributed to a single ledger entry in the footprint.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LedgerEntryResources {
+    /// Key of the entry.
+    pub key: LedgerKey,
+    /// Whether the entry had to be read from disk, i.e. it is a non-Soroban
+    /// entry or an auto-restored persistent entry.
+    pub is_disk_read: bool,
+    /// Whether the entry is in the read-write footprint.
+    pub is_write: bool,
+    /// Number of bytes read from disk for the entry.
+    pub disk_read_bytes: u32,
+    /// Number of bytes written to the ledger for the entry.
+    pub write_bytes: u32,
+    /// Rent-related change of the entry, if it had any, as it would be
+    /// returned by `extract_rent_changes`.
+    pub rent_change: Option<LedgerEntryRentChange>,
+    /// Ledger sequence number the rent change is relative to.
+    pub ledger_sequence: u32,
+}
+
+/// Fuel consumed by a single Wasm function during an invocation.
+///
+/// Fuel is the unit of the Wasm instruction metering, every unit is charged
+/// as `WasmInsnExec` CPU instructions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WasmFunctionProfile {
+    /// Index of the function in the contract Wasm module.
+    pub function_index: u32,
+    /// Name of the function from the `name` section of the module, if any.
+    pub name: Option<String>,
+    /// Number of times the function has been called.
+    pub calls: u64,
+    /// Fuel consumed by the function itself, i.e. excluding the functions it
+    /// has called.
+    ///
+    /// This includes the fuel of the profiling hook calls, see
+    /// `DetailedInvocationResources::wasm_profile_hook_fuel`. Note also, that
+    /// the fuel of the first block of a function is consumed before the
+    /// function reports its entry, so it is attributed to the caller.
+    pub fuel: u64,
 }
 
 /// Detailed estimate of the transaction fees in stroops based on the
@@ -235,6 +297,35 @@ pub struct DetailedFeeEstimate {
     pub fee_estimate: FeeEstimate,
     /// Fee estimates for sub-calls made during the invocation, if any.
     pub sub_call_fee_estimates: Vec<DetailedFeeEstimate>,
+    /// Fee estimates for every metered ledger entry, most expensive first.
+    /// Only present when the invocation has `ledger_entries` metered.
+    pub ledger_entry_fee_estimates: Vec<LedgerEntryFeeEstimate>,
+}
+
+/// Estimate of the fees in stroops attributed to a single ledger entry.
+///
+/// Every component is rounded up separately for every entry, so the sum of
+/// the entry fees may slightly exceed the respective aggregate fees in
+/// `FeeEstimate`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LedgerEntryFeeEstimate {
+    /// Key of the entry.
+    pub key: LedgerKey,
+    /// Number of bytes read from disk for the entry.
+    pub disk_read_bytes: u32,
+    /// Number of bytes written to the ledger for the entry.
+    pub write_bytes: u32,
+    /// Number of rent bumps (TTL extensions) of the entry, either 0 or 1.
+    pub rent_bumps: u32,
+    /// Total fee (sum of all the remaining fee fields).
+    pub total: i64,
+    /// Fee for reading the entry. As for the aggregate fees, every written
+    /// entry is also charged a read.
+    pub disk_read: i64,
+    /// Fee for writing the entry.
+    pub write: i64,
+    /// Rent fee for the entry, including the TTL entry write.
+    pub rent: i64,
 }
 
 impl InvocationResources {
@@ -370,10 +461,74 @@ impl DetailedInvocationResources {
                 )
             })
             .collect();
+        let rent_fee_config = RentFeeConfiguration {
+            fee_per_write_1kb: fee_config.fee_per_write_1kb,
+            fee_per_rent_1kb,
+            fee_per_write_entry: fee_config.fee_per_write_entry,
+            persistent_rent_rate_denominator,
+            temporary_rent_rate_denominator,
+        };
+        let mut ledger_entry_fee_estimates: Vec<LedgerEntryFeeEstimate> = self
+            .ledger_entries
+            .iter()
+            .map(|e| e.estimate_fees(fee_config, &rent_fee_config))
+            .collect();
+        ledger_entry_fee_estimates.sort_by_key(|e| std::cmp::Reverse(e.total));
         DetailedFeeEstimate {
             invocation: self.invocation.clone(),
             fee_estimate,
             sub_call_fee_estimates,
+            ledger_entry_fee_estimates,
+        }
+    }
+}
+
+impl LedgerEntryResources {
+    fn estimate_fees(
+        &self,
+        fee_config: &FeeConfiguration,
+        rent_fee_config: &RentFeeConfiguration,
+    ) -> LedgerEntryFeeEstimate {
+        let read_entries = self.is_disk_read as i64 + self.is_write as i64;
+        let disk_read = fee_config
+            .fee_per_disk_read_entry
+            .saturating_mul(read_entries)
+            .saturating_add(compute_fee_per_increment(
+                self.disk_read_bytes.into(),
+                fee_config.fee_per_disk_read_1kb,
+                DATA_SIZE_1KB_INCREMENT,
+            ));
+        let write = if self.is_write {
+            fee_config
+                .fee_per_write_entry
+                .saturating_add(compute_fee_per_increment(
+                    self.write_bytes.into(),
+                    fee_config.fee_per_write_1kb,
+                    DATA_SIZE_1KB_INCREMENT,
+                ))
+        } else {
+            0
+        };
+        let (rent, rent_bumps) = match &self.rent_change {
+            Some(rent_change) => (
+                compute_rent_fee(
+                    std::slice::from_ref(rent_change),
+                    rent_fee_config,
+                    self.ledger_sequence,
+                ),
+                (rent_change.old_live_until_ledger < rent_change.new_live_until_ledger) as u32,
+            ),
+            None => (0, 0),
+        };
+        LedgerEntryFeeEstimate {
+            key: self.key.clone(),
+            disk_read_bytes: self.disk_read_bytes,
+            write_bytes: self.write_bytes,
+            rent_bumps,
+            total: disk_read.saturating_add(write).saturating_add(rent),
+            disk_read,
+            write,
+            rent,
         }
     }
 }
@@ -388,6 +543,10 @@ impl DetailedInvocationResources {
 #[derive(Clone, Default)]
 pub(crate) struct InvocationMeter {
     enabled: bool,
+    wasm_profiling: bool,
+    // Instrumented modules of the profiled contracts, keyed by the Wasm hash.
+    profiled_modules: BTreeMap<Hash, Rc<ProfiledModule>>,
+    ledger_entry_metering: bool,
     stack_depth: u32,
     storage_snapshot: Storage,
     detailed_invocation_resources: Option<DetailedInvocationResources>,
@@ -520,6 +679,38 @@ impl InvocationMeter {
         self.detailed_invocation_resources.clone()
     }
 
+    // Walks down to the resources of the innermost active invocation.
+    fn current_invocation_resources<'a>(
+        detailed_invocation_resources: &'a mut Option<DetailedInvocationResources>,
+        stack_depth: u32,
+        host: &Host,
+    ) -> Result<&'a mut DetailedInvocationResources, HostError> {
+        let mut current_invocation_resources =
+            detailed_invocation_resources.as_mut().ok_or_else(|| {
+                host.err(
+                    ScErrorType::Context,
+                    ScErrorCode::InternalError,
+                    "missing invocation resources for non-root invocation",
+                    &[],
+                )
+            })?;
+
+        for _ in 0..(stack_depth - 1) {
+            current_invocation_resources = current_invocation_resources
+                .sub_call_resources
+                .last_mut()
+                .ok_or_else(|| {
+                    host.err(
+                        ScErrorType::Context,
+                        ScErrorCode::InternalError,
+                        "incorrect stack depth for invocation metering",
+                        &[],
+                    )
+                })?;
+        }
+        Ok(current_invocation_resources)
+    }
+
     fn push_invocation<'a>(
         &mut self,
         host: &'a Host,
@@ -539,34 +730,19 @@ impl InvocationMeter {
             self.stack_depth = 1;
             self.detailed_invocation_resources = Some(DetailedInvocationResources {
                 invocation,
-                resources: host.snapshot_current_resources(&self.storage_snapshot),
+                resources: host.snapshot_current_resources(&self.storage_snapshot, None),
                 sub_call_resources: vec![],
+                wasm_profile: vec![],
+                wasm_profile_hook_fuel: 0,
+                ledger_entries: vec![],
             });
             return Ok(Some(InvocationMeterScope::new(host)));
         }
-        let mut parent_invocation_resources =
-            self.detailed_invocation_resources.as_mut().ok_or_else(|| {
-                host.err(
-                    ScErrorType::Context,
-                    ScErrorCode::InternalError,
-                    "missing invocation resources for non-root invocation",
-                    &[],
-                )
-            })?;
-
-        for _ in 0..(self.stack_depth - 1) {
-            parent_invocation_resources = parent_invocation_resources
-                .sub_call_resources
-                .last_mut()
-                .ok_or_else(|| {
-                    host.err(
-                        ScErrorType::Context,
-                        ScErrorCode::InternalError,
-                        "incorrect stack depth for invocation metering",
-                        &[],
-                    )
-                })?;
-        }
+        let parent_invocation_resources = Self::current_invocation_resources(
+            &mut self.detailed_invocation_resources,
+            self.stack_depth,
+            host,
+        )?;
         // In tests we sometimes end up with multiple invocations that do
         // effectively the same thing, e.g. a test helper that registers a
         // contract will call a host function that creates contract, both can
@@ -586,8 +762,11 @@ impl InvocationMeter {
             .sub_call_resources
             .push(DetailedInvocationResources {
                 invocation: invocation.clone(),
-                resources: host.snapshot_current_resources(&self.storage_snapshot),
+                resources: host.snapshot_current_resources(&self.storage_snapshot, None),
                 sub_call_resources: vec![],
+                wasm_profile: vec![],
+                wasm_profile_hook_fuel: 0,
+                ledger_entries: vec![],
             });
 
         self.stack_depth += 1;
@@ -598,32 +777,22 @@ impl InvocationMeter {
         if self.stack_depth == 0 {
             return Ok(());
         }
-        let mut current_invocation_resources =
-            self.detailed_invocation_resources.as_mut().ok_or_else(|| {
-                host.err(
-                    ScErrorType::Context,
-                    ScErrorCode::InternalError,
-                    "missing invocation resources for non-root invocation",
-                    &[],
-                )
-            })?;
-
-        for _ in 0..(self.stack_depth - 1) {
-            current_invocation_resources = current_invocation_resources
-                .sub_call_resources
-                .last_mut()
-                .ok_or_else(|| {
-                    host.err(
-                        ScErrorType::Context,
-                        ScErrorCode::InternalError,
-                        "incorrect stack depth for invocation metering",
-                        &[],
-                    )
-                })?;
-        }
-        current_invocation_resources.resources = host
-            .snapshot_current_resources(&self.storage_snapshot)
-            .subtract(&current_invocation_resources.resources);
+        // Ledger entries are only attributed to the root invocation, as the
+        // entry changes of the sub-calls don't map to the transaction fees.
+        let mut ledger_entries = vec![];
+        let collect_ledger_entries = self.stack_depth == 1 && self.ledger_entry_metering;
+        let snapshot = host.snapshot_current_resources(
+            &self.storage_snapshot,
+            collect_ledger_entries.then_some(&mut ledger_entries),
+        );
+        let current_invocation_resources = Self::current_invocation_resources(
+            &mut self.detailed_invocation_resources,
+            self.stack_depth,
+            host,
+        )?;
+        current_invocation_resources.resources =
+            snapshot.subtract(&current_invocation_resources.resources);
+        current_invocation_resources.ledger_entries = ledger_entries;
 
         // If we're popping the root invocation in test environment, we need to
         // emulate the write-back to the module cache (typically done by the
@@ -642,6 +811,46 @@ impl InvocationMeter {
         self.stack_depth -= 1;
         Ok(())
     }
+
+    // Merges the Wasm function profile of a VM call into the current
+    // invocation.
+    fn add_wasm_profile(
+        &mut self,
+        host: &Host,
+        profile: Vec<WasmFunctionProfile>,
+        hook_fuel: u64,
+    ) -> Result<(), HostError> {
+        if self.stack_depth == 0 || profile.is_empty() {
+            return Ok(());
+        }
+        let current_invocation_resources = Self::current_invocation_resources(
+            &mut self.detailed_invocation_resources,
+            self.stack_depth,
+            host,
+        )?;
+        current_invocation_resources.wasm_profile_hook_fuel = current_invocation_resources
+            .wasm_profile_hook_fuel
+            .saturating_add(hook_fuel);
+        let wasm_profile = &mut current_invocation_resources.wasm_profile;
+        for func in profile {
+            match wasm_profile
+                .iter_mut()
+                .find(|f| f.function_index == func.function_index)
+            {
+                Some(f) => {
+                    f.calls = f.calls.saturating_add(func.calls);
+                    f.fuel = f.fuel.saturating_add(func.fuel);
+                }
+                None => wasm_profile.push(func),
+            }
+        }
+        wasm_profile.sort_by(|a, b| {
+            b.fuel
+                .cmp(&a.fuel)
+                .then(a.function_index.cmp(&b.function_index))
+        });
+        Ok(())
+    }
 }
 
 impl Host {
@@ -684,9 +893,104 @@ impl Host {
         }
     }
 
+    /// Enables invocation metering together with per-function profiling of
+    /// the Wasm code, reported in `DetailedInvocationResources::wasm_profile`.
+    ///
+    /// Profiled contracts run from an instrumented copy of their Wasm that
+    /// notifies the host about function entries and returns. The
+    /// instrumentation is built under the shadow budget, but its extra
+    /// instructions consume fuel, so profiled invocations are charged a bit
+    /// more CPU instructions than the regular ones.
+    pub fn enable_wasm_profiling(&self) {
+        self.enable_invocation_metering();
+        if let Ok(mut meter) = self.0.invocation_meter.try_borrow_mut() {
+            meter.wasm_profiling = true;
+        }
+    }
+
+    /// Enables invocation metering together with the breakdown of the storage
+    /// resources of the root invocation per ledger entry, reported in
+    /// `DetailedInvocationResources::ledger_entries`.
+    pub fn enable_ledger_entry_metering(&self) {
+        self.enable_invocation_metering();
+        if let Ok(mut meter) = self.0.invocation_meter.try_borrow_mut() {
+            meter.ledger_entry_metering = true;
+        }
+    }
+
+    pub(crate) fn is_wasm_profiling_enabled(&self) -> bool {
+        self.0
+            .invocation_meter
+            .try_borrow()
+            .is_ok_and(|meter| meter.enabled && meter.wasm_profiling)
+    }
+
+    // Runs `f` on the profiler of the currently running VM, if the VM is
+    // profiled.
+    pub(crate) fn with_current_wasm_profiler<F>(&self, f: F) -> Result<(), HostError>
+    where
+        F: FnOnce(&mut WasmProfiler),
+    {
+        if !self.is_wasm_profiling_enabled() {
+            return Ok(());
+        }
+        self.with_current_frame(|frame| {
+            if let Frame::ContractVM { vm, .. } = frame {
+                if let Some(profiler) = vm.wasm_profiler.try_borrow_mut_or_err()?.as_mut() {
+                    f(profiler);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    pub(crate) fn record_wasm_profile(
+        &self,
+        profile: Vec<WasmFunctionProfile>,
+        hook_fuel: u64,
+    ) -> Result<(), HostError> {
+        self.try_borrow_invocation_meter_mut()?
+            .add_wasm_profile(self, profile, hook_fuel)
+    }
+
+    // Swaps the VM instantiated for a contract call for a profiled one when
+    // Wasm profiling is enabled. The regular instantiation has already been
+    // charged, so the profiled VM is built under the shadow budget. The
+    // instrumented module is only built once per Wasm and then reused.
+    pub(crate) fn maybe_instantiate_profiled_vm(
+        &self,
+        vm: Rc<Vm>,
+        wasm_hash: &Hash,
+    ) -> Result<Rc<Vm>, HostError> {
+        if !self.is_wasm_profiling_enabled() {
+            return Ok(vm);
+        }
+        self.budget_ref().with_observable_shadow_mode(|| {
+            let cached = self
+                .try_borrow_invocation_meter()?
+                .profiled_modules
+                .get(wasm_hash)
+                .cloned();
+            let module = match cached {
+                Some(module) => module,
+                None => {
+                    let (code, _costs) = self.retrieve_wasm_from_storage(wasm_hash)?;
+                    let module =
+                        ProfiledModule::new(self, code.as_slice(), vm.module.cost_inputs.clone())?;
+                    self.try_borrow_invocation_meter_mut()?
+                        .profiled_modules
+                        .insert(wasm_hash.clone(), Rc::clone(&module));
+                    module
+                }
+            };
+            Vm::new_profiled(self, vm.contract_id.clone(), &module)
+        })
+    }
+
     fn snapshot_current_resources(
         &self,
         init_storage_snapshot: &Storage,
+        ledger_entries: Option<&mut Vec<LedgerEntryResources>>,
     ) -> SubInvocationResources {
         let mut invocation_resources = SubInvocationResources::default();
         let budget = self.budget_ref();
@@ -700,6 +1004,7 @@ impl Host {
             let _res = self.try_snapshot_storage_and_event_resources(
                 init_storage_snapshot,
                 &mut invocation_resources,
+                ledger_entries,
             );
             #[cfg(test)]
             _res.unwrap();
@@ -713,6 +1018,7 @@ impl Host {
         &self,
         init_storage_snapshot: &Storage,
         invocation_resources: &mut SubInvocationResources,
+        mut ledger_entries: Option<&mut Vec<LedgerEntryResources>>,
     ) -> Result<(), HostError> {
         let mut curr_storage = self.try_borrow_storage_mut()?;
         let curr_footprint = curr_storage.footprint.clone();
@@ -722,6 +1028,10 @@ impl Host {
             let maybe_init_entry = init_storage_snapshot.get_from_map(key, self)?;
             let mut init_entry_size_for_rent = 0;
             let mut init_live_until_ledger = curr_ledger_seq;
+            let mut disk_read_bytes = 0;
+            // Expired entries are treated as non-existent for the rent
+            // changes, the same way as the ledger changes do.
+            let mut is_init_entry_live = false;
             let mut is_disk_read = match key.as_ref() {
                 LedgerKey::ContractData(_) | LedgerKey::ContractCode(_) => false,
                 _ => true,
@@ -732,6 +1042,7 @@ impl Host {
                         // Only bump `init_live_until_ledger` to a value higher than the current
                         // ledger in order to get the appropriate rent bump amount.
                         init_live_until_ledger = live_until;
+                        is_init_entry_live = true;
                     } else {
                         // If the entry is persistent and it has expired, then
                         // we deal with the autorestore and thus need to mark
@@ -744,6 +1055,7 @@ impl Host {
                 metered_write_xdr(self.budget_ref(), init_entry.as_ref(), &mut buf)?;
                 if is_disk_read {
                     invocation_resources.disk_read_bytes += buf.len() as i32;
+                    disk_read_bytes = buf.len() as u32;
                 }
                 init_entry_size_for_rent =
                     entry_size_for_rent(self.budget_ref(), &init_entry, buf.len() as u32)?;
@@ -751,6 +1063,7 @@ impl Host {
             let mut entry_size = 0;
             let mut new_entry_size_for_rent = 0;
             let mut entry_live_until_ledger = None;
+            let mut encoded_entry = None;
             let maybe_entry = curr_storage.try_get_full(key, self, None)?;
             if let Some((entry, entry_live_until)) = maybe_entry {
                 let mut buf = Vec::<u8>::new();
@@ -759,6 +1072,7 @@ impl Host {
                 new_entry_size_for_rent =
                     entry_size_for_rent(self.budget_ref(), &entry, entry_size)?;
                 entry_live_until_ledger = entry_live_until;
+                encoded_entry = Some(buf);
             }
             if is_disk_read {
                 invocation_resources.disk_read_entries += 1;
@@ -794,6 +1108,48 @@ impl Host {
                     }
                 }
             }
+
+            if let Some(ledger_entries) = ledger_entries.as_mut() {
+                let is_write = matches!(curr_access_type, AccessType::ReadWrite);
+                let ttl_change = get_key_durability(key.as_ref()).map(|durability| {
+                    let old_live_until_ledger = if is_init_entry_live {
+                        init_live_until_ledger
+                    } else {
+                        0
+                    };
+                    LedgerEntryLiveUntilChange {
+                        // The key hash is not relevant for the rent.
+                        key_hash: vec![],
+                        durability,
+                        entry_type: key.discriminant(),
+                        old_live_until_ledger,
+                        new_live_until_ledger: entry_live_until_ledger
+                            .unwrap_or_default()
+                            .max(old_live_until_ledger),
+                    }
+                });
+                let entry_change = LedgerEntryChange {
+                    read_only: !is_write,
+                    encoded_key: vec![],
+                    old_entry_size_bytes_for_rent: if is_init_entry_live {
+                        init_entry_size_for_rent
+                    } else {
+                        0
+                    },
+                    encoded_new_value: if is_write { encoded_entry } else { None },
+                    new_entry_size_bytes_for_rent: new_entry_size_for_rent,
+                    ttl_change,
+                };
+                ledger_entries.push(LedgerEntryResources {
+                    key: key.as_ref().clone(),
+                    is_disk_read,
+                    is_write,
+                    disk_read_bytes,
+                    write_bytes: if is_write { entry_size } else { 0 },
+                    rent_change: extract_rent_change(&entry_change),
+                    ledger_sequence: curr_ledger_seq,
+                });
+            }
         }
         let events = self.try_borrow_events()?.externalize(self)?;
         let encoded_contract_events = encode_contract_events(self.budget_ref(), &events)?;
@@ -812,7 +1168,7 @@ fn compute_fee_per_increment(resource_value: i64, fee_rate: i64, increment: i64)
 mod test {
     use super::*;
     use crate::{
-        xdr::{ContractId, Hash},
+        xdr::{ContractCostType, ContractId, Hash},
         Symbol, TryFromVal, TryIntoVal,
     };
     use expect_test::expect;
@@ -883,6 +1239,9 @@ mod test {
                     temporary_entry_rent_bumps: 0,
                 },
                 sub_call_resources: [],
+                wasm_profile: [],
+                wasm_profile_hook_fuel: 0,
+                ledger_entries: [],
             }"#]]
         .assert_eq(
             format!(
@@ -946,6 +1305,9 @@ mod test {
                     temporary_entry_rent_bumps: 0,
                 },
                 sub_call_resources: [],
+                wasm_profile: [],
+                wasm_profile_hook_fuel: 0,
+                ledger_entries: [],
             }"#]]
         .assert_eq(
             format!(
@@ -1440,7 +1802,13 @@ mod test {
                             temporary_entry_rent_bumps: 0,
                         },
                         sub_call_resources: vec![],
+                        wasm_profile: vec![],
+                        wasm_profile_hook_fuel: 0,
+                        ledger_entries: vec![],
                     }],
+                    wasm_profile: vec![],
+                    wasm_profile_hook_fuel: 0,
+                    ledger_entries: vec![],
                 },
                 DetailedInvocationResources {
                     invocation: MeteringInvocation::InvokeContract(
@@ -1465,8 +1833,14 @@ mod test {
                         temporary_entry_rent_bumps: -9,
                     },
                     sub_call_resources: vec![],
+                    wasm_profile: vec![],
+                    wasm_profile_hook_fuel: 0,
+                    ledger_entries: vec![],
                 },
             ],
+            wasm_profile: vec![],
+            wasm_profile_hook_fuel: 0,
+            ledger_entries: vec![],
         };
 
         let fee_estimate = resources.estimate_fees(
@@ -1536,8 +1910,10 @@ mod test {
                                     temporary_entry_rent: 0,
                                 },
                                 sub_call_fee_estimates: [],
+                                ledger_entry_fee_estimates: [],
                             },
                         ],
+                        ledger_entry_fee_estimates: [],
                     },
                     DetailedFeeEstimate {
                         invocation: InvokeContract(
@@ -1562,9 +1938,201 @@ mod test {
                             temporary_entry_rent: 0,
                         },
                         sub_call_fee_estimates: [],
+                        ledger_entry_fee_estimates: [],
                     },
                 ],
+                ledger_entry_fee_estimates: [],
             }"#]]
         .assert_eq(format!("{:#?}", fee_estimate).as_str());
     }
+
+    #[test]
+    fn test_ledger_entry_metering() {
+        let host = Host::test_host_with_recording_footprint();
+        host.enable_ledger_entry_metering();
+        host.with_mut_ledger_info(|li| {
+            li.sequence_number = 100;
+            li.max_entry_ttl = 10000;
+            li.min_persistent_entry_ttl = 1000;
+            li.min_temp_entry_ttl = 16;
+        })
+        .unwrap();
+        let contract_id = host.register_test_contract_wasm(CONTRACT_STORAGE);
+        let key = Symbol::try_from_small_str("key_1").unwrap();
+        host.call(
+            contract_id,
+            Symbol::try_from_val(&host, &"put_persistent").unwrap(),
+            test_vec![&host, key, 1234_u64].into(),
+        )
+        .unwrap();
+
+        let resources = host.get_detailed_last_invocation_resources().unwrap();
+        // Contract instance, contract code and the new data entry.
+        assert_eq!(resources.ledger_entries.len(), 3);
+        let total_write_bytes: u32 = resources.ledger_entries.iter().map(|e| e.write_bytes).sum();
+        assert_eq!(total_write_bytes as i32, resources.resources.write_bytes);
+        let data_entry = resources
+            .ledger_entries
+            .iter()
+            .find(|e| matches!(e.key, LedgerKey::ContractData(_)) && e.is_write)
+            .unwrap();
+        assert!(data_entry.write_bytes > 0);
+        let rent_change = data_entry.rent_change.as_ref().unwrap();
+        assert!(rent_change.is_persistent);
+        assert_eq!(rent_change.old_size_bytes, 0);
+        assert_eq!(rent_change.old_live_until_ledger, 0);
+        assert_eq!(rent_change.new_live_until_ledger, 100 + 1000 - 1);
+        // The read-only entries don't change.
+        for entry in resources.ledger_entries.iter().filter(|e| !e.is_write) {
+            assert_eq!(entry.write_bytes, 0);
+            assert!(entry.rent_change.is_none());
+        }
+
+        let fee_estimate = resources.estimate_fees(
+            &FeeConfiguration {
+                fee_per_instruction_increment: 1000,
+                fee_per_disk_read_entry: 2000,
+                fee_per_write_1kb: 3000,
+                fee_per_write_entry: 4000,
+                fee_per_disk_read_1kb: 1500,
+                fee_per_historical_1kb: 300,
+                fee_per_contract_event_1kb: 200,
+                fee_per_transaction_size_1kb: 900,
+            },
+            6000,
+            1000,
+            2000,
+        );
+        // The new entry dominates the storage fees.
+        let entry_fees = &fee_estimate.ledger_entry_fee_estimates;
+        assert_eq!(entry_fees.len(), 3);
+        assert_eq!(entry_fees[0].key, data_entry.key);
+        assert_eq!(entry_fees[0].rent_bumps, 1);
+        assert!(entry_fees[0].write > 0 && entry_fees[0].rent > 0);
+        assert_eq!(
+            entry_fees[0].total,
+            entry_fees[0].disk_read + entry_fees[0].write + entry_fees[0].rent
+        );
+        // The written entry is charged a read, just like in the aggregate fee.
+        assert_eq!(entry_fees[0].disk_read, 2000);
+        for entry_fee in &entry_fees[1..] {
+            assert_eq!(entry_fee.total, 0);
+        }
+    }
+
+    fn wasm_with_named_functions() -> Vec<u8> {
+        use soroban_synth_wasm::{Arity, ModEmitter};
+        let me = ModEmitter::default_with_test_protocol();
+        let mut fe = me.func(Arity(0), 0);
+        for _ in 0..500 {
+            fe.i64_const(1).drop();
+        }
+        fe.push(Symbol::try_from_small_str("hot").unwrap());
+        let (me, hot) = fe.finish();
+        let mut fe = me.func(Arity(0), 0);
+        fe.push(Symbol::try_from_small_str("cold").unwrap());
+        let (me, cold) = fe.finish();
+        let mut fe = me.func(Arity(0), 0);
+        fe.call_func(cold).drop();
+        fe.call_func(hot).drop();
+        fe.call_func(cold);
+        let (mut me, test) = fe.finish();
+        me.export_func(test, "test");
+
+        // Function names subsection of the `name` section.
+        let mut names = vec![];
+        let funcs = [(hot.0, "hot"), (cold.0, "cold"), (test.0, "test")];
+        names.push(funcs.len() as u8);
+        for (index, name) in funcs {
+            names.push(index as u8);
+            names.push(name.len() as u8);
+            names.extend_from_slice(name.as_bytes());
+        }
+        let mut name_section = vec![1, names.len() as u8];
+        name_section.extend_from_slice(&names);
+        me.custom_section("name", &name_section);
+        me.finish()
+    }
+
+    #[test]
+    fn test_wasm_profiling() {
+        let host = Host::test_host_with_recording_footprint();
+        host.enable_wasm_profiling();
+        let contract = host.register_test_contract_wasm(&wasm_with_named_functions());
+        let res = host
+            .call(
+                contract,
+                Symbol::try_from_small_str("test").unwrap(),
+                host.vec_new().unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            SymbolStr::try_from_val(&host, &Symbol::try_from(res).unwrap())
+                .unwrap()
+                .to_string(),
+            "cold"
+        );
+
+        let resources = host.get_detailed_last_invocation_resources().unwrap();
+        let profile: Vec<(u32, Option<&str>, u64)> = resources
+            .wasm_profile
+            .iter()
+            .map(|f| (f.function_index, f.name.as_deref(), f.calls))
+            .collect();
+        // Sorted by the consumed fuel.
+        assert_eq!(
+            profile,
+            vec![
+                (0, Some("hot"), 1),
+                (2, Some("test"), 1),
+                (1, Some("cold"), 2),
+            ]
+        );
+        // All the fuel is attributed to some function.
+        let total_fuel: u64 = resources.wasm_profile.iter().map(|f| f.fuel).sum();
+        assert_eq!(
+            total_fuel,
+            host.budget_ref()
+                .get_tracker(ContractCostType::WasmInsnExec)
+                .unwrap()
+                .iterations
+        );
+        // 4 function entries (`i32.const` + `call`) and 3 returns from the
+        // defined functions (`call`) with the calibrated fuel costs.
+        assert_eq!(resources.wasm_profile_hook_fuel, 4 * (1 + 67) + 3 * 67);
+
+        // The instrumented module is reused by the subsequent calls.
+        host.call(
+            contract,
+            Symbol::try_from_small_str("test").unwrap(),
+            host.vec_new().unwrap(),
+        )
+        .unwrap();
+        let second_resources = host.get_detailed_last_invocation_resources().unwrap();
+        assert_eq!(second_resources.wasm_profile, resources.wasm_profile);
+        assert_eq!(
+            second_resources.wasm_profile_hook_fuel,
+            resources.wasm_profile_hook_fuel
+        );
+        assert_eq!(
+            host.try_borrow_invocation_meter()
+                .unwrap()
+                .profiled_modules
+                .len(),
+            1
+        );
+
+        // Profiles are only collected when explicitly requested.
+        let host = Host::test_host_with_recording_footprint();
+        host.enable_invocation_metering();
+        let contract = host.register_test_contract_wasm(&wasm_with_named_functions());
+        host.call(
+            contract,
+            Symbol::try_from_small_str("test").unwrap(),
+            host.vec_new().unwrap(),
+        )
+        .unwrap();
+        let resources = host.get_detailed_last_invocation_resources().unwrap();
+        assert!(resources.wasm_profile.is_empty());
+    }
 }
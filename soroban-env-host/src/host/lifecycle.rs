@@ -364,6 +364,24 @@ impl Host {
         self.call_constructor(&contract_id, self.call_args_from_obj(constructor_args)?)
     }
 
+    /// Registers a natively implemented contract at `contract_address`.
+    ///
+    /// The contract is invoked via the same frame and authorization machinery
+    /// as Wasm contracts, which allows e.g. mocking the contract dependencies
+    /// in tests without compiling them to Wasm.
+    pub fn register_builtin_test_contract(
+        &self,
+        contract_address: AddressObject,
+        contract: Rc<dyn crate::builtin_contracts::BuiltinContract>,
+    ) -> Result<(), HostError> {
+        self.register_test_contract(
+            contract_address,
+            Rc::new(crate::builtin_contracts::BuiltinContractFunctionSet(
+                contract,
+            )),
+        )
+    }
+
     // This is a test utility that allows calling constructor on a contract that
     // already exists in the storage. It is incorrect to call this
     // on a properly instantiated contract (as it must have already had the
@@ -1,4 +1,6 @@
-use crate::xdr::{ContractDataDurability, LedgerKey};
+use crate::xdr::{
+    AccountEntry, AccountEntryExt, AccountEntryExtensionV1Ext, ContractDataDurability, LedgerKey,
+};
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct LedgerInfo {
@@ -37,3 +39,29 @@ pub fn get_key_durability(key: &LedgerKey) -> Option<ContractDataDurability> {
         _ => None,
     }
 }
+
+/// Returns the `(min, max)` native balance bounds of the account for the
+/// given `base_reserve`.
+///
+/// The minimum balance covers the reserve for the account itself and all of
+/// its sub-entries (adjusted for sponsorships), as well as the selling
+/// liabilities. The maximum balance is reduced by the buying liabilities.
+pub fn get_account_balance_bounds(account: &AccountEntry, base_reserve: u32) -> (i64, i64) {
+    let base_reserve = base_reserve as i64;
+    if let AccountEntryExt::V1(ext1) = &account.ext {
+        let net_entries = if let AccountEntryExtensionV1Ext::V2(ext2) = &ext1.ext {
+            2i64 + (account.num_sub_entries as i64) + (ext2.num_sponsoring as i64)
+                - (ext2.num_sponsored as i64)
+        } else {
+            2i64 + account.num_sub_entries as i64
+        };
+        let min_balance = net_entries * base_reserve + ext1.liabilities.selling;
+        let max_balance = i64::MAX - ext1.liabilities.buying;
+        (min_balance, max_balance)
+    } else {
+        let net_entries = 2i64 + (account.num_sub_entries as i64);
+        let min_balance = net_entries * base_reserve;
+        let max_balance = i64::MAX;
+        (min_balance, max_balance)
+    }
+}
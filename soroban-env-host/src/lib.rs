@@ -58,6 +58,8 @@ pub use host::{TraceEvent, TraceHook, TraceRecord, TraceState};
 #[doc(hidden)]
 pub mod cost_runner;
 
+#[cfg(any(test, feature = "testutils"))]
+pub use builtin_contracts::BuiltinContract;
 #[cfg(any(test, feature = "testutils"))]
 pub use host::{ContractFunctionSet, ContractInvocationEvent};
 
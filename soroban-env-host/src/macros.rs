@@ -70,7 +70,7 @@ macro_rules! host_vec {
 }
 
 // This is just a variant of `host_vec` that unwraps args and the result
-#[cfg(test)]
+#[cfg(any(test, feature = "testutils"))]
 macro_rules! test_vec {
     ($host:expr $(,)?) => {
         $crate::builtin_contracts::base_types::Vec::new($host).unwrap()
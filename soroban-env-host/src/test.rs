@@ -8,6 +8,7 @@ mod bn254;
 mod budget_metering;
 mod bytes;
 mod complex;
+mod cpu_audit;
 mod crypto;
 mod depth_limit;
 mod dispatch;
@@ -26,9 +27,11 @@ mod lifecycle;
 mod lifetime_extension;
 mod linear_memory;
 mod map;
+mod merkle;
 #[cfg(feature = "testutils")]
 mod metering_benchmark;
 mod num;
+mod poseidon;
 mod post_mvp;
 mod prng;
 mod protocol_gate;
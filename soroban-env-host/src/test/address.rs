@@ -1,10 +1,10 @@
-use crate::{host_object::MuxedScAddress, Host, HostError};
+use crate::{host_object::MuxedScAddress, testutils::wasm as wasm_util, Host, HostError};
 use soroban_env_common::{
     xdr::{
         AccountId, ClaimableBalanceId, ContractId, Hash, MuxedEd25519Account, PoolId, PublicKey,
         ScAddress, ScBytes, ScErrorCode, ScErrorType, ScString, ScVal, Uint256,
     },
-    Compare, Env, StringObject, Val,
+    Compare, Env, StringObject, Symbol, TryIntoVal, Val,
 };
 
 fn extract_string(host: &Host, s: StringObject) -> String {
@@ -50,6 +50,41 @@ fn test_muxed_address_to_components_conversion() {
     assert_eq!(mux_id_val, ScVal::U64(123));
 }
 
+#[test]
+fn test_get_id_from_muxed_address_called_from_wasm() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let contract_id_obj = host.register_test_contract_wasm(
+        wasm_util::wasm_module_calling_get_id_from_muxed_address().as_slice(),
+    );
+    let muxed_address_obj = host.add_host_object(MuxedScAddress(ScAddress::MuxedAccount(
+        MuxedEd25519Account {
+            id: u64::MAX,
+            ed25519: Uint256([10; 32]),
+        },
+    )))?;
+    let res = host.call(
+        contract_id_obj,
+        Symbol::try_from_small_str("test")?,
+        test_vec![&host, muxed_address_obj].into(),
+    )?;
+    assert_eq!(host.from_host_val(res)?, ScVal::U64(u64::MAX));
+
+    // Regular addresses can't be passed where the muxed address is expected.
+    let address_obj = host.add_host_object(ScAddress::Account(AccountId(
+        PublicKey::PublicKeyTypeEd25519(Uint256([10; 32])),
+    )))?;
+    let res = host.call(
+        contract_id_obj,
+        Symbol::try_from_small_str("test")?,
+        test_vec![&host, address_obj].into(),
+    );
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Value, ScErrorCode::InvalidInput)
+    ));
+    Ok(())
+}
+
 #[test]
 fn test_invalid_muxed_address_object_conversions() {
     let host = observe_host!(Host::test_host());
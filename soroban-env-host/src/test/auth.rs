@@ -1041,6 +1041,9 @@ fn test_two_authorized_trees() {
                                 temporary_entry_rent_bumps: 0,
                             },
                             sub_call_resources: [],
+                            wasm_profile: [],
+                            wasm_profile_hook_fuel: 0,
+                            ledger_entries: [],
                         },
                         DetailedInvocationResources {
                             invocation: InvokeContract(
@@ -1068,8 +1071,14 @@ fn test_two_authorized_trees() {
                                 temporary_entry_rent_bumps: 0,
                             },
                             sub_call_resources: [],
+                            wasm_profile: [],
+                            wasm_profile_hook_fuel: 0,
+                            ledger_entries: [],
                         },
                     ],
+                    wasm_profile: [],
+                    wasm_profile_hook_fuel: 0,
+                    ledger_entries: [],
                 },
                 DetailedInvocationResources {
                     invocation: InvokeContract(
@@ -1123,6 +1132,9 @@ fn test_two_authorized_trees() {
                                 temporary_entry_rent_bumps: 0,
                             },
                             sub_call_resources: [],
+                            wasm_profile: [],
+                            wasm_profile_hook_fuel: 0,
+                            ledger_entries: [],
                         },
                         DetailedInvocationResources {
                             invocation: InvokeContract(
@@ -1150,10 +1162,19 @@ fn test_two_authorized_trees() {
                                 temporary_entry_rent_bumps: 0,
                             },
                             sub_call_resources: [],
+                            wasm_profile: [],
+                            wasm_profile_hook_fuel: 0,
+                            ledger_entries: [],
                         },
                     ],
+                    wasm_profile: [],
+                    wasm_profile_hook_fuel: 0,
+                    ledger_entries: [],
                 },
             ],
+            wasm_profile: [],
+            wasm_profile_hook_fuel: 0,
+            ledger_entries: [],
         }"#]]
     .assert_eq(
         format!(
@@ -2484,10 +2505,19 @@ fn test_require_auth_within_check_auth() {
                                 temporary_entry_rent_bumps: 1,
                             },
                             sub_call_resources: [],
+                            wasm_profile: [],
+                            wasm_profile_hook_fuel: 0,
+                            ledger_entries: [],
                         },
                     ],
+                    wasm_profile: [],
+                    wasm_profile_hook_fuel: 0,
+                    ledger_entries: [],
                 },
             ],
+            wasm_profile: [],
+            wasm_profile_hook_fuel: 0,
+            ledger_entries: [],
         }"#]]
     .assert_eq(
         format!(
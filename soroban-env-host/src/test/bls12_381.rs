@@ -89,6 +89,26 @@ struct HashToCurveTestSuite {
     vectors: Vec<TestCase>,
 }
 
+#[derive(Deserialize, Debug)]
+struct KzgProofInput {
+    commitment: String,
+    z: String,
+    y: String,
+    proof: String,
+}
+
+// A `verify_kzg_proof` case in the consensus-spec format: points are
+// compressed and a `null` output means the inputs are rejected.
+#[derive(Deserialize, Debug)]
+struct KzgProofTestCase {
+    name: String,
+    input: KzgProofInput,
+    output: Option<bool>,
+}
+
+// `[tau]G2` (`g2_monomial[1]`) of the EIP-4844 mainnet trusted setup, compressed.
+const TRUSTED_SETUP_TAU_G2: &str = "0xb5bfd7dd8cdeb128843bc287230af38926187075cbfbefa81009a2ce615ac53d2914e5870cb452d2afaaab24f3499f72185cbfee53492714734429b7b38608e23926c911cceceac9a36851477ba4c60b087041de621000edc98edada20c1def2";
+
 fn parse_hex(s: &str) -> Vec<u8> {
     Vec::from_hex(s.trim_start_matches("0x")).unwrap()
 }
@@ -1748,3 +1768,213 @@ fn test_serialization_roundtrip() -> Result<(), HostError> {
     }
     Ok(())
 }
+
+#[test]
+fn kzg_verify_point_evaluation() -> Result<(), HostError> {
+    use ark_ec::CurveGroup;
+    // Proofs are built here from a known secret `tau`, so that the commitment
+    // `[p(tau)]G1` and the proof `[(p(tau) - p(z)) / (tau - z)]G1` can be
+    // computed directly for arbitrary polynomials. The mainnet trusted setup
+    // is covered by `kzg_verify_point_evaluation_consensus_spec_vectors`.
+    let mut rng = StdRng::from_seed([0xff; 32]);
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+    let tau = Fr::rand(&mut rng);
+    let tau_g2 =
+        host.g2_affine_serialize_uncompressed(&(G2Affine::generator() * tau).into_affine())?;
+    let eval = |coeffs: &[Fr], x: Fr| {
+        coeffs
+            .iter()
+            .rev()
+            .fold(Fr::from(0u64), |acc, c| acc * x + c)
+    };
+    let g1_mul =
+        |s: Fr| host.g1_affine_serialize_uncompressed(&(G1Affine::generator() * s).into_affine());
+    // Returns `(commitment, y, proof)` for the polynomial with `coeffs` at `z`.
+    let prove = |coeffs: &[Fr], z: Fr| -> Result<(BytesObject, Fr, BytesObject), HostError> {
+        let y = eval(coeffs, z);
+        let q = (eval(coeffs, tau) - y) / (tau - z);
+        Ok((g1_mul(eval(coeffs, tau))?, y, g1_mul(q)?))
+    };
+    let verify = |commitment: BytesObject,
+                  z: U256Val,
+                  y: U256Val,
+                  proof: BytesObject|
+     -> Result<bool, HostError> {
+        host.budget_ref().reset_default()?;
+        Ok(host
+            .bls12_381_kzg_verify_point_evaluation(commitment, z, y, proof, tau_g2)?
+            .into())
+    };
+    let fr = |s: Fr| host.fr_to_u256val(s);
+    let modulus: U256Val = host
+        .obj_from_u256_pieces(
+            0x73eda753299d7d48,
+            0x3339d80809a1d805,
+            0x53bda402fffe5bfe,
+            0xffffffff00000001,
+        )?
+        .into();
+    let u256_max: U256Val = host
+        .obj_from_u256_pieces(u64::MAX, u64::MAX, u64::MAX, u64::MAX)?
+        .into();
+
+    // 1. correct proofs
+    let coeffs: Vec<Fr> = (0..16).map(|_| Fr::rand(&mut rng)).collect();
+    for z in [
+        Fr::from(0u64),
+        Fr::from(1u64),
+        -Fr::from(1u64),
+        Fr::rand(&mut rng),
+    ] {
+        let (commitment, y, proof) = prove(&coeffs, z)?;
+        assert!(verify(commitment, fr(z)?, fr(y)?, proof)?);
+    }
+    // 2. zero polynomial: the commitment and the proof are points at infinity
+    {
+        let z = Fr::rand(&mut rng);
+        assert!(verify(
+            g1_zero(&host)?,
+            fr(z)?,
+            fr(Fr::from(0u64))?,
+            g1_zero(&host)?
+        )?);
+        // a non-zero `y` doesn't match the zero polynomial
+        assert!(!verify(
+            g1_zero(&host)?,
+            fr(z)?,
+            fr(Fr::from(1u64))?,
+            g1_zero(&host)?
+        )?);
+    }
+    // 3. constant polynomial: the proof is the point at infinity
+    {
+        let (commitment, y, proof) = prove(&[Fr::from(2u64)], Fr::rand(&mut rng))?;
+        assert_eq!(y, Fr::from(2u64));
+        assert!(verify(commitment, fr(Fr::rand(&mut rng))?, fr(y)?, proof)?);
+        assert!(verify(
+            commitment,
+            fr(Fr::rand(&mut rng))?,
+            fr(y)?,
+            g1_zero(&host)?
+        )?);
+    }
+    // 4. incorrect proofs
+    {
+        let z = Fr::rand(&mut rng);
+        let (commitment, y, proof) = prove(&coeffs, z)?;
+        // wrong evaluation
+        assert!(!verify(commitment, fr(z)?, fr(y + Fr::from(1u64))?, proof)?);
+        // wrong point
+        assert!(!verify(commitment, fr(z + Fr::from(1u64))?, fr(y)?, proof)?);
+        // proof for another point
+        let (_, _, other_proof) = prove(&coeffs, z + Fr::from(1u64))?;
+        assert!(!verify(commitment, fr(z)?, fr(y)?, other_proof)?);
+        // proof is the point at infinity
+        assert!(!verify(commitment, fr(z)?, fr(y)?, g1_zero(&host)?)?);
+        // random proof
+        assert!(!verify(
+            commitment,
+            fr(z)?,
+            fr(y)?,
+            sample_g1(&host, &mut rng)?
+        )?);
+    }
+    // 5. `z` or `y` is not a canonical scalar
+    {
+        let z = Fr::rand(&mut rng);
+        let (commitment, y, proof) = prove(&coeffs, z)?;
+        for invalid in [modulus, u256_max] {
+            assert!(HostError::result_matches_err(
+                verify(commitment, invalid, fr(y)?, proof),
+                (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+            ));
+            assert!(HostError::result_matches_err(
+                verify(commitment, fr(z)?, invalid, proof),
+                (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+            ));
+        }
+    }
+    // 6. invalid points
+    {
+        let z = Fr::rand(&mut rng);
+        let (commitment, y, proof) = prove(&coeffs, z)?;
+        for invalid in [
+            sample_g1_not_on_curve(&host, &mut rng)?,
+            sample_g1_not_in_subgroup(&host, &mut rng)?,
+            sample_g1_out_of_range(&host, &mut rng)?,
+        ] {
+            assert!(HostError::result_matches_err(
+                verify(invalid, fr(z)?, fr(y)?, proof),
+                (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+            ));
+            assert!(HostError::result_matches_err(
+                verify(commitment, fr(z)?, fr(y)?, invalid),
+                (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+            ));
+        }
+        host.budget_ref().reset_default()?;
+        assert!(HostError::result_matches_err(
+            host.bls12_381_kzg_verify_point_evaluation(
+                commitment,
+                fr(z)?,
+                fr(y)?,
+                proof,
+                sample_g2_not_in_subgroup(&host, &mut rng)?
+            ),
+            (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn kzg_verify_point_evaluation_consensus_spec_vectors() -> Result<(), HostError> {
+    use ark_serialize::CanonicalDeserialize;
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+    // Points not in G1 must reach the host, so only the encoding is checked
+    // when decompressing.
+    let g1 = |s: &str| {
+        let p = G1Affine::deserialize_compressed_unchecked(parse_hex(s).as_slice()).unwrap();
+        host.g1_affine_serialize_uncompressed(&p)
+    };
+    let fr = |s: &str| {
+        let bytes = host.bytes_new_from_slice(&parse_hex(s))?;
+        host.u256_val_from_be_bytes(bytes)
+    };
+    let tau_g2 = host.g2_affine_serialize_uncompressed(
+        &G2Affine::deserialize_compressed(parse_hex(TRUSTED_SETUP_TAU_G2).as_slice()).unwrap(),
+    )?;
+    let test_cases: Vec<KzgProofTestCase> =
+        serde_json::from_slice(&std::fs::read("./src/test/data/verify_kzg_proof.json").unwrap())
+            .unwrap();
+    for case in test_cases {
+        host.budget_ref().reset_default()?;
+        let res = host.bls12_381_kzg_verify_point_evaluation(
+            g1(&case.input.commitment)?,
+            fr(&case.input.z)?,
+            fr(&case.input.y)?,
+            g1(&case.input.proof)?,
+            tau_g2,
+        );
+        match case.output {
+            Some(expected) => assert_eq!(bool::from(res?), expected, "{}", case.name),
+            None => assert!(
+                HostError::result_matches_err(
+                    res,
+                    (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+                ),
+                "{}",
+                case.name
+            ),
+        }
+    }
+    Ok(())
+}
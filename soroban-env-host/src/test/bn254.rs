@@ -1,18 +1,18 @@
 use crate::{
     crypto::bn254::{BN254_G1_SERIALIZED_SIZE, BN254_G2_SERIALIZED_SIZE},
     xdr::{ContractCostType, ScErrorCode, ScErrorType},
-    BytesObject, Env, EnvBase, ErrorHandler, Host, HostError, U256Val, U32Val,
+    BytesObject, Env, EnvBase, ErrorHandler, Host, HostError, U256Val, U32Val, U64Val,
 };
 use ark_bn254::{Fq, Fq2, Fr, G1Affine, G2Affine};
 use ark_ec::{AffineRepr, CurveGroup};
 
 use std::ops::Add;
 
-use ark_ff::{BigInteger, PrimeField, UniformRand};
+use ark_ff::{BigInteger, Field, PrimeField, UniformRand};
 use ark_serialize::CanonicalSerialize;
 use core::panic;
 use rand::{rngs::StdRng, SeedableRng};
-use soroban_env_common::{ConversionError, TryFromVal, U256};
+use soroban_env_common::{Compare, ConversionError, TryFromVal, U256};
 use std::cmp::Ordering;
 
 const MODULUS: &str = "0x2523648240000001BA344D80000000086121000000000013A700000000000013";
@@ -128,7 +128,8 @@ fn sample_g2_not_in_subgroup(host: &Host, rng: &mut StdRng) -> Result<BytesObjec
 
 fn sample_g2_out_of_range(host: &Host, rng: &mut StdRng) -> Result<BytesObject, HostError> {
     let g2 = sample_g2(host, rng)?;
-    host.bytes_copy_from_slice(g2, U32Val::from(0), MODULUS.as_bytes())
+    // overwrite the first coordinate with the (little-endian) field modulus
+    host.bytes_copy_from_slice(g2, U32Val::from(0), &Fq::MODULUS.to_bytes_le())
 }
 
 fn neg_g2(bo: BytesObject, host: &Host) -> Result<BytesObject, HostError> {
@@ -554,6 +555,373 @@ fn test_bn254_multi_pairing_check() -> Result<(), HostError> {
     Ok(())
 }
 
+// The following functions are not available before protocol 25, so their
+// tests use unobserved hosts.
+
+#[test]
+fn test_bn254_g1_msm() -> Result<(), HostError> {
+    let mut rng = StdRng::from_seed([0x5d; 32]);
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+    host.budget_ref().reset_unlimited()?;
+
+    // 1. vector lengths don't match
+    {
+        let vp = host.vec_new_from_slice(&[
+            sample_g1(&host, &mut rng)?.to_val(),
+            sample_g1(&host, &mut rng)?.to_val(),
+        ])?;
+        let vs = host.vec_new_from_slice(&[sample_fr(&host, &mut rng)?.to_val()])?;
+        assert!(HostError::result_matches_err(
+            host.bn254_g1_msm(vp, vs),
+            (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+        ));
+    }
+
+    // 2. vector length is 0
+    {
+        let vp = host.vec_new()?;
+        let vs = host.vec_new()?;
+        assert!(HostError::result_matches_err(
+            host.bn254_g1_msm(vp, vs),
+            (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+        ));
+    }
+
+    // 3. any point is invalid
+    {
+        let vp = host.vec_new_from_slice(&[
+            sample_g1(&host, &mut rng)?.to_val(),
+            invalid_g1(&host, InvalidPointTypes::PointNotOnCurve, &mut rng)?.to_val(),
+        ])?;
+        let vs = host.vec_new_from_slice(&[
+            sample_fr(&host, &mut rng)?.to_val(),
+            sample_fr(&host, &mut rng)?.to_val(),
+        ])?;
+        assert!(HostError::result_matches_err(
+            host.bn254_g1_msm(vp, vs),
+            (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+        ));
+    }
+
+    // 4. msm result equals the sum of the individual multiplications
+    {
+        let mut points = vec![];
+        let mut scalars = vec![];
+        let mut expected = g1_zero(&host)?;
+        for _ in 0..5 {
+            let p = sample_g1(&host, &mut rng)?;
+            let s = sample_fr(&host, &mut rng)?;
+            let ps = host.bn254_g1_mul(p, s)?;
+            expected = host.bn254_g1_add(expected, ps)?;
+            points.push(p.to_val());
+            scalars.push(s.to_val());
+        }
+        let vp = host.vec_new_from_slice(&points)?;
+        let vs = host.vec_new_from_slice(&scalars)?;
+        let res = host.bn254_g1_msm(vp, vs)?;
+        assert_eq!(
+            host.obj_cmp(res.into(), expected.into())?,
+            Ordering::Equal as i64
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_bn254_g2_add() -> Result<(), HostError> {
+    let mut rng = StdRng::from_seed([0x5e; 32]);
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+
+    // 1. invalid points
+    {
+        let p2 = sample_g2(&host, &mut rng)?;
+        for ty in [
+            InvalidPointTypes::TooManyBytes,
+            InvalidPointTypes::TooFewBytes,
+            InvalidPointTypes::PointNotOnCurve,
+            InvalidPointTypes::OutOfRange,
+        ] {
+            let p1 = invalid_g2(&host, ty, &mut rng)?;
+            assert!(HostError::result_matches_err(
+                host.bn254_g2_add(p1, p2),
+                (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+            ));
+            assert!(HostError::result_matches_err(
+                host.bn254_g2_add(p2, p1),
+                (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+            ));
+        }
+    }
+
+    // 2. points outside of the subgroup are allowed
+    {
+        let p1 = invalid_g2(&host, InvalidPointTypes::PointNotInSubgroup, &mut rng)?;
+        let p2 = sample_g2(&host, &mut rng)?;
+        assert!(host.bn254_g2_add(p1, p2).is_ok());
+    }
+
+    // 3. lhs.add(zero) = lhs
+    {
+        let p1 = sample_g2(&host, &mut rng)?;
+        let res = host.bn254_g2_add(p1, g2_zero(&host)?)?;
+        assert_eq!(host.obj_cmp(p1.into(), res.into())?, Ordering::Equal as i64);
+    }
+
+    // 4. commutative a + b = b + a
+    {
+        let a = sample_g2(&host, &mut rng)?;
+        let b = sample_g2(&host, &mut rng)?;
+        let a_plus_b = host.bn254_g2_add(a, b)?;
+        let b_plus_a = host.bn254_g2_add(b, a)?;
+        assert_eq!(
+            host.obj_cmp(a_plus_b.into(), b_plus_a.into())?,
+            Ordering::Equal as i64
+        );
+    }
+
+    // 5. associative (a + b) + c = a + (b + c)
+    {
+        let a = sample_g2(&host, &mut rng)?;
+        let b = sample_g2(&host, &mut rng)?;
+        let c = sample_g2(&host, &mut rng)?;
+        let aplusb = host.bn254_g2_add(a, b)?;
+        let aplusb_plus_c = host.bn254_g2_add(aplusb, c)?;
+        let bplusc = host.bn254_g2_add(b, c)?;
+        let a_plus_bplusc = host.bn254_g2_add(a, bplusc)?;
+        assert_eq!(
+            host.obj_cmp(aplusb_plus_c.into(), a_plus_bplusc.into())?,
+            Ordering::Equal as i64
+        );
+    }
+
+    // 6. a - a = zero
+    {
+        let a = sample_g2(&host, &mut rng)?;
+        let neg_a = neg_g2(a, &host)?;
+        let res = host.bn254_g2_add(a, neg_a)?;
+        let zero = g2_zero(&host)?;
+        assert_eq!(
+            host.obj_cmp(res.into(), zero.into())?,
+            Ordering::Equal as i64
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_bn254_g2_mul() -> Result<(), HostError> {
+    let mut rng = StdRng::from_seed([0x5f; 32]);
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+
+    // 1. point must be in the subgroup
+    {
+        let p = invalid_g2(&host, InvalidPointTypes::PointNotInSubgroup, &mut rng)?;
+        assert!(HostError::result_matches_err(
+            host.bn254_g2_mul(p, U256Val::from_u32(2)),
+            (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+        ));
+    }
+
+    // 2. lhs * 0 = 0
+    {
+        let lhs = sample_g2(&host, &mut rng)?;
+        let res = host.bn254_g2_mul(lhs, U256Val::from_u32(0))?;
+        let zero = g2_zero(&host)?;
+        assert_eq!(
+            host.obj_cmp(res.into(), zero.into())?,
+            Ordering::Equal as i64
+        );
+    }
+
+    // 3. lhs * 1 = lhs
+    {
+        let lhs = sample_g2(&host, &mut rng)?;
+        let res = host.bn254_g2_mul(lhs, U256Val::from_u32(1))?;
+        assert_eq!(
+            host.obj_cmp(res.into(), lhs.into())?,
+            Ordering::Equal as i64
+        );
+    }
+
+    // 4. lhs * 2 = lhs + lhs
+    {
+        let lhs = sample_g2(&host, &mut rng)?;
+        let res = host.bn254_g2_mul(lhs, U256Val::from_u32(2))?;
+        let lhs_plus_lhs = host.bn254_g2_add(lhs, lhs)?;
+        assert_eq!(
+            host.obj_cmp(res.into(), lhs_plus_lhs.into())?,
+            Ordering::Equal as i64
+        );
+    }
+
+    // 5. associative P * a * b = P * b * a
+    {
+        let p = sample_g2(&host, &mut rng)?;
+        let a = sample_fr(&host, &mut rng)?;
+        let b = sample_fr(&host, &mut rng)?;
+        let pa = host.bn254_g2_mul(p, a)?;
+        let pab = host.bn254_g2_mul(pa, b)?;
+        let pb = host.bn254_g2_mul(p, b)?;
+        let pba = host.bn254_g2_mul(pb, a)?;
+        assert_eq!(
+            host.obj_cmp(pab.into(), pba.into())?,
+            Ordering::Equal as i64
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_bn254_g2_msm() -> Result<(), HostError> {
+    let mut rng = StdRng::from_seed([0x60; 32]);
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+    host.budget_ref().reset_unlimited()?;
+
+    // 1. vector lengths don't match
+    {
+        let vp = host.vec_new_from_slice(&[sample_g2(&host, &mut rng)?.to_val()])?;
+        let vs = host.vec_new_from_slice(&[
+            sample_fr(&host, &mut rng)?.to_val(),
+            sample_fr(&host, &mut rng)?.to_val(),
+        ])?;
+        assert!(HostError::result_matches_err(
+            host.bn254_g2_msm(vp, vs),
+            (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+        ));
+    }
+
+    // 2. vector length is 0
+    {
+        let vp = host.vec_new()?;
+        let vs = host.vec_new()?;
+        assert!(HostError::result_matches_err(
+            host.bn254_g2_msm(vp, vs),
+            (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+        ));
+    }
+
+    // 3. any point is not in the subgroup
+    {
+        let vp = host.vec_new_from_slice(&[
+            sample_g2(&host, &mut rng)?.to_val(),
+            invalid_g2(&host, InvalidPointTypes::PointNotInSubgroup, &mut rng)?.to_val(),
+        ])?;
+        let vs = host.vec_new_from_slice(&[
+            sample_fr(&host, &mut rng)?.to_val(),
+            sample_fr(&host, &mut rng)?.to_val(),
+        ])?;
+        assert!(HostError::result_matches_err(
+            host.bn254_g2_msm(vp, vs),
+            (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+        ));
+    }
+
+    // 4. msm result equals the sum of the individual multiplications
+    {
+        let mut points = vec![];
+        let mut scalars = vec![];
+        let mut expected = g2_zero(&host)?;
+        for _ in 0..5 {
+            let p = sample_g2(&host, &mut rng)?;
+            let s = sample_fr(&host, &mut rng)?;
+            let ps = host.bn254_g2_mul(p, s)?;
+            expected = host.bn254_g2_add(expected, ps)?;
+            points.push(p.to_val());
+            scalars.push(s.to_val());
+        }
+        let vp = host.vec_new_from_slice(&points)?;
+        let vs = host.vec_new_from_slice(&scalars)?;
+        let res = host.bn254_g2_msm(vp, vs)?;
+        assert_eq!(
+            host.obj_cmp(res.into(), expected.into())?,
+            Ordering::Equal as i64
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_bn254_fr_arithmetic() -> Result<(), HostError> {
+    let mut rng = StdRng::from_seed([0x61; 32]);
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+
+    let assert_fr_eq = |res: U256Val, expected: Fr| -> Result<(), HostError> {
+        let expected = fr_to_u256val(&host, expected)?;
+        assert_eq!(
+            host.compare(&res.to_val(), &expected.to_val())?,
+            Ordering::Equal
+        );
+        Ok(())
+    };
+
+    for _ in 0..10 {
+        let a = Fr::rand(&mut rng);
+        let b = Fr::rand(&mut rng);
+        let av = fr_to_u256val(&host, a)?;
+        let bv = fr_to_u256val(&host, b)?;
+        assert_fr_eq(host.bn254_fr_add(av, bv)?, a + b)?;
+        assert_fr_eq(host.bn254_fr_sub(av, bv)?, a - b)?;
+        assert_fr_eq(host.bn254_fr_mul(av, bv)?, a * b)?;
+        assert_fr_eq(host.bn254_fr_inv(av)?, a.inverse().unwrap())?;
+        for exp in [0u64, 1, 2, 0xdeadbeef, u64::MAX] {
+            let ev = host.map_err(U64Val::try_from_val(&host, &exp))?;
+            assert_fr_eq(host.bn254_fr_pow(av, ev)?, a.pow([exp]))?;
+        }
+    }
+
+    // inputs are reduced modulo r
+    {
+        let r_plus_one = host.obj_from_u256_pieces(
+            0x30644e72e131a029,
+            0xb85045b68181585d,
+            0x2833e84879b97091,
+            0x43e1f593f0000002,
+        )?;
+        assert_fr_eq(
+            host.bn254_fr_add(r_plus_one.into(), U256Val::from_u32(0))?,
+            Fr::from(1u64),
+        )?;
+    }
+
+    // 0 - 1 = r - 1
+    {
+        let res = host.bn254_fr_sub(U256Val::from_u32(0), U256Val::from_u32(1))?;
+        assert_fr_eq(res, -Fr::from(1u64))?;
+    }
+
+    // inverting zero fails
+    assert!(HostError::result_matches_err(
+        host.bn254_fr_inv(U256Val::from_u32(0)),
+        (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+    ));
+
+    Ok(())
+}
+
 #[test]
 fn test_serialization_roundtrip() -> Result<(), HostError> {
     let mut rng = StdRng::from_seed([0xff; 32]);
@@ -642,3 +1010,188 @@ fn test_serialization_roundtrip() -> Result<(), HostError> {
     }
     Ok(())
 }
+
+#[test]
+fn test_bn254_check_points() -> Result<(), HostError> {
+    let mut rng = StdRng::from_seed([0x5e; 32]);
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+
+    // g1
+    {
+        assert!(bool::from(
+            host.bn254_check_g1_is_on_curve(g1_zero(&host)?)?
+        ));
+        for _ in 0..10 {
+            let p = sample_g1(&host, &mut rng)?;
+            assert!(bool::from(host.bn254_check_g1_is_on_curve(p)?));
+            let p = sample_g1_not_on_curve(&host, &mut rng)?;
+            assert!(!bool::from(host.bn254_check_g1_is_on_curve(p)?));
+        }
+        for ty in [
+            InvalidPointTypes::TooManyBytes,
+            InvalidPointTypes::TooFewBytes,
+            InvalidPointTypes::OutOfRange,
+        ] {
+            let p = invalid_g1(&host, ty, &mut rng)?;
+            assert!(HostError::result_matches_err(
+                host.bn254_check_g1_is_on_curve(p),
+                (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+            ));
+        }
+    }
+    // g2
+    {
+        assert!(bool::from(
+            host.bn254_check_g2_is_on_curve(g2_zero(&host)?)?
+        ));
+        assert!(bool::from(
+            host.bn254_check_g2_is_in_subgroup(g2_zero(&host)?)?
+        ));
+        for _ in 0..10 {
+            let p = sample_g2(&host, &mut rng)?;
+            assert!(bool::from(host.bn254_check_g2_is_on_curve(p)?));
+            assert!(bool::from(host.bn254_check_g2_is_in_subgroup(p)?));
+            let p = sample_g2_not_in_subgroup(&host, &mut rng)?;
+            assert!(bool::from(host.bn254_check_g2_is_on_curve(p)?));
+            assert!(!bool::from(host.bn254_check_g2_is_in_subgroup(p)?));
+            let p = sample_g2_not_on_curve(&host, &mut rng)?;
+            assert!(!bool::from(host.bn254_check_g2_is_on_curve(p)?));
+            // the subgroup check requires the point to be on the curve
+            assert!(HostError::result_matches_err(
+                host.bn254_check_g2_is_in_subgroup(p),
+                (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+            ));
+        }
+        for ty in [
+            InvalidPointTypes::TooManyBytes,
+            InvalidPointTypes::TooFewBytes,
+            InvalidPointTypes::OutOfRange,
+        ] {
+            let p = invalid_g2(&host, ty, &mut rng)?;
+            assert!(HostError::result_matches_err(
+                host.bn254_check_g2_is_on_curve(p),
+                (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn fq_from_hex(hex_str: &str) -> Fq {
+    Fq::from_be_bytes_mod_order(&hex::decode(hex_str).unwrap())
+}
+
+fn g1_from_hex(host: &Host, x: &str, y: &str) -> Result<BytesObject, HostError> {
+    let p = G1Affine::new(fq_from_hex(x), fq_from_hex(y));
+    host.bn254_g1_affine_serialize_uncompressed(&p)
+}
+
+fn fq_bytesobj(host: &Host, fq: Fq) -> Result<BytesObject, HostError> {
+    let mut buf: Vec<u8> = Vec::with_capacity(32);
+    fq.serialize_uncompressed(&mut buf).unwrap();
+    host.add_host_object(host.scbytes_from_slice(&buf)?)
+}
+
+#[test]
+fn test_bn254_map_fp_to_g1() -> Result<(), HostError> {
+    let mut rng = StdRng::from_seed([0x5e; 32]);
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+
+    // 1. known answers, `u = 0` and `u = 1`
+    {
+        let res = host.bn254_map_fp_to_g1(fq_bytesobj(&host, Fq::ZERO)?)?;
+        let expected = g1_from_hex(
+            &host,
+            "183227397098d014dc2822db40c0ac2ecbc0b548b438e5469e10460b6c3e7ea3",
+            "0a6ea289876b139cfe2cd1f08c065a2ab4aad542eaccb013520ea36934e877b4",
+        )?;
+        assert_eq!(
+            host.obj_cmp(res.into(), expected.into())?,
+            Ordering::Equal as i64
+        );
+        let res = host.bn254_map_fp_to_g1(fq_bytesobj(&host, Fq::ONE)?)?;
+        let expected = g1_from_hex(
+            &host,
+            "2b8d79cdcaaca9beddf982188d7d92fd2acc298e53b6ec72d69aab86960a1727",
+            "16de5b0e1c87130160106734a03a0e2a4a78ed715dba060f06235c2abdb920e5",
+        )?;
+        assert_eq!(
+            host.obj_cmp(res.into(), expected.into())?,
+            Ordering::Equal as i64
+        );
+    }
+
+    // 2. random inputs map to valid points
+    for _ in 0..20 {
+        let res = host.bn254_map_fp_to_g1(fq_bytesobj(&host, Fq::rand(&mut rng))?)?;
+        assert!(bool::from(host.bn254_check_g1_is_on_curve(res)?));
+    }
+
+    // 3. invalid field elements
+    {
+        let too_long = host.bytes_new_from_slice(&[0u8; 33])?;
+        assert!(HostError::result_matches_err(
+            host.bn254_map_fp_to_g1(too_long),
+            (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+        ));
+        let out_of_range = host.bytes_new_from_slice(&[0xff; 32])?;
+        assert!(HostError::result_matches_err(
+            host.bn254_map_fp_to_g1(out_of_range),
+            (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_bn254_hash_to_g1() -> Result<(), HostError> {
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+
+    // test vectors of the `BN254G1_XMD:SHA-256_SVDW_RO_` suite, matching
+    // the gnark-crypto implementation
+    let dst = host.bytes_new_from_slice(b"QUUX-V01-CS02-with-BN254G1_XMD:SHA-256_SVDW_RO_")?;
+    let vectors = [
+        (
+            "",
+            "0a976ab906170db1f9638d376514dbf8c42aef256a54bbd48521f20749e59e86",
+            "02925ead66b9e68bfc309b014398640ab55f6619ab59bc1fab2210ad4c4d53d5",
+        ),
+        (
+            "abc",
+            "23f717bee89b1003957139f193e6be7da1df5f1374b26a4643b0378b5baf53d1",
+            "04142f826b71ee574452dbc47e05bc3e1a647478403a7ba38b7b93948f4e151d",
+        ),
+    ];
+    for (msg, x, y) in vectors {
+        let msg = host.bytes_new_from_slice(msg.as_bytes())?;
+        let res = host.bn254_hash_to_g1(msg, dst)?;
+        let expected = g1_from_hex(&host, x, y)?;
+        assert_eq!(
+            host.obj_cmp(res.into(), expected.into())?,
+            Ordering::Equal as i64
+        );
+    }
+
+    // invalid dst lengths
+    let msg = host.bytes_new_from_slice(b"abc")?;
+    for dst_len in [0, 256] {
+        let dst = host.bytes_new_from_slice(&vec![0u8; dst_len])?;
+        assert!(HostError::result_matches_err(
+            host.bn254_hash_to_g1(msg, dst),
+            (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+        ));
+    }
+    Ok(())
+}
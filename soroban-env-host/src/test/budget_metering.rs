@@ -5,11 +5,11 @@ use crate::{
         metered_xdr::metered_write_xdr,
     },
     xdr::{ContractCostType, ScMap, ScMapEntry, ScVal},
-    Env, ErrorHandler, Host, HostError, Symbol, Val,
+    AddressObject, Env, ErrorHandler, Host, HostError, Symbol, Val,
 };
 use expect_test::{self, expect};
 use soroban_env_common::xdr::{ScErrorCode, ScErrorType};
-use soroban_test_wasms::VEC;
+use soroban_test_wasms::{ADD_I32, INVOKE_CONTRACT, VEC};
 
 #[test]
 fn xdr_object_conversion() -> Result<(), HostError> {
@@ -546,3 +546,158 @@ fn total_amount_charged_from_random_inputs() -> Result<(), HostError> {
 
     Ok(())
 }
+
+#[test]
+fn budget_profile_attributes_charges_to_call_stack() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let caller_obj = host.register_test_contract_wasm(INVOKE_CONTRACT);
+    let callee_obj = host.register_test_contract_wasm(ADD_I32);
+    let args = host.test_vec_obj::<i32>(&[5, 6])?;
+    let args = host.vec_push_back(args, callee_obj.to_val())?;
+
+    let budget = host.budget_cloned();
+    assert_eq!(budget.get_profile()?, None);
+    budget.reset_unlimited()?;
+    budget.enable_profiling()?;
+    host.call(
+        caller_obj,
+        Symbol::try_from_small_str("add_with").unwrap(),
+        args,
+    )?;
+    let profile = budget.get_profile()?.unwrap();
+
+    // Every charge is accounted for.
+    let total_cpu: u64 = profile.entries.iter().map(|e| e.cpu_insns).sum();
+    let total_mem: u64 = profile.entries.iter().map(|e| e.mem_bytes).sum();
+    assert_eq!(total_cpu, budget.get_cpu_insns_consumed()?);
+    assert_eq!(total_mem, budget.get_mem_bytes_consumed()?);
+
+    let label = |obj: AddressObject, func: &str| -> Result<String, HostError> {
+        let id = host.contract_id_from_address(obj)?;
+        let contract = stellar_strkey::Strkey::Contract(stellar_strkey::Contract(id.0 .0));
+        Ok(format!("{}:{}", contract.to_string(), func))
+    };
+    let caller = label(caller_obj, "add_with")?;
+    let callee = label(callee_obj, "add")?;
+    let cpu_under = |stack: &[&str], ty: ContractCostType| -> u64 {
+        profile
+            .entries
+            .iter()
+            .filter(|e| {
+                e.cost_type == ty && e.stack.iter().map(String::as_str).eq(stack.iter().copied())
+            })
+            .map(|e| e.cpu_insns)
+            .sum()
+    };
+    // Wasm execution is attributed to the contract frames themselves, the
+    // host function dispatch to the host functions called by the contracts.
+    // The contract is invoked via the `call` host function, which is the
+    // root of every stack.
+    let wasm = ContractCostType::WasmInsnExec;
+    let dispatch = ContractCostType::DispatchHostFunction;
+    assert!(cpu_under(&["call", &caller], wasm) > 0);
+    assert!(cpu_under(&["call", &caller, "call", &callee], wasm) > 0);
+    assert!(cpu_under(&["call", &caller, "contract_event"], dispatch) > 0);
+    assert!(cpu_under(&["call", &caller, "call"], dispatch) > 0);
+    assert!(
+        cpu_under(
+            &["call", &caller, "call", &callee, "contract_event"],
+            dispatch
+        ) > 0
+    );
+    assert_eq!(cpu_under(&["call", &caller, "call"], wasm), 0);
+
+    // The folded output has one line per charged stack and cost type.
+    let folded = profile.folded_cpu_insns();
+    assert_eq!(
+        folded.lines().count(),
+        profile.entries.iter().filter(|e| e.cpu_insns > 0).count()
+    );
+    let wasm_line_prefix = format!("call;{caller};call;{callee};WasmInsnExec ");
+    let wasm_line = folded
+        .lines()
+        .find(|l| l.starts_with(&wasm_line_prefix))
+        .unwrap();
+    assert_eq!(
+        wasm_line[wasm_line_prefix.len()..].parse::<u64>().unwrap(),
+        cpu_under(&["call", &caller, "call", &callee], wasm)
+    );
+
+    // Resetting the tracker discards the collected charges.
+    budget.reset_tracker()?;
+    assert!(budget.get_profile()?.unwrap().entries.is_empty());
+    Ok(())
+}
+
+#[test]
+fn budget_cost_params_round_trip() -> Result<(), HostError> {
+    let (cpu_params, mem_params) = Budget::default().get_cost_params()?;
+    assert_eq!(cpu_params.0.len(), ContractCostType::variants().len());
+    assert_eq!(mem_params.0.len(), ContractCostType::variants().len());
+
+    let budget = Budget::try_from_configs(100, 100, cpu_params.clone(), mem_params.clone())?;
+    assert_eq!(budget.get_cost_params()?, (cpu_params, mem_params));
+    Ok(())
+}
+
+#[test]
+fn budget_timeline_records_charge_stream() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let caller_obj = host.register_test_contract_wasm(INVOKE_CONTRACT);
+    let callee_obj = host.register_test_contract_wasm(ADD_I32);
+    let args = host.test_vec_obj::<i32>(&[5, 6])?;
+    let args = host.vec_push_back(args, callee_obj.to_val())?;
+
+    let budget = host.budget_cloned();
+    assert_eq!(budget.get_timeline()?, None);
+    budget.reset_unlimited()?;
+    budget.enable_timeline()?;
+    host.call(
+        caller_obj,
+        Symbol::try_from_small_str("add_with").unwrap(),
+        args,
+    )?;
+    let timeline = budget.get_timeline()?.unwrap();
+
+    // Every charge is recorded, in order.
+    let total_cpu: u64 = timeline.entries.iter().map(|e| e.cpu_insns).sum();
+    let total_mem: u64 = timeline.entries.iter().map(|e| e.mem_bytes).sum();
+    assert_eq!(total_cpu, budget.get_cpu_insns_consumed()?);
+    assert_eq!(total_mem, budget.get_mem_bytes_consumed()?);
+    assert!(timeline
+        .entries
+        .iter()
+        .enumerate()
+        .all(|(i, e)| e.sequence == i as u64));
+
+    // The caller runs in the first frame and the callee in the nested one.
+    let wasm_depths: Vec<u32> = timeline
+        .entries
+        .iter()
+        .filter(|e| e.cost_type == ContractCostType::WasmInsnExec)
+        .map(|e| e.depth)
+        .collect();
+    assert!(wasm_depths.contains(&1));
+    assert!(wasm_depths.contains(&2));
+    assert_eq!(timeline.entries.iter().map(|e| e.depth).max(), Some(2));
+
+    let csv = timeline.to_csv();
+    assert_eq!(csv.lines().count(), timeline.entries.len() + 1);
+    assert_eq!(
+        csv.lines().next(),
+        Some("sequence,cost_type,iterations,input,cpu_insns,mem_bytes,depth")
+    );
+    let json: serde_json::Value = serde_json::from_str(&timeline.to_json()).unwrap();
+    let json_entries = json.as_array().unwrap();
+    assert_eq!(json_entries.len(), timeline.entries.len());
+    let last = timeline.entries.last().unwrap();
+    let last_json = json_entries.last().unwrap();
+    assert_eq!(last_json["cost_type"], last.cost_type.name());
+    assert_eq!(last_json["cpu_insns"], last.cpu_insns);
+    assert_eq!(last_json["depth"], last.depth);
+
+    // Resetting the tracker discards the recorded charges.
+    budget.reset_tracker()?;
+    assert!(budget.get_timeline()?.unwrap().entries.is_empty());
+    Ok(())
+}
@@ -0,0 +1,253 @@
+// Attributes the cpu instructions actually executed during a contract
+// invocation to the cost types charged along the way. The host charges for
+// work before doing it, so the instructions measured between two charges are
+// attributed to the first of them. Cost types with a ratio of measured to
+// charged instructions well above 1 are likely under-priced in the workload.
+//
+// The audits of real workloads live in `metering_benchmark` and have the same
+// constraints as the other tests there.
+
+use crate::{budget::AsBudget, xdr::ContractCostType, Env, Host, HostError, Symbol, TryFromVal};
+use soroban_test_wasms::ADD_I32;
+use std::{cell::RefCell, rc::Rc};
+
+const N_COST_TYPES: usize = ContractCostType::variants().len();
+
+/// Attributes measured instructions to the cost type of the latest charge.
+pub(crate) struct CpuAuditRecorder {
+    current: Option<ContractCostType>,
+    charged: [u64; N_COST_TYPES],
+    measured: [u64; N_COST_TYPES],
+    unattributed: u64,
+}
+
+impl CpuAuditRecorder {
+    pub(crate) fn new() -> Self {
+        Self {
+            current: None,
+            charged: [0; N_COST_TYPES],
+            measured: [0; N_COST_TYPES],
+            unattributed: 0,
+        }
+    }
+
+    /// Records a charge, given the instructions measured since the previous
+    /// one.
+    pub(crate) fn record_charge(&mut self, ty: ContractCostType, cpu_charged: u64, measured: u64) {
+        self.attribute(measured);
+        self.charged[ty as usize] = self.charged[ty as usize].saturating_add(cpu_charged);
+        self.current = Some(ty);
+    }
+
+    /// Ends the recording, given the instructions measured since the last
+    /// charge.
+    pub(crate) fn finish(mut self, measured: u64) -> CpuAudit {
+        self.attribute(measured);
+        let entries = ContractCostType::variants()
+            .iter()
+            .filter(|ct| self.charged[**ct as usize] > 0 || self.measured[**ct as usize] > 0)
+            .map(|ct| CpuAuditEntry {
+                cost_type: *ct,
+                charged_cpu_insns: self.charged[*ct as usize],
+                measured_cpu_insns: self.measured[*ct as usize],
+            })
+            .collect();
+        CpuAudit {
+            entries,
+            unattributed_cpu_insns: self.unattributed,
+        }
+    }
+
+    fn attribute(&mut self, measured: u64) {
+        match self.current {
+            Some(ty) => {
+                self.measured[ty as usize] = self.measured[ty as usize].saturating_add(measured)
+            }
+            None => self.unattributed = self.unattributed.saturating_add(measured),
+        }
+    }
+}
+
+/// Charged and measured cpu instructions of a cost type.
+#[derive(Clone, Debug)]
+pub(crate) struct CpuAuditEntry {
+    pub(crate) cost_type: ContractCostType,
+    pub(crate) charged_cpu_insns: u64,
+    pub(crate) measured_cpu_insns: u64,
+}
+
+impl CpuAuditEntry {
+    /// Ratio of the measured to the charged instructions, above 1 for
+    /// under-charged cost types.
+    pub(crate) fn ratio(&self) -> f64 {
+        self.measured_cpu_insns as f64 / (self.charged_cpu_insns as f64).max(1.0)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CpuAudit {
+    pub(crate) entries: Vec<CpuAuditEntry>,
+    /// Instructions measured before the first charge.
+    pub(crate) unattributed_cpu_insns: u64,
+}
+
+impl CpuAudit {
+    /// Returns the cost types whose measured instructions exceed the charged
+    /// ones by more than `min_ratio`, most under-charged first.
+    pub(crate) fn under_charged(&self, min_ratio: f64) -> Vec<&CpuAuditEntry> {
+        let mut res: Vec<&CpuAuditEntry> = self
+            .entries
+            .iter()
+            .filter(|e| e.ratio() > min_ratio)
+            .collect();
+        res.sort_by(|a, b| b.ratio().total_cmp(&a.ratio()));
+        res
+    }
+
+    #[cfg(feature = "testutils")]
+    pub(crate) fn report(&self, min_ratio: f64) {
+        println!();
+        println!(
+            "{:<40} {:>16} {:>16} {:>10}",
+            "cost_type", "charged_cpu", "measured_cpu", "ratio"
+        );
+        for e in self.entries.iter() {
+            println!(
+                "{:<40} {:>16} {:>16} {:>10.3}",
+                format!("{:?}", e.cost_type),
+                e.charged_cpu_insns,
+                e.measured_cpu_insns,
+                e.ratio()
+            );
+        }
+        println!("unattributed cpu insns: {}", self.unattributed_cpu_insns);
+        let under_charged = self.under_charged(min_ratio);
+        if under_charged.is_empty() {
+            println!("no cost type is under-charged by more than {min_ratio:.2}x");
+        } else {
+            println!("under-charged by more than {min_ratio:.2}x:");
+            for e in under_charged {
+                println!("    {:?}: {:.3}x", e.cost_type, e.ratio());
+            }
+        }
+        println!();
+    }
+}
+
+/// Calls a contract function while counting the actual cpu instructions
+/// executed, and attributes them to the cost types charged along the way.
+#[cfg(feature = "testutils")]
+pub(crate) fn audited_call(
+    host: &Host,
+    contract: crate::AddressObject,
+    func: Symbol,
+    args: crate::VecObject,
+) -> Result<(Result<crate::Val, HostError>, CpuAudit), HostError> {
+    use soroban_bench_utils::HostTracker;
+
+    struct AuditState {
+        tracker: HostTracker<'static>,
+        last_count: u64,
+        recorder: CpuAuditRecorder,
+    }
+
+    let budget = host.as_budget().clone();
+    budget.reset_unlimited()?;
+    let state = Rc::new(RefCell::new(AuditState {
+        tracker: HostTracker::new(),
+        last_count: 0,
+        recorder: CpuAuditRecorder::new(),
+    }));
+    state.borrow_mut().tracker.start(None);
+
+    let hook_state = state.clone();
+    budget.set_charge_hook(Some(Rc::new(move |ty, cpu| {
+        let st = &mut *hook_state.borrow_mut();
+        let measured = st.tracker.cpu_insns_so_far().saturating_sub(st.last_count);
+        st.recorder.record_charge(ty, cpu, measured);
+        // The instructions spent in the hook are left out.
+        st.last_count = st.tracker.cpu_insns_so_far();
+    })))?;
+    let res = host.call(contract, func, args);
+    budget.set_charge_hook(None)?;
+
+    let AuditState {
+        mut tracker,
+        last_count,
+        recorder,
+    } = Rc::into_inner(state)
+        .expect("the charge hook has been dropped")
+        .into_inner();
+    let measured = tracker.cpu_insns_so_far().saturating_sub(last_count);
+    let _ = tracker.stop();
+    Ok((res, recorder.finish(measured)))
+}
+
+#[test]
+fn cpu_audit_attributes_instructions_to_latest_charge() {
+    let mut recorder = CpuAuditRecorder::new();
+    recorder.record_charge(ContractCostType::DispatchHostFunction, 100, 7);
+    recorder.record_charge(ContractCostType::ComputeSha256Hash, 1000, 150);
+    // The start of Wasm execution, whose fuel is charged once it stops.
+    recorder.record_charge(ContractCostType::WasmInsnExec, 0, 3000);
+    recorder.record_charge(ContractCostType::WasmInsnExec, 400, 500);
+    recorder.record_charge(ContractCostType::ComputeSha256Hash, 1000, 10);
+    let audit = recorder.finish(1200);
+
+    // Only the instructions before the first charge are unattributed.
+    assert_eq!(audit.unattributed_cpu_insns, 7);
+    let entry = |ty| {
+        let e = audit.entries.iter().find(|e| e.cost_type == ty).unwrap();
+        (e.charged_cpu_insns, e.measured_cpu_insns)
+    };
+    assert_eq!(entry(ContractCostType::DispatchHostFunction), (100, 150));
+    assert_eq!(entry(ContractCostType::ComputeSha256Hash), (2000, 4200));
+    assert_eq!(entry(ContractCostType::WasmInsnExec), (400, 510));
+    assert_eq!(audit.entries.len(), 3);
+
+    let under_charged: Vec<ContractCostType> = audit
+        .under_charged(1.4)
+        .iter()
+        .map(|e| e.cost_type)
+        .collect();
+    assert_eq!(
+        under_charged,
+        vec![
+            ContractCostType::ComputeSha256Hash,
+            ContractCostType::DispatchHostFunction
+        ]
+    );
+}
+
+#[test]
+fn charge_hook_reports_every_charge() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let contract_id_obj = host.register_test_contract_wasm(ADD_I32);
+    let budget = host.as_budget().clone();
+    let charges: Rc<RefCell<Vec<(ContractCostType, u64)>>> = Default::default();
+    let hook_charges = charges.clone();
+    budget.set_charge_hook(Some(Rc::new(move |ty, cpu| {
+        hook_charges.borrow_mut().push((ty, cpu))
+    })))?;
+    let cpu_before = budget.get_cpu_insns_consumed()?;
+    let res = host.call(
+        contract_id_obj,
+        Symbol::try_from_small_str("add")?,
+        host.test_vec_obj(&[4i32, 7i32])?,
+    )?;
+    budget.set_charge_hook(None)?;
+    assert_eq!(i32::try_from_val(&host, &res)?, 11);
+
+    let charges = charges.borrow();
+    let cpu_reported: u64 = charges.iter().map(|(_, cpu)| cpu).sum();
+    assert_eq!(cpu_reported, budget.get_cpu_insns_consumed()? - cpu_before);
+    // The start of the Wasm execution precedes the charge of its fuel.
+    let wasm_start = charges
+        .iter()
+        .position(|c| *c == (ContractCostType::WasmInsnExec, 0))
+        .unwrap();
+    assert!(charges[wasm_start + 1..]
+        .iter()
+        .any(|(ty, cpu)| *ty == ContractCostType::WasmInsnExec && *cpu > 0));
+    Ok(())
+}
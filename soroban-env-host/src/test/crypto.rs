@@ -97,6 +97,94 @@ fn keccak256_test() {
     ));
 }
 
+#[test]
+fn sha512_test() {
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version().unwrap() < 25 {
+        return;
+    }
+    let compute_hash = |input_bytes: &[u8]| -> Result<String, HostError> {
+        let bytes_obj = host.bytes_new_from_slice(input_bytes)?;
+        let hash_obj = host.compute_hash_sha512(bytes_obj)?;
+        Ok(host
+            .fixed_length_bytes_from_bytesobj_input::<Vec<u8>, 64>("hash", hash_obj)?
+            .encode_hex())
+    };
+    assert_eq!(
+        compute_hash(&[]).unwrap(),
+        "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+    );
+    assert_eq!(
+        compute_hash(b"abc").unwrap(),
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+    );
+    host.budget_ref().reset_default().unwrap();
+    let too_long_vec = vec![1u8; 10_000_000];
+    assert!(is_budget_exceeded(
+        compute_hash(too_long_vec.as_slice()).err().unwrap()
+    ));
+}
+
+#[test]
+fn blake2b_test() {
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version().unwrap() < 25 {
+        return;
+    }
+    let compute_hash = |input_bytes: &[u8], output_len: u32| -> Result<String, HostError> {
+        let bytes_obj = host.bytes_new_from_slice(input_bytes)?;
+        let hash_obj = host.compute_hash_blake2b(bytes_obj, U32Val::from(output_len))?;
+        let hash = host.visit_obj(hash_obj, |b: &crate::xdr::ScBytes| Ok(b.to_vec()))?;
+        assert_eq!(hash.len(), output_len as usize);
+        Ok(hash.encode_hex())
+    };
+    assert_eq!(
+        compute_hash(b"abc", 64).unwrap(),
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+    );
+    // BLAKE2b-256 is not a truncation of BLAKE2b-512, the output length is
+    // a part of the hash parameters.
+    assert_eq!(
+        compute_hash(&[], 32).unwrap(),
+        "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+    );
+    assert_eq!(
+        compute_hash(b"abc", 32).unwrap(),
+        "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319"
+    );
+    // Invalid output lengths.
+    assert!(is_crypto_error(compute_hash(b"abc", 0).err().unwrap()));
+    assert!(is_crypto_error(compute_hash(b"abc", 65).err().unwrap()));
+}
+
+#[test]
+fn blake3_test() {
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version().unwrap() < 25 {
+        return;
+    }
+    let compute_hash = |input_bytes: &[u8]| -> Result<String, HostError> {
+        let bytes_obj = host.bytes_new_from_slice(input_bytes)?;
+        let hash_obj = host.compute_hash_blake3(bytes_obj)?;
+        Ok(host
+            .hash_from_bytesobj_input("hash", hash_obj)?
+            .0
+            .encode_hex())
+    };
+    assert_eq!(
+        compute_hash(&[]).unwrap(),
+        "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+    );
+    assert_eq!(
+        compute_hash(b"abc").unwrap(),
+        "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+    );
+    assert_eq!(
+        compute_hash(b"test vector for soroban").unwrap(),
+        "246b2b1a40f4b6acfae7f465b542ecbd9f13200f0cbf1774939d9d2739f7f422"
+    );
+}
+
 #[test]
 fn ed25519_verify_test() {
     let host = observe_host!(Host::test_host());
@@ -179,6 +267,95 @@ fn ed25519_verify_test() {
     ).err().unwrap()));
 }
 
+#[test]
+fn ed25519_verify_batch_test() -> Result<(), HostError> {
+    // `verify_sig_ed25519_batch` is not available before protocol 25.
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+
+    // (public key, message, signature) from
+    // https://datatracker.ietf.org/doc/html/rfc8032#section-7.1
+    let vectors = [
+        (
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        ),
+        (
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "72",
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+        ),
+        (
+            "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+            "af82",
+            "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+        ),
+    ];
+    let batch = |vectors: &[(&str, &str, &str)]| -> Result<(), HostError> {
+        let mut ks = vec![];
+        let mut xs = vec![];
+        let mut ss = vec![];
+        for (k, x, s) in vectors {
+            ks.push(
+                host.bytes_new_from_slice(&hex::decode(k).unwrap())?
+                    .to_val(),
+            );
+            xs.push(
+                host.bytes_new_from_slice(&hex::decode(x).unwrap())?
+                    .to_val(),
+            );
+            ss.push(
+                host.bytes_new_from_slice(&hex::decode(s).unwrap())?
+                    .to_val(),
+            );
+        }
+        host.verify_sig_ed25519_batch(
+            host.vec_new_from_slice(&ks)?,
+            host.vec_new_from_slice(&xs)?,
+            host.vec_new_from_slice(&ss)?,
+        )
+        .map(|_| ())
+    };
+
+    assert!(batch(&vectors).is_ok());
+    assert!(batch(&vectors[1..2]).is_ok());
+
+    // A single incorrect payload fails the whole batch.
+    let mut invalid = vectors;
+    invalid[2].1 = "af83";
+    assert!(is_crypto_error(batch(&invalid).err().unwrap()));
+
+    // Empty batch.
+    assert!(is_crypto_error(batch(&[]).err().unwrap()));
+
+    // Mismatched lengths.
+    let k = host.bytes_new_from_slice(&hex::decode(vectors[0].0).unwrap())?;
+    let x = host.bytes_new_from_slice(&hex::decode(vectors[0].1).unwrap())?;
+    let s = host.bytes_new_from_slice(&hex::decode(vectors[0].2).unwrap())?;
+    assert!(is_crypto_error(
+        host.verify_sig_ed25519_batch(
+            host.vec_new_from_slice(&[k.to_val(), k.to_val()])?,
+            host.vec_new_from_slice(&[x.to_val()])?,
+            host.vec_new_from_slice(&[s.to_val()])?,
+        )
+        .err()
+        .unwrap()
+    ));
+
+    // Inputs must be bytes.
+    assert!(host
+        .verify_sig_ed25519_batch(
+            host.vec_new_from_slice(&[U32Val::from(1).to_val()])?,
+            host.vec_new_from_slice(&[x.to_val()])?,
+            host.vec_new_from_slice(&[s.to_val()])?,
+        )
+        .is_err());
+    Ok(())
+}
+
 #[test]
 fn recover_ecdsa_secp256k1_key_test() {
     let host = observe_host!(Host::test_host());
@@ -419,3 +596,362 @@ fn test_secp256r1_signature_verification() -> Result<(), HostError> {
 
     Ok(())
 }
+
+#[test]
+fn test_secp256k1_signature_verification() -> Result<(), HostError> {
+    use k256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey};
+
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+
+    let verify_sig = |public_key: &[u8],
+                      msg_digest: &[u8],
+                      signature: &[u8]|
+     -> Result<crate::Void, HostError> {
+        let public_key_obj = host.bytes_new_from_slice(public_key)?;
+        let msg_digest_obj = host.bytes_new_from_slice(msg_digest)?;
+        let signature_obj = host.bytes_new_from_slice(signature)?;
+        host.budget_ref().reset_default().unwrap();
+        host.verify_sig_ecdsa_secp256k1(public_key_obj, msg_digest_obj, signature_obj)
+    };
+
+    let signing_key = SigningKey::from_bytes(&[0x11; 32].into()).unwrap();
+    let public_key = signing_key.verifying_key().to_encoded_point(false);
+    let msg_digest =
+        hex::decode("ce0677bb30baa8cf067c88db9811f4333d131bf8bcf12fe7065d211dce971008").unwrap();
+    // `k256` always produces normalized (low-s) signatures.
+    let signature: Signature = signing_key.sign_prehash(&msg_digest).unwrap();
+
+    // 0. Valid
+    verify_sig(public_key.as_bytes(), &msg_digest, &signature.to_bytes())?;
+
+    // 1. Modified message digest
+    let mut modified_digest = msg_digest.clone();
+    modified_digest[0] ^= 1;
+    let res = verify_sig(
+        public_key.as_bytes(),
+        &modified_digest,
+        &signature.to_bytes(),
+    );
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+    ));
+    // Malformed message digest
+    let res = verify_sig(
+        public_key.as_bytes(),
+        &msg_digest[1..],
+        &signature.to_bytes(),
+    );
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Object, ScErrorCode::UnexpectedSize)
+    ));
+
+    // 2. Public key in compressed format
+    let compressed_key = signing_key.verifying_key().to_encoded_point(true);
+    let res = verify_sig(
+        compressed_key.as_bytes(),
+        &msg_digest,
+        &signature.to_bytes(),
+    );
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+    ));
+    // Public key of a different signer
+    let other_key = SigningKey::from_bytes(&[0x22; 32].into()).unwrap();
+    let res = verify_sig(
+        other_key.verifying_key().to_encoded_point(false).as_bytes(),
+        &msg_digest,
+        &signature.to_bytes(),
+    );
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+    ));
+
+    // 3. Mathematically valid, but not normalized (high-s) signature
+    let high_s_signature = Signature::from_scalars(signature.r(), -*signature.s()).unwrap();
+    let res = verify_sig(
+        public_key.as_bytes(),
+        &msg_digest,
+        &high_s_signature.to_bytes(),
+    );
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn recover_ecdsa_secp256r1_key_test() -> Result<(), HostError> {
+    use ecdsa::RecoveryId;
+    use p256::ecdsa::SigningKey;
+
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+
+    let recover_key =
+        |msg_digest: &[u8], signature: &[u8], recovery_id: u32| -> Result<Vec<u8>, HostError> {
+            let msg_digest_obj = host.bytes_new_from_slice(msg_digest)?;
+            let signature_obj = host.bytes_new_from_slice(signature)?;
+            host.budget_ref().reset_default().unwrap();
+            let pk_obj = host.recover_key_ecdsa_secp256r1(
+                msg_digest_obj,
+                signature_obj,
+                U32Val::from(recovery_id),
+            )?;
+            Ok(host
+                .fixed_length_bytes_from_bytesobj_input::<Vec<u8>, 65>("pk", pk_obj)?
+                .to_vec())
+        };
+
+    let msg_digest =
+        hex::decode("d1b8ef21eb4182ee270638061063a3f3c16c114e33937f69fb232cc833965a94").unwrap();
+    for key_byte in 1..=8_u8 {
+        let signing_key = SigningKey::from_bytes(&[key_byte; 32].into()).unwrap();
+        let public_key = signing_key.verifying_key().to_encoded_point(false);
+        let (mut signature, mut recovery_id) =
+            signing_key.sign_prehash_recoverable(&msg_digest).unwrap();
+        // Normalizing `s` negates the `R` point used for the recovery, so the
+        // y-parity bit of the recovery id has to be flipped accordingly.
+        if let Some(normalized) = signature.normalize_s() {
+            signature = normalized;
+            recovery_id = RecoveryId::new(!recovery_id.is_y_odd(), recovery_id.is_x_reduced());
+        }
+        let signature = signature.to_bytes();
+
+        // Successful recovery
+        assert_eq!(
+            recover_key(&msg_digest, &signature, recovery_id.to_byte() as u32)?,
+            public_key.as_bytes()
+        );
+        // Flipping the y-parity recovers a different key (or fails).
+        let flipped_id = recovery_id.to_byte() as u32 ^ 1;
+        if let Ok(recovered) = recover_key(&msg_digest, &signature, flipped_id) {
+            assert_ne!(recovered, public_key.as_bytes());
+        }
+        // Modified digest recovers a different key (or fails).
+        let mut modified_digest = msg_digest.clone();
+        modified_digest[31] ^= 1;
+        if let Ok(recovered) =
+            recover_key(&modified_digest, &signature, recovery_id.to_byte() as u32)
+        {
+            assert_ne!(recovered, public_key.as_bytes());
+        }
+        // Bad recovery ids
+        assert!(is_crypto_error(
+            recover_key(&msg_digest, &signature, 4).err().unwrap()
+        ));
+        assert!(is_crypto_error(
+            recover_key(&msg_digest, &signature, u32::MAX)
+                .err()
+                .unwrap()
+        ));
+        // Malformed digest
+        assert!(is_object_error(
+            recover_key(&msg_digest[1..], &signature, recovery_id.to_byte() as u32)
+                .err()
+                .unwrap()
+        ));
+        // Malformed signature
+        let mut long_signature = signature.to_vec();
+        long_signature.push(0);
+        assert!(is_crypto_error(
+            recover_key(&msg_digest, &long_signature, recovery_id.to_byte() as u32)
+                .err()
+                .unwrap()
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_schnorr_secp256k1_signature_verification() -> Result<(), HostError> {
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+
+    let verify_sig = |public_key: &str, msg: &str, signature: &str| -> Result<(), HostError> {
+        let public_key_obj = host.bytes_new_from_slice(&hex::decode(public_key).unwrap())?;
+        let msg_obj = host.bytes_new_from_slice(&hex::decode(msg).unwrap())?;
+        let signature_obj = host.bytes_new_from_slice(&hex::decode(signature).unwrap())?;
+        host.budget_ref().reset_default().unwrap();
+        host.verify_sig_schnorr_secp256k1(public_key_obj, msg_obj, signature_obj)?;
+        Ok(())
+    };
+
+    // Test vectors from BIP-340:
+    // https://github.com/bitcoin/bips/blob/master/bip-0340/test-vectors.csv
+    // (public key, message, signature, is valid)
+    let msg = "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89";
+    let pk = "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659";
+    let pk_short_msgs = "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117";
+    let long_msg = "99".repeat(100);
+    let vectors: [(&str, &str, &str, bool); 19] = [
+        (
+            "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0",
+            true,
+        ),
+        (
+            pk,
+            msg,
+            "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A",
+            true,
+        ),
+        (
+            "DD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8",
+            "7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C",
+            "5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1BAB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7",
+            true,
+        ),
+        (
+            "25D1DFF95105F5253C4022F628A996AD3A0D95FBF21D468A1B33F8C160D8F517",
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+            "7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3",
+            true,
+        ),
+        // Test fails if `msg` is reduced modulo `p` or `n`.
+        (
+            "D69C3509BB99E412E68B0FE8544E72837DFA30746D8BE2AA65975F29D22DC7B9",
+            "4DF3C3F68FCC83B27E9D42C90431A72499F17875C81A599B566C9889B9696703",
+            "00000000000000000000003B78CE563F89A0ED9414F5AA28AD0D96D6795F9C6376AFB1548AF603B3EB45C9F8207DEE1060CB71C04E80F593060B07D28308D7F4",
+            true,
+        ),
+        // Public key not on the curve.
+        (
+            "EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34",
+            msg,
+            "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            false,
+        ),
+        // `has_even_y(R)` is false.
+        (
+            pk,
+            msg,
+            "FFF97BD5755EEEA420453A14355235D382F6472F8568A18B2F057A14602975563CC27944640AC607CD107AE10923D9EF7A73C643E166BE5EBEAFA34B1AC553E2",
+            false,
+        ),
+        // Negated message.
+        (
+            pk,
+            msg,
+            "1FA62E331EDBC21C394792D2AB1100A7B432B013DF3F6FF4F99FCB33E0E1515F28890B3EDB6E7189B630448B515CE4F8622A954CFE545735AAEA5134FCCDB2BD",
+            false,
+        ),
+        // Negated `s` value.
+        (
+            pk,
+            msg,
+            "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769961764B3AA9B2FFCB6EF947B6887A226E8D7C93E00C5ED0C1834FF0D0C2E6DA6",
+            false,
+        ),
+        // `sG - eP` is infinite (with `x(inf) = 0`).
+        (
+            pk,
+            msg,
+            "0000000000000000000000000000000000000000000000000000000000000000123DDA8328AF9C23A94C1FEECFD123BA4FB73476F0D594DCB65C6425BD186051",
+            false,
+        ),
+        // `sG - eP` is infinite (with `x(inf) = 1`).
+        (
+            pk,
+            msg,
+            "00000000000000000000000000000000000000000000000000000000000000017615FBAF5AE28864013C099742DEADB4DBA87F11AC6754F93780D5A1837CF197",
+            false,
+        ),
+        // `sig[0:32]` is not an X coordinate on the curve.
+        (
+            pk,
+            msg,
+            "4A298DACAE57395A15D0795DDBFD1DCB564DA82B0F269BC70A74F8220429BA1D69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            false,
+        ),
+        // `sig[0:32]` is equal to the field size.
+        (
+            pk,
+            msg,
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            false,
+        ),
+        // `sig[32:64]` is equal to the curve order.
+        (
+            pk,
+            msg,
+            "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+            false,
+        ),
+        // Public key is not a valid X coordinate because it exceeds the field size.
+        (
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30",
+            msg,
+            "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            false,
+        ),
+        // Messages of size 0, 1, 17 and 100 bytes.
+        (
+            pk_short_msgs,
+            "",
+            "71535DB165ECD9FBBC046E5FFAEA61186BB6AD436732FCCC25291A55895464CF6069CE26BF03466228F19A3A62DB8A649F2D560FAC652827D1AF0574E427AB63",
+            true,
+        ),
+        (
+            pk_short_msgs,
+            "11",
+            "08A20A0AFEF64124649232E0693C583AB1B9934AE63B4C3511F3AE1134C6A303EA3173BFEA6683BD101FA5AA5DBC1996FE7CACFC5A577D33EC14564CEC2BACBF",
+            true,
+        ),
+        (
+            pk_short_msgs,
+            "0102030405060708090A0B0C0D0E0F1011",
+            "5130F39A4059B43BC7CAC09A19ECE52B5D8699D1A71E3C52DA9AFDB6B50AC370C4A482B77BF960F8681540E25B6771ECE1E5A37FD80E5A51897C5566A97EA5A5",
+            true,
+        ),
+        (
+            pk_short_msgs,
+            &long_msg,
+            "403B12B0D8555A344175EA7EC746566303321E5DBFA8BE6F091635163ECA79A8585ED3E3170807E7C03B720FC54C7B23897FCBA0E9D0B4A06894CFD249F22367",
+            true,
+        ),
+    ];
+    for (i, &(public_key, msg, signature, valid)) in vectors.iter().enumerate() {
+        let res = verify_sig(public_key, msg, signature);
+        if valid {
+            assert!(res.is_ok(), "vector {i} failed verification");
+        } else {
+            assert!(
+                HostError::result_matches_err(
+                    res,
+                    (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+                ),
+                "vector {i} passed verification"
+            );
+        }
+    }
+
+    // Malformed public key and signature lengths.
+    let (public_key, msg, signature, _) = vectors[1];
+    assert!(HostError::result_matches_err(
+        verify_sig(&public_key[2..], msg, signature),
+        (ScErrorType::Object, ScErrorCode::UnexpectedSize)
+    ));
+    assert!(HostError::result_matches_err(
+        verify_sig(&format!("02{public_key}"), msg, signature),
+        (ScErrorType::Object, ScErrorCode::UnexpectedSize)
+    ));
+    assert!(HostError::result_matches_err(
+        verify_sig(public_key, msg, &signature[2..]),
+        (ScErrorType::Object, ScErrorCode::UnexpectedSize)
+    ));
+    Ok(())
+}
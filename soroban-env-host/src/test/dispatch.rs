@@ -2,6 +2,7 @@ use soroban_synth_wasm::{Arity, LocalRef, ModEmitter, Operand};
 
 use crate::{
     budget::AsBudget,
+    host::{TraceEvent, TraceHook},
     host_object::{HostMap, HostVec, MuxedScAddress},
     xdr::{
         ContractId, Duration, Hash, MuxedEd25519Account, ScAddress, ScBytes, ScErrorCode,
@@ -10,12 +11,13 @@ use crate::{
     AddressObject, Bool, BytesObject, DurationObject, DurationSmall, DurationVal, Env, Error, Host,
     HostError, I128Object, I128Small, I128Val, I256Object, I256Small, I256Val, I32Val, I64Object,
     I64Small, MapObject, MuxedAddressObject, StorageType, StringObject, Symbol, SymbolObject,
-    SymbolSmall, TimepointObject, TimepointSmall, TimepointVal, U128Object, U128Small, U128Val,
-    U256Object, U256Small, U256Val, U32Val, U64Object, U64Small, U64Val, Val, VecObject, Void,
-    I256, U256,
+    SymbolSmall, TimepointObject, TimepointSmall, TimepointVal, TryFromVal, U128Object, U128Small,
+    U128Val, U256Object, U256Small, U256Val, U32Val, U64Object, U64Small, U64Val, Val, VecObject,
+    Void, I256, U256,
 };
 
 use soroban_env_macros::generate_synth_dispatch_host_fn_tests;
+use std::{cell::RefCell, rc::Rc};
 
 trait TestVal {
     fn test_val() -> Val;
@@ -338,3 +340,44 @@ impl TestObject for MuxedAddressObject {
 }
 
 generate_synth_dispatch_host_fn_tests!("../soroban-env-common/env.json");
+
+#[test]
+fn dispatch_traces_all_args_of_six_arg_host_fn() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.as_budget().reset_unlimited()?;
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    let traced_args: Rc<RefCell<Vec<String>>> = Default::default();
+    let hook_args = traced_args.clone();
+    let hook: TraceHook = Rc::new(move |_, evt| {
+        if let TraceEvent::EnvCall("verify_merkle_proof", args) = evt {
+            *hook_args.borrow_mut() = args.iter().map(|a| format!("{a:?}")).collect();
+        }
+        Ok(())
+    });
+    host.set_trace_hook(Some(hook))?;
+    let contract_id_obj =
+        host.register_test_contract_wasm(wasm_module_calling_verify_merkle_proof().as_slice());
+    // A single-node tree: the leaf is the root and the proof is empty.
+    let leaf = BytesObject::test_object(&host);
+    let args = HostVec::from_vec(vec![
+        leaf.to_val(),
+        host.vec_new()?.to_val(),
+        U64Val::try_from_val(&host, &0_u64)?.to_val(),
+        leaf.to_val(),
+        Symbol::try_from_small_str("sha256")?.to_val(),
+        Bool::from(false).to_val(),
+    ])?;
+    let args = host.add_host_object(args)?;
+    let res = host.call(contract_id_obj, Symbol::try_from_small_str("test")?, args)?;
+    host.set_trace_hook(None)?;
+    assert_eq!(
+        res.get_payload(),
+        Symbol::try_from_small_str("pass")?.to_val().get_payload()
+    );
+    let traced_args = traced_args.borrow();
+    assert_eq!(traced_args.len(), 6);
+    assert!(traced_args.iter().all(|a| !a.starts_with("bad:")));
+    Ok(())
+}
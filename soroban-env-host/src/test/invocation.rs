@@ -3,14 +3,16 @@ use std::{cmp::Ordering, rc::Rc};
 use expect_test::expect;
 use soroban_env_common::{
     xdr::{self, ContractCostType, ScError, ScErrorCode},
-    Compare, Env, EnvBase, TryFromVal, TryIntoVal, Val,
+    AddressObject, Compare, Env, EnvBase, StorageType, SymbolStr, TryFromVal, TryIntoVal, U64Val,
+    Val,
 };
 
 use crate::{
-    budget::AsBudget, events::HostEvent, test::observe::ObservedHost, xdr::ScErrorType,
-    ContractFunctionSet, Error, Host, HostError, Symbol, Tag,
+    budget::AsBudget, events::HostEvent, host::metered_xdr::metered_write_xdr,
+    test::observe::ObservedHost, xdr::ScErrorType, BuiltinContract, ContractFunctionSet, Error,
+    ErrorHandler, Host, HostError, Symbol, Tag, DEFAULT_XDR_RW_LIMITS,
 };
-use soroban_test_wasms::{ADD_I32, ALLOC, ERR, INVOKE_CONTRACT, VEC};
+use soroban_test_wasms::{ADD_I32, ALLOC, ERR, FIB, INVOKE_CONTRACT, VEC};
 
 #[test]
 fn invoke_single_contract_function() -> Result<(), HostError> {
@@ -316,6 +318,136 @@ fn native_invoke_return_err_variants() -> Result<(), HostError> {
     Ok(())
 }
 
+struct MockOracle;
+impl BuiltinContract for MockOracle {
+    fn call(&self, func: &Symbol, host: &Host, args: &[Val]) -> Result<Val, HostError> {
+        let price_key = Symbol::try_from_small_str("price")?;
+        match SymbolStr::try_from_val(host, func)?.as_ref() {
+            "set_price" => {
+                let admin: AddressObject = args[0].try_into()?;
+                host.require_auth(admin)?;
+                host.put_contract_data(price_key.into(), args[1], StorageType::Instance)?;
+                Ok(Val::VOID.into())
+            }
+            "price" => host.get_contract_data(price_key.into(), StorageType::Instance),
+            _ => Err(host.err(
+                ScErrorType::Context,
+                ScErrorCode::MissingValue,
+                "function does not exist",
+                &[func.to_val()],
+            )),
+        }
+    }
+}
+
+#[test]
+fn native_builtin_contract_invocation() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let addr = host.add_host_object(xdr::ScAddress::Contract(xdr::ContractId(xdr::Hash(
+        [1; 32],
+    ))))?;
+    // The missing constructor is treated as no-op.
+    host.register_builtin_test_contract(addr, Rc::new(MockOracle))?;
+
+    let admin = host.add_host_object(xdr::ScAddress::Account(xdr::AccountId(
+        xdr::PublicKey::PublicKeyTypeEd25519(xdr::Uint256([2; 32])),
+    )))?;
+    host.switch_to_recording_auth(true)?;
+    host.call(
+        addr,
+        Symbol::try_from_small_str("set_price")?,
+        host.vec_new_from_slice(&[admin.into(), 1234_u32.into()])?,
+    )?;
+    // The auth is required via the regular authorization machinery.
+    let recorded_auth = host.get_recorded_auth_payloads()?;
+    assert_eq!(recorded_auth.len(), 1);
+    assert_eq!(
+        recorded_auth[0].address,
+        Some(host.scaddress_from_address(admin)?)
+    );
+
+    let price = host.call(addr, Symbol::try_from_small_str("price")?, host.vec_new()?)?;
+    assert_eq!(u32::try_from_val(&host, &price)?, 1234);
+
+    // Errors are propagated to the caller.
+    let res = host.call(
+        addr,
+        Symbol::try_from_small_str("set_price")?,
+        host.vec_new_from_slice(&[1_u32.into(), 1_u32.into()])?,
+    );
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Value, ScErrorCode::UnexpectedType)
+    ));
+    let res = host.call(addr, Symbol::try_from_small_str("foo")?, host.vec_new()?);
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Context, ScErrorCode::MissingValue)
+    ));
+    Ok(())
+}
+
+struct FailingContract;
+impl BuiltinContract for FailingContract {
+    fn call(&self, func: &Symbol, host: &Host, _args: &[Val]) -> Result<Val, HostError> {
+        match SymbolStr::try_from_val(host, func)?.as_ref() {
+            "contract" => Err(host.error(Error::from_contract_error(7), "contract error", &[])),
+            "budget" => Err(host.err(
+                ScErrorType::Budget,
+                ScErrorCode::ExceededLimit,
+                "out of budget",
+                &[],
+            )),
+            "internal" => Err(host.err(
+                ScErrorType::Context,
+                ScErrorCode::InternalError,
+                "internal error",
+                &[],
+            )),
+            _ => Err(host.err(
+                ScErrorType::Context,
+                ScErrorCode::MissingValue,
+                "function does not exist",
+                &[func.to_val()],
+            )),
+        }
+    }
+}
+
+#[test]
+fn native_builtin_contract_errors_are_propagated_as_is() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let addr = host.add_host_object(xdr::ScAddress::Contract(xdr::ContractId(xdr::Hash(
+        [1; 32],
+    ))))?;
+    host.register_builtin_test_contract(addr, Rc::new(FailingContract))?;
+
+    // Contract errors are recoverable via `try_call`, same as for Wasm.
+    let res = host.try_call(
+        addr,
+        Symbol::try_from_small_str("contract")?,
+        host.vec_new()?,
+    )?;
+    assert_eq!(Error::try_from(res)?, Error::from_contract_error(7));
+
+    // Budget and internal errors are not recoverable and keep their codes.
+    let res = host.try_call(addr, Symbol::try_from_small_str("budget")?, host.vec_new()?);
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Budget, ScErrorCode::ExceededLimit)
+    ));
+    let res = host.try_call(
+        addr,
+        Symbol::try_from_small_str("internal")?,
+        host.vec_new()?,
+    );
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Context, ScErrorCode::InternalError)
+    ));
+    Ok(())
+}
+
 #[test]
 fn wasm_invoke_return_err_variants() -> Result<(), HostError> {
     // Here we test several variants of returning-a-Val-that-is-an-Error
@@ -491,3 +623,275 @@ fn guest_error() -> Result<(), HostError> {
     );
     Ok(())
 }
+
+#[test]
+fn try_call_with_budget_within_sub_budget() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    let contract_id_obj = host.register_test_contract_wasm(ADD_I32);
+    let res = host.try_call_with_budget(
+        contract_id_obj,
+        Symbol::try_from_small_str("add")?,
+        host.test_vec_obj(&[4i32, 7i32])?,
+        U64Val::try_from_val(&host, &10_000_000_u64)?,
+        U64Val::try_from_val(&host, &10_000_000_u64)?,
+    )?;
+    assert_eq!(i32::try_from_val(&host, &res)?, 11);
+    Ok(())
+}
+
+#[test]
+fn try_call_with_budget_recovers_from_sub_budget_exhaustion() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    let fib_id_obj = host.register_test_contract_wasm(FIB);
+    let add_id_obj = host.register_test_contract_wasm(ADD_I32);
+    let cpu_limit =
+        host.as_budget().get_cpu_insns_remaining()? + host.as_budget().get_cpu_insns_consumed()?;
+
+    let res = host.try_call_with_budget(
+        fib_id_obj,
+        Symbol::try_from_small_str("main")?,
+        host.vec_new()?,
+        U64Val::try_from_val(&host, &1_000_000_u64)?,
+        U64Val::try_from_val(&host, &u64::MAX)?,
+    )?;
+    let budget_err: Error = (ScErrorType::Budget, ScErrorCode::ExceededLimit).into();
+    assert_eq!(res.get_payload(), budget_err.to_val().get_payload());
+
+    // The parent limits are restored once the call returns.
+    assert_eq!(
+        host.as_budget().get_cpu_insns_remaining()? + host.as_budget().get_cpu_insns_consumed()?,
+        cpu_limit
+    );
+    // And the caller is free to carry on.
+    let res = host.call(
+        add_id_obj,
+        Symbol::try_from_small_str("add")?,
+        host.test_vec_obj(&[4i32, 7i32])?,
+    )?;
+    assert_eq!(i32::try_from_val(&host, &res)?, 11);
+    Ok(())
+}
+
+#[test]
+fn try_call_with_budget_nested_in_sub_budget() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    let fib_id_obj = host.register_test_contract_wasm(FIB);
+    let main = Symbol::try_from_small_str("main")?;
+
+    // The inner sub-budget is bounded by the outer one, so exhausting it
+    // exhausts the outer sub-budget too, which can't be recovered from
+    // within it.
+    let (outer_res, outer_crossed) =
+        host.as_budget().with_sub_limits(1_000_000, u64::MAX, || {
+            host.try_call_with_budget(
+                fib_id_obj,
+                main,
+                host.vec_new()?,
+                U64Val::try_from_val(&host, &u64::MAX)?,
+                U64Val::try_from_val(&host, &u64::MAX)?,
+            )
+        })?;
+    assert!(outer_crossed);
+    assert!(HostError::result_matches_err(
+        outer_res,
+        (ScErrorType::Budget, ScErrorCode::ExceededLimit)
+    ));
+    assert!(host.as_budget().is_within_limits()?);
+    Ok(())
+}
+
+#[test]
+fn try_call_with_budget_inner_cap_is_recoverable_within_sub_budget() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    let fib_id_obj = host.register_test_contract_wasm(FIB);
+    let main = Symbol::try_from_small_str("main")?;
+
+    // The inner sub-budget is smaller than the outer one, so only the inner
+    // one is exhausted and the call can be recovered from within the outer
+    // sub-budget.
+    let (res, outer_crossed) = host.as_budget().with_sub_limits(10_000_000, u64::MAX, || {
+        let res = host.try_call_with_budget(
+            fib_id_obj,
+            main,
+            host.vec_new()?,
+            U64Val::try_from_val(&host, &1_000_000_u64)?,
+            U64Val::try_from_val(&host, &u64::MAX)?,
+        )?;
+        assert!(host.as_budget().is_within_limits()?);
+        Ok(res)
+    })?;
+    assert!(!outer_crossed);
+    let res = res?;
+    let budget_err: Error = (ScErrorType::Budget, ScErrorCode::ExceededLimit).into();
+    assert_eq!(res.get_payload(), budget_err.to_val().get_payload());
+    assert!(host.as_budget().is_within_limits()?);
+    Ok(())
+}
+
+struct BudgetBurner;
+impl BuiltinContract for BudgetBurner {
+    fn call(&self, func: &Symbol, host: &Host, _args: &[Val]) -> Result<Val, HostError> {
+        let key = Symbol::try_from_small_str("key")?;
+        match SymbolStr::try_from_val(host, func)?.as_ref() {
+            "burn" => {
+                host.put_contract_data(key.into(), 1_u32.into(), StorageType::Persistent)?;
+                host.contract_event(host.vec_new()?, key.into())?;
+                loop {
+                    host.as_budget()
+                        .charge(ContractCostType::ComputeSha256Hash, Some(1024))?;
+                }
+            }
+            "has" => Ok(host
+                .has_contract_data(key.into(), StorageType::Persistent)?
+                .into()),
+            "deep_xdr" => {
+                // Exceeds the XDR depth limit, which is reported as budget
+                // exhaustion without consuming the budget.
+                let mut scv = xdr::ScVal::Void;
+                for _ in 0..=DEFAULT_XDR_RW_LIMITS.depth {
+                    scv = xdr::ScVal::Vec(Some(xdr::ScVec(vec![scv].try_into()?)));
+                }
+                metered_write_xdr(host.as_budget(), &scv, &mut vec![])?;
+                Ok(Val::VOID.into())
+            }
+            _ => Err(host.err(
+                ScErrorType::Context,
+                ScErrorCode::MissingValue,
+                "function does not exist",
+                &[func.to_val()],
+            )),
+        }
+    }
+}
+
+#[test]
+fn try_call_with_budget_rolls_back_on_sub_budget_exhaustion() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    let addr = host.add_host_object(xdr::ScAddress::Contract(xdr::ContractId(xdr::Hash(
+        [1; 32],
+    ))))?;
+    host.register_builtin_test_contract(addr, Rc::new(BudgetBurner))?;
+
+    let res = host.try_call_with_budget(
+        addr,
+        Symbol::try_from_small_str("burn")?,
+        host.vec_new()?,
+        U64Val::try_from_val(&host, &1_000_000_u64)?,
+        U64Val::try_from_val(&host, &u64::MAX)?,
+    )?;
+    let budget_err: Error = (ScErrorType::Budget, ScErrorCode::ExceededLimit).into();
+    assert_eq!(res.get_payload(), budget_err.to_val().get_payload());
+
+    // The storage write of the callee has been rolled back...
+    let has = host.call(addr, Symbol::try_from_small_str("has")?, host.vec_new()?)?;
+    assert!(!bool::try_from_val(&host, &has)?);
+    // ...and its event is marked as emitted by a failed call.
+    let contract_events: Vec<HostEvent> = host
+        .get_events()?
+        .0
+        .into_iter()
+        .filter(|e| e.event.type_ == xdr::ContractEventType::Contract)
+        .collect();
+    assert_eq!(contract_events.len(), 1);
+    assert!(contract_events[0].failed_call);
+    Ok(())
+}
+
+#[test]
+fn try_call_with_budget_xdr_failure_is_not_recoverable() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    let addr = host.add_host_object(xdr::ScAddress::Contract(xdr::ContractId(xdr::Hash(
+        [1; 32],
+    ))))?;
+    host.register_builtin_test_contract(addr, Rc::new(BudgetBurner))?;
+
+    // The callee fails with the same error as sub-budget exhaustion, but
+    // stays well within its cap, so the failure isn't recovered from.
+    let res = host.try_call_with_budget(
+        addr,
+        Symbol::try_from_small_str("deep_xdr")?,
+        host.vec_new()?,
+        U64Val::try_from_val(&host, &10_000_000_u64)?,
+        U64Val::try_from_val(&host, &u64::MAX)?,
+    );
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Budget, ScErrorCode::ExceededLimit)
+    ));
+    assert!(host.as_budget().is_within_limits()?);
+    Ok(())
+}
+
+#[test]
+fn try_call_with_budget_from_wasm_refills_fuel_from_restored_limit() -> Result<(), HostError> {
+    use crate::testutils::wasm::wasm_module_calling_try_call_with_budget_then_looping;
+
+    let host = Host::test_host_with_recording_footprint();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    let fib_id_obj = host.register_test_contract_wasm(FIB);
+    // The caller loops for more fuel than the sub-budget of the callee
+    // provides after the call returns, which only succeeds if its fuel is
+    // refilled from the restored limits.
+    let caller_id_obj =
+        host.register_test_contract_wasm(&wasm_module_calling_try_call_with_budget_then_looping(
+            U64Val::try_from_val(&host, &1_000_000_u64)?.to_val(),
+            U64Val::try_from_val(&host, &100_000_000_u64)?.to_val(),
+            100_000,
+            Error::from_type_and_code(ScErrorType::Budget, ScErrorCode::ExceededLimit).to_val(),
+        ));
+
+    let res = host.call(
+        caller_id_obj,
+        Symbol::try_from_small_str("test")?,
+        host.vec_new_from_slice(&[fib_id_obj.into(), host.vec_new()?.into()])?,
+    )?;
+    assert!(bool::try_from_val(&host, &res)?);
+    assert!(host.as_budget().is_within_limits()?);
+    Ok(())
+}
+
+#[test]
+fn try_call_with_budget_parent_exhaustion_is_unrecoverable() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    let contract_id_obj = host.register_test_contract_wasm(FIB);
+    host.as_budget().reset_limits(2_000_000, 100_000_000)?;
+
+    // The sub-budget is capped by what remains of the parent budget, so
+    // running out of it exhausts the parent budget as well.
+    let res = host.try_call_with_budget(
+        contract_id_obj,
+        Symbol::try_from_small_str("main")?,
+        host.vec_new()?,
+        U64Val::try_from_val(&host, &u64::MAX)?,
+        U64Val::try_from_val(&host, &u64::MAX)?,
+    );
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Budget, ScErrorCode::ExceededLimit)
+    ));
+    assert!(!host.as_budget().is_within_limits()?);
+    Ok(())
+}
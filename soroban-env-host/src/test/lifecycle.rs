@@ -1894,10 +1894,19 @@ mod cap_58_constructor {
                                             temporary_entry_rent_bumps: 0,
                                         },
                                         sub_call_resources: [],
+                                        wasm_profile: [],
+                                        wasm_profile_hook_fuel: 0,
+                                        ledger_entries: [],
                                     },
                                 ],
+                                wasm_profile: [],
+                                wasm_profile_hook_fuel: 0,
+                                ledger_entries: [],
                             },
                         ],
+                        wasm_profile: [],
+                        wasm_profile_hook_fuel: 0,
+                        ledger_entries: [],
                     }"#]]
                 .assert_eq(
                     format!(
@@ -2052,8 +2061,14 @@ mod cap_58_constructor {
                                             temporary_entry_rent_bumps: 0,
                                         },
                                         sub_call_resources: [],
+                                        wasm_profile: [],
+                                        wasm_profile_hook_fuel: 0,
+                                        ledger_entries: [],
                                     },
                                 ],
+                                wasm_profile: [],
+                                wasm_profile_hook_fuel: 0,
+                                ledger_entries: [],
                             },
                             DetailedInvocationResources {
                                 invocation: InvokeContract(
@@ -2081,8 +2096,14 @@ mod cap_58_constructor {
                                     temporary_entry_rent_bumps: 0,
                                 },
                                 sub_call_resources: [],
+                                wasm_profile: [],
+                                wasm_profile_hook_fuel: 0,
+                                ledger_entries: [],
                             },
                         ],
+                        wasm_profile: [],
+                        wasm_profile_hook_fuel: 0,
+                        ledger_entries: [],
                     }"#]]
                 .assert_eq(
                     format!(
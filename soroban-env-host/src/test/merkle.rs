@@ -0,0 +1,240 @@
+use crate::{
+    xdr::{ScErrorCode, ScErrorType},
+    Env, EnvBase, Host, HostError, Symbol, TryFromVal, U64Val, Val, VecObject,
+};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+fn hash_pair(keccak: bool, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut buf = left.to_vec();
+    buf.extend_from_slice(right);
+    if keccak {
+        Keccak256::digest(&buf).into()
+    } else {
+        Sha256::digest(&buf).into()
+    }
+}
+
+// Builds all the levels of a tree with a power of two number of leaves.
+fn build_tree(keccak: bool, sorted: bool, leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| {
+                if sorted && pair[1] < pair[0] {
+                    hash_pair(keccak, &pair[1], &pair[0])
+                } else {
+                    hash_pair(keccak, &pair[0], &pair[1])
+                }
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+fn proof_for(levels: &[Vec<[u8; 32]>], index: usize) -> Vec<[u8; 32]> {
+    levels[..levels.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(level, nodes)| nodes[(index >> level) ^ 1])
+        .collect()
+}
+
+struct MerkleTest {
+    host: Host,
+    hash: Symbol,
+    sorted: bool,
+}
+
+impl MerkleTest {
+    fn new(hash: &str, sorted: bool) -> Self {
+        let host = Host::test_host();
+        let hash = Symbol::try_from_val(&host, &hash).unwrap();
+        Self { host, hash, sorted }
+    }
+
+    fn proof_vec(&self, proof: &[impl AsRef<[u8]>]) -> Result<VecObject, HostError> {
+        let nodes = proof
+            .iter()
+            .map(|n| Ok(self.host.bytes_new_from_slice(n.as_ref())?.to_val()))
+            .collect::<Result<Vec<Val>, HostError>>()?;
+        self.host.vec_new_from_slice(&nodes)
+    }
+
+    fn verify(
+        &self,
+        leaf: &[u8],
+        proof: &[impl AsRef<[u8]>],
+        index: u64,
+        root: &[u8],
+    ) -> Result<bool, HostError> {
+        let host = &self.host;
+        let leaf = host.bytes_new_from_slice(leaf)?;
+        let proof = self.proof_vec(proof)?;
+        let index = U64Val::try_from_val(host, &index)?;
+        let root = host.bytes_new_from_slice(root)?;
+        host.budget_ref().reset_default()?;
+        Ok(host
+            .verify_merkle_proof(leaf, proof, index, root, self.hash, self.sorted.into())?
+            .into())
+    }
+}
+
+fn leaves(n: u8) -> Vec<[u8; 32]> {
+    (0..n).map(|i| Sha256::digest([i]).into()).collect()
+}
+
+#[test]
+fn test_merkle_proofs() -> Result<(), HostError> {
+    if Host::current_test_protocol() < 25 {
+        return Ok(());
+    }
+    for (hash, keccak) in [("sha256", false), ("keccak256", true)] {
+        for sorted in [false, true] {
+            let t = MerkleTest::new(hash, sorted);
+            let levels = build_tree(keccak, sorted, leaves(16));
+            let root = levels.last().unwrap()[0];
+            for (index, leaf) in levels[0].iter().enumerate() {
+                let proof = proof_for(&levels, index);
+                assert!(t.verify(leaf, &proof, index as u64, &root)?);
+
+                // Tampered sibling.
+                let mut bad_proof = proof.clone();
+                bad_proof[2][0] ^= 1;
+                assert!(!t.verify(leaf, &bad_proof, index as u64, &root)?);
+                // Wrong leaf.
+                let other_leaf = levels[0][index ^ 1];
+                assert!(!t.verify(&other_leaf, &proof, index as u64, &root)?);
+                // Truncated proof.
+                assert!(!t.verify(leaf, &proof[..3], index as u64 & 7, &root)?);
+            }
+            // Single-node tree: the leaf is the root.
+            let empty: [[u8; 32]; 0] = [];
+            assert!(t.verify(&root, &empty, 0, &root)?);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_merkle_proof_index_handling() -> Result<(), HostError> {
+    if Host::current_test_protocol() < 25 {
+        return Ok(());
+    }
+    let levels = build_tree(false, false, leaves(8));
+    let root = levels.last().unwrap()[0];
+    let proof = proof_for(&levels, 5);
+
+    // Positional proofs depend on the index.
+    let t = MerkleTest::new("sha256", false);
+    assert!(t.verify(&levels[0][5], &proof, 5, &root)?);
+    assert!(!t.verify(&levels[0][5], &proof, 4, &root)?);
+    // The index must fit into the proof depth.
+    assert!(HostError::result_matches_err(
+        t.verify(&levels[0][5], &proof, 5 + 8, &root),
+        (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+    ));
+    assert!(HostError::result_matches_err(
+        t.verify(&levels[0][5], &proof, u64::MAX, &root),
+        (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+    ));
+    // A 64-level proof can use the whole index range.
+    let deep_proof = vec![[7u8; 32]; 64];
+    let mut node = [1u8; 32];
+    for sibling in deep_proof.iter() {
+        node = hash_pair(false, sibling, &node);
+    }
+    assert!(t.verify(&[1u8; 32], &deep_proof, u64::MAX, &node)?);
+    let too_deep_proof = vec![[7u8; 32]; 65];
+    assert!(HostError::result_matches_err(
+        t.verify(&[1u8; 32], &too_deep_proof, 0, &node),
+        (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+    ));
+
+    // Sorted-pair proofs ignore the index.
+    let levels = build_tree(false, true, leaves(8));
+    let root = levels.last().unwrap()[0];
+    let proof = proof_for(&levels, 5);
+    let t = MerkleTest::new("sha256", true);
+    assert!(t.verify(&levels[0][5], &proof, 0, &root)?);
+    assert!(t.verify(&levels[0][5], &proof, u64::MAX, &root)?);
+    Ok(())
+}
+
+#[test]
+fn test_merkle_proof_invalid_inputs() -> Result<(), HostError> {
+    if Host::current_test_protocol() < 25 {
+        return Ok(());
+    }
+    let levels = build_tree(true, false, leaves(4));
+    let root = levels.last().unwrap()[0];
+    let leaf = levels[0][1];
+    let proof = proof_for(&levels, 1);
+
+    let t = MerkleTest::new("sha512", false);
+    assert!(HostError::result_matches_err(
+        t.verify(&leaf, &proof, 1, &root),
+        (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+    ));
+
+    let t = MerkleTest::new("keccak256", false);
+    assert!(t.verify(&leaf, &proof, 1, &root)?);
+    // Nodes must be 32 bytes long.
+    assert!(HostError::result_matches_err(
+        t.verify(&leaf[1..], &proof, 1, &root),
+        (ScErrorType::Object, ScErrorCode::UnexpectedSize)
+    ));
+    assert!(HostError::result_matches_err(
+        t.verify(&leaf, &proof, 1, &root[1..]),
+        (ScErrorType::Object, ScErrorCode::UnexpectedSize)
+    ));
+    let short_sibling: Vec<Vec<u8>> = vec![proof[0].to_vec(), proof[1][..31].to_vec()];
+    assert!(HostError::result_matches_err(
+        t.verify(&leaf, &short_sibling, 1, &root),
+        (ScErrorType::Object, ScErrorCode::UnexpectedSize)
+    ));
+    // Proof elements must be `Bytes`.
+    let host = &t.host;
+    let proof_vec = host.vec_new_from_slice(&[
+        host.bytes_new_from_slice(&proof[0])?.to_val(),
+        U64Val::try_from_val(host, &1_u64)?.to_val(),
+    ])?;
+    assert!(HostError::result_matches_err(
+        host.verify_merkle_proof(
+            host.bytes_new_from_slice(&leaf)?,
+            proof_vec,
+            U64Val::try_from_val(host, &1_u64)?,
+            host.bytes_new_from_slice(&root)?,
+            t.hash,
+            false.into(),
+        ),
+        (ScErrorType::Value, ScErrorCode::UnexpectedType)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_merkle_proof_metering_is_linear_in_depth() -> Result<(), HostError> {
+    if Host::current_test_protocol() < 25 {
+        return Ok(());
+    }
+    let t = MerkleTest::new("sha256", false);
+    let cpu_for_depth = |depth: usize| -> Result<u64, HostError> {
+        let proof = vec![[3u8; 32]; depth];
+        t.verify(&[1u8; 32], &proof, 0, &[0u8; 32])?;
+        t.host.budget_ref().get_cpu_insns_consumed()
+    };
+    let d8 = cpu_for_depth(8)?;
+    let d16 = cpu_for_depth(16)?;
+    let d32 = cpu_for_depth(32)?;
+    assert!(d8 < d16 && d16 < d32);
+    // Equal depth increments cost the same, up to the rounding of the
+    // linear cost model terms.
+    let (per_8_levels, per_16_levels) = (d16 - d8, d32 - d16);
+    assert!((per_16_levels / 2).abs_diff(per_8_levels) <= per_8_levels / 100);
+    Ok(())
+}
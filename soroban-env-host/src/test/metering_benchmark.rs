@@ -4,10 +4,12 @@ use crate::{
     storage::{Footprint, Storage},
     Host, HostError, LedgerInfo, MeteredOrdMap,
 };
-use soroban_env_common::{Env, Symbol};
-use soroban_test_wasms::{ADD_I32, COMPLEX};
+use soroban_env_common::{Env, Symbol, TryFromVal};
+use soroban_test_wasms::{ADD_I32, COMPLEX, FANNKUCH, LOADGEN};
 
+use super::cpu_audit::audited_call;
 use crate::testutils::{generate_account_id, generate_bytes_array};
+use crate::VecObject;
 
 // The follow tests enables resource (cpu and mem) trackers, their main purpose is to evaluate
 // metering accuracy by comparing modeled resource usage from the budget vs actual resource usage.
@@ -138,3 +140,54 @@ fn run_complex() -> Result<(), HostError> {
     std::thread::sleep(std::time::Duration::from_secs(2));
     Ok(())
 }
+
+// The audit tests below attribute the actually executed cpu instructions to
+// the cost types charged during an invocation (see `cpu_audit::audited_call`) and
+// list the cost types that are charged less than they cost. They have the same
+// constraints as the tests above:
+//
+// RUST_TEST_THREADS=1  cargo test --release --package soroban-env-host --lib --features testutils -- test::metering_benchmark::audit  --nocapture --ignored
+
+// Cost types whose measured instructions exceed the charged ones by more than
+// this ratio are reported as under-charged.
+const AUDIT_UNDER_CHARGED_RATIO: f64 = 1.2;
+
+fn audit_contract_call(
+    wasm: &[u8],
+    func: &str,
+    args: impl FnOnce(&Host) -> Result<VecObject, HostError>,
+) -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.set_ledger_info(LEDGER_INFO)?;
+    let contract_id_obj = host.register_test_contract_wasm(wasm);
+    let args = args(&host)?;
+    let (res, audit) = audited_call(
+        &host,
+        contract_id_obj,
+        Symbol::try_from_val(&host, &func)?,
+        args,
+    )?;
+    res?;
+    audit.report(AUDIT_UNDER_CHARGED_RATIO);
+    Ok(())
+}
+
+#[ignore]
+#[test]
+fn audit_loadgen() -> Result<(), HostError> {
+    audit_contract_call(LOADGEN, "do_cpu_only_work", |host| {
+        host.test_vec_obj::<u32>(&[1_000_000, 1_000, 100])
+    })
+}
+
+#[ignore]
+#[test]
+fn audit_complex() -> Result<(), HostError> {
+    audit_contract_call(COMPLEX, "go", |host| host.test_vec_obj::<u32>(&[]))
+}
+
+#[ignore]
+#[test]
+fn audit_fannkuch() -> Result<(), HostError> {
+    audit_contract_call(FANNKUCH, "main", |host| host.test_vec_obj::<u32>(&[]))
+}
@@ -0,0 +1,442 @@
+use crate::{
+    xdr::{ScErrorCode, ScErrorType},
+    Env, EnvBase, ErrorHandler, Host, HostError, Symbol, U256Val, Val, VecObject,
+};
+use ark_ff::{BigInteger, PrimeField};
+use soroban_env_common::{TryFromVal, U256};
+use std::cmp::Ordering;
+
+// Grain LFSR used by the reference implementations of Poseidon and Poseidon2
+// to derive the round constants (and the Poseidon MDS matrix) from the
+// parameters of the permutation.
+struct Grain {
+    state: Vec<bool>,
+}
+
+impl Grain {
+    fn new(field_size: u64, t: u64, rounds_f: u64, rounds_p: u64) -> Self {
+        let mut state = vec![];
+        let mut push = |v: u64, width: u32| {
+            for i in (0..width).rev() {
+                state.push((v >> i) & 1 == 1);
+            }
+        };
+        // Prime field, `x^alpha` S-box.
+        push(1, 2);
+        push(0, 4);
+        push(field_size, 12);
+        push(t, 12);
+        push(rounds_f, 10);
+        push(rounds_p, 10);
+        push((1 << 30) - 1, 30);
+        let mut grain = Self { state };
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let s = &self.state;
+        let bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0];
+        self.state.remove(0);
+        self.state.push(bit);
+        bit
+    }
+
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let b1 = self.step();
+            let b2 = self.step();
+            if b1 {
+                return b2;
+            }
+        }
+    }
+
+    fn next_bigint<F: PrimeField>(&mut self) -> F::BigInt {
+        let bits: Vec<bool> = (0..F::MODULUS_BIT_SIZE).map(|_| self.next_bit()).collect();
+        F::BigInt::from_bits_be(&bits)
+    }
+
+    // Samples a field element by rejection.
+    fn next_field_element<F: PrimeField>(&mut self) -> F {
+        loop {
+            if let Some(f) = F::from_bigint(self.next_bigint::<F>()) {
+                return f;
+            }
+        }
+    }
+
+    // Samples a field element by reduction (used for the MDS matrix).
+    fn next_field_element_reduced<F: PrimeField>(&mut self) -> F {
+        F::from_le_bytes_mod_order(&self.next_bigint::<F>().to_bytes_le())
+    }
+}
+
+fn poseidon_params<F: PrimeField>(
+    t: usize,
+    rounds_f: usize,
+    rounds_p: usize,
+) -> (Vec<Vec<F>>, Vec<Vec<F>>) {
+    let mut grain = Grain::new(
+        F::MODULUS_BIT_SIZE as u64,
+        t as u64,
+        rounds_f as u64,
+        rounds_p as u64,
+    );
+    let round_constants = (0..rounds_f + rounds_p)
+        .map(|_| (0..t).map(|_| grain.next_field_element()).collect())
+        .collect();
+    // Cauchy matrix `1 / (x_i + y_j)`. The sampled values for the standard
+    // parameter sets are distinct and produce no zero sums.
+    let xs: Vec<F> = (0..t).map(|_| grain.next_field_element_reduced()).collect();
+    let ys: Vec<F> = (0..t).map(|_| grain.next_field_element_reduced()).collect();
+    let mds = xs
+        .iter()
+        .map(|x| ys.iter().map(|y| (*x + y).inverse().unwrap()).collect())
+        .collect();
+    (mds, round_constants)
+}
+
+fn poseidon2_round_constants<F: PrimeField>(
+    t: usize,
+    rounds_f: usize,
+    rounds_p: usize,
+) -> Vec<Vec<F>> {
+    let mut grain = Grain::new(
+        F::MODULUS_BIT_SIZE as u64,
+        t as u64,
+        rounds_f as u64,
+        rounds_p as u64,
+    );
+    (0..rounds_f + rounds_p)
+        .map(|r| {
+            if r < rounds_f / 2 || r >= rounds_f / 2 + rounds_p {
+                (0..t).map(|_| grain.next_field_element()).collect()
+            } else {
+                let mut row = vec![F::zero(); t];
+                row[0] = grain.next_field_element();
+                row
+            }
+        })
+        .collect()
+}
+
+fn field_to_val<F: PrimeField>(host: &Host, f: &F) -> Result<Val, HostError> {
+    let bytes: [u8; 32] = f.into_bigint().to_bytes_be().try_into().unwrap();
+    let u = U256::from_be_bytes(bytes);
+    Ok(host.map_err(U256Val::try_from_val(host, &u))?.to_val())
+}
+
+fn field_vec<F: PrimeField>(host: &Host, v: &[F]) -> Result<VecObject, HostError> {
+    let vals = v
+        .iter()
+        .map(|f| field_to_val(host, f))
+        .collect::<Result<Vec<_>, _>>()?;
+    host.vec_new_from_slice(&vals)
+}
+
+fn field_matrix<F: PrimeField>(host: &Host, m: &[Vec<F>]) -> Result<VecObject, HostError> {
+    let rows = m
+        .iter()
+        .map(|row| Ok(field_vec(host, row)?.to_val()))
+        .collect::<Result<Vec<_>, HostError>>()?;
+    host.vec_new_from_slice(&rows)
+}
+
+fn u256_hex_vec(host: &Host, hex_values: &[&str]) -> Result<VecObject, HostError> {
+    let vals = hex_values
+        .iter()
+        .map(|h| {
+            let mut bytes = [0u8; 32];
+            let decoded = hex::decode(format!("{:0>64}", h)).unwrap();
+            bytes.copy_from_slice(&decoded);
+            Ok(host
+                .map_err(U256Val::try_from_val(host, &U256::from_be_bytes(bytes)))?
+                .to_val())
+        })
+        .collect::<Result<Vec<_>, HostError>>()?;
+    host.vec_new_from_slice(&vals)
+}
+
+fn small_u256_vec(host: &Host, values: &[u32]) -> Result<VecObject, HostError> {
+    let vals: Vec<Val> = values
+        .iter()
+        .map(|v| U256Val::from_u32(*v).to_val())
+        .collect();
+    host.vec_new_from_slice(&vals)
+}
+
+fn assert_vec_eq(host: &Host, a: VecObject, b: VecObject) -> Result<(), HostError> {
+    assert_eq!(
+        host.obj_cmp(a.to_val(), b.to_val())?,
+        Ordering::Equal as i64
+    );
+    Ok(())
+}
+
+fn bn254_symbol() -> Symbol {
+    Symbol::try_from_small_str("BN254").unwrap()
+}
+
+fn bls12_381_symbol() -> Symbol {
+    Symbol::try_from_small_str("BLS12_381").unwrap()
+}
+
+// The Poseidon permutation functions are not available before protocol 25, so
+// these tests use unobserved hosts.
+
+#[test]
+fn test_poseidon_bn254_reference_vector() -> Result<(), HostError> {
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+    host.budget_ref().reset_unlimited()?;
+
+    // circomlib `Poseidon(2)` parameters: t = 3, x^5, 8 full and 57 partial
+    // rounds. The hash of `[1, 2]` is the first element of the permuted
+    // state `[0, 1, 2]`.
+    let (mds, rc) = poseidon_params::<ark_bn254::Fr>(3, 8, 57);
+    let res = host.poseidon_permutation(
+        small_u256_vec(&host, &[0, 1, 2])?,
+        bn254_symbol(),
+        3u32.into(),
+        5u32.into(),
+        8u32.into(),
+        57u32.into(),
+        field_matrix(&host, &mds)?,
+        field_matrix(&host, &rc)?,
+    )?;
+    let hash = host.vec_get(res, 0u32.into())?;
+    let expected = u256_hex_vec(
+        &host,
+        &["115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"],
+    )?;
+    assert_eq!(
+        host.obj_cmp(hash, host.vec_get(expected, 0u32.into())?)?,
+        Ordering::Equal as i64
+    );
+    Ok(())
+}
+
+#[test]
+fn test_poseidon2_bn254_reference_vector() -> Result<(), HostError> {
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+    host.budget_ref().reset_unlimited()?;
+
+    // HorizenLabs reference implementation, t = 3, x^5, 8 full and 56
+    // partial rounds.
+    let rc = poseidon2_round_constants::<ark_bn254::Fr>(3, 8, 56);
+    let res = host.poseidon2_permutation(
+        small_u256_vec(&host, &[0, 1, 2])?,
+        bn254_symbol(),
+        3u32.into(),
+        5u32.into(),
+        8u32.into(),
+        56u32.into(),
+        small_u256_vec(&host, &[1, 1, 2])?,
+        field_matrix(&host, &rc)?,
+    )?;
+    let expected = u256_hex_vec(
+        &host,
+        &[
+            "0bb61d24daca55eebcb1929a82650f328134334da98ea4f847f760054f4a3033",
+            "303b6f7c86d043bfcbcc80214f26a30277a15d3f74ca654992defe7ff8d03570",
+            "1ed25194542b12eef8617361c3ba7c52e660b145994427cc86296242cf766ec8",
+        ],
+    )?;
+    assert_vec_eq(&host, res, expected)
+}
+
+#[test]
+fn test_poseidon2_bn254_width_4_reference_vector() -> Result<(), HostError> {
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+    host.budget_ref().reset_unlimited()?;
+
+    // HorizenLabs parameters for t = 4, x^5, 8 full and 56 partial rounds
+    // (also used by barretenberg). The external matrix of this width is
+    // `M4` alone, unlike the larger widths.
+    let rc = poseidon2_round_constants::<ark_bn254::Fr>(4, 8, 56);
+    let mat_internal_diag_m_1 = u256_hex_vec(
+        &host,
+        &[
+            "10dc6e9c006ea38b04b1e03b4bd9490c0d03f98929ca1d7fb56821fd19d3b6e7",
+            "0c28145b6a44df3e0149b3d0a30b3bb599df9756d4dd9b84a86b38cfb45a740b",
+            "00544b8338791518b2c7645a50392798b21f75bb60e3596170067d00141cac15",
+            "222c01175718386f2e2e82eb122789e352e105a3b8fa852613bc534433ee428b",
+        ],
+    )?;
+    let res = host.poseidon2_permutation(
+        small_u256_vec(&host, &[0, 1, 2, 3])?,
+        bn254_symbol(),
+        4u32.into(),
+        5u32.into(),
+        8u32.into(),
+        56u32.into(),
+        mat_internal_diag_m_1,
+        field_matrix(&host, &rc)?,
+    )?;
+    let expected = u256_hex_vec(
+        &host,
+        &[
+            "01bd538c2ee014ed5141b29e9ae240bf8db3fe5b9a38629a9647cf8d76c01737",
+            "239b62e7db98aa3a2a8f6a0d2fa1709e7a35959aa6c7034814d9daa90cbac662",
+            "04cbb44c61d928ed06808456bf758cbf0c18d1e15a7b6dbc8245fa7515d5e3cb",
+            "2e11c5cff2a22c64d01304b778d78f6998eff1ab73163a35603f54794c30847a",
+        ],
+    )?;
+    assert_vec_eq(&host, res, expected)
+}
+
+#[test]
+fn test_poseidon2_bls12_381_reference_vector() -> Result<(), HostError> {
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+    host.budget_ref().reset_unlimited()?;
+
+    // HorizenLabs reference implementation, t = 3, x^5, 8 full and 56
+    // partial rounds.
+    let rc = poseidon2_round_constants::<ark_bls12_381::Fr>(3, 8, 56);
+    let res = host.poseidon2_permutation(
+        small_u256_vec(&host, &[0, 1, 2])?,
+        bls12_381_symbol(),
+        3u32.into(),
+        5u32.into(),
+        8u32.into(),
+        56u32.into(),
+        small_u256_vec(&host, &[1, 1, 2])?,
+        field_matrix(&host, &rc)?,
+    )?;
+    let expected = u256_hex_vec(
+        &host,
+        &[
+            "1b152349b1950b6a8ca75ee4407b6e26ca5cca5650534e56ef3fd45761fbf5f0",
+            "4c5793c87d51bdc2c08a32108437dc0000bd0275868f09ebc5f36919af5b3891",
+            "1fc8ed171e67902ca49863159fe5ba6325318843d13976143b8125f08b50dc6b",
+        ],
+    )?;
+    assert_vec_eq(&host, res, expected)
+}
+
+#[test]
+fn test_poseidon_invalid_parameters() -> Result<(), HostError> {
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+    host.budget_ref().reset_unlimited()?;
+
+    let (mds, rc) = poseidon_params::<ark_bn254::Fr>(3, 8, 57);
+    let mds = field_matrix(&host, &mds)?;
+    let rc = field_matrix(&host, &rc)?;
+    let input = small_u256_vec(&host, &[0, 1, 2])?;
+    let call = |input: VecObject, field: Symbol, t: u32, d: u32, rounds_f: u32, rounds_p: u32| {
+        host.poseidon_permutation(
+            input,
+            field,
+            t.into(),
+            d.into(),
+            rounds_f.into(),
+            rounds_p.into(),
+            mds,
+            rc,
+        )
+    };
+    let invalid_input = (ScErrorType::Crypto, ScErrorCode::InvalidInput);
+
+    assert!(call(input, bn254_symbol(), 3, 5, 8, 57).is_ok());
+    // unsupported field
+    assert!(HostError::result_matches_err(
+        call(input, Symbol::try_from_small_str("BN256")?, 3, 5, 8, 57),
+        invalid_input
+    ));
+    // `x^3` is not a permutation of the BN254 scalar field
+    assert!(HostError::result_matches_err(
+        call(input, bn254_symbol(), 3, 3, 8, 57),
+        invalid_input
+    ));
+    // unsupported S-box degree
+    assert!(HostError::result_matches_err(
+        call(input, bn254_symbol(), 3, 9, 8, 57),
+        invalid_input
+    ));
+    // odd number of full rounds
+    assert!(HostError::result_matches_err(
+        call(input, bn254_symbol(), 3, 5, 7, 58),
+        invalid_input
+    ));
+    // number of rounds doesn't match the round constants
+    assert!(HostError::result_matches_err(
+        call(input, bn254_symbol(), 3, 5, 8, 56),
+        invalid_input
+    ));
+    // state width doesn't match the mds matrix and the input
+    assert!(HostError::result_matches_err(
+        call(input, bn254_symbol(), 4, 5, 8, 57),
+        invalid_input
+    ));
+    assert!(HostError::result_matches_err(
+        call(input, bn254_symbol(), 1, 5, 8, 57),
+        invalid_input
+    ));
+    // input length doesn't match the state width
+    assert!(HostError::result_matches_err(
+        call(small_u256_vec(&host, &[0, 1])?, bn254_symbol(), 3, 5, 8, 57),
+        invalid_input
+    ));
+    // input must consist of U256 values
+    assert!(HostError::result_matches_err(
+        call(
+            host.vec_new_from_slice(&[Val::from_u32(0).to_val(); 3])?,
+            bn254_symbol(),
+            3,
+            5,
+            8,
+            57
+        ),
+        (ScErrorType::Value, ScErrorCode::UnexpectedType)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_poseidon2_invalid_parameters() -> Result<(), HostError> {
+    let host = Host::test_host();
+    if host.get_ledger_protocol_version()? < 25 {
+        return Ok(());
+    }
+    host.enable_debug()?;
+    host.budget_ref().reset_unlimited()?;
+
+    // Poseidon2 is only defined for the widths 2, 3 and multiples of 4.
+    let rc = poseidon2_round_constants::<ark_bn254::Fr>(5, 8, 56);
+    assert!(HostError::result_matches_err(
+        host.poseidon2_permutation(
+            small_u256_vec(&host, &[0, 1, 2, 3, 4])?,
+            bn254_symbol(),
+            5u32.into(),
+            5u32.into(),
+            8u32.into(),
+            56u32.into(),
+            small_u256_vec(&host, &[1, 1, 1, 1, 2])?,
+            field_matrix(&host, &rc)?,
+        ),
+        (ScErrorType::Crypto, ScErrorCode::InvalidInput)
+    ));
+    Ok(())
+}
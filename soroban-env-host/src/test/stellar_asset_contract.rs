@@ -7,7 +7,10 @@ use crate::{
     builtin_contracts::{
         base_types::Address,
         contract_error::ContractError,
-        stellar_asset_contract::test_stellar_asset_contract::TestStellarAssetContract,
+        stellar_asset_contract::{
+            sep41_conformance::{create_reference_token, Sep41ConformanceTest},
+            test_stellar_asset_contract::TestStellarAssetContract,
+        },
         testutils::{
             account_to_address, authorize_single_invocation,
             authorize_single_invocation_with_nonce, contract_id_to_address, create_account,
@@ -16,7 +19,7 @@ use crate::{
         },
     },
     host::{frame::TestContractFrame, Frame},
-    testutils::generate_bytes_array,
+    testutils::{generate_bytes_array, wasm::wasm_module_with_sep41_token},
     xdr::{
         self, AccountFlags, AccountId, AlphaNum12, AlphaNum4, Asset, AssetCode12, AssetCode4,
         ContractEventType, ContractExecutable, ContractId, Hash, InvokeContractArgs,
@@ -40,7 +43,7 @@ struct StellarAssetContractTest {
     // The `obs` field here just exists to
     // live as long as Host, then be dropped.
     #[allow(dead_code)]
-    obs: ObservedHost,
+    obs: Option<ObservedHost>,
     host: Host,
     issuer_key: SigningKey,
     user_key: SigningKey,
@@ -54,6 +57,17 @@ impl StellarAssetContractTest {
     fn setup(testname: &'static str) -> Self {
         let host = Host::test_host_with_recording_footprint();
         let obs = ObservedHost::new(testname, host.clone());
+        Self::setup_with_host(host, Some(obs))
+    }
+
+    // Sets up the test without recording the host observations. This is
+    // meant for the tests that drive the contract through the test harnesses,
+    // as their traces just repeat the traces of the regular contract tests.
+    fn setup_without_observations() -> Self {
+        Self::setup_with_host(Host::test_host_with_recording_footprint(), None)
+    }
+
+    fn setup_with_host(host: Host, obs: Option<ObservedHost>) -> Self {
         let protocol_version = host.get_ledger_protocol_version().unwrap();
         host.set_ledger_info(LedgerInfo {
             protocol_version,
@@ -494,6 +508,67 @@ fn test_do_not_init_contract_for_invalid_asset() {
     run_test(b"Abc1\0\0\0\0\0\0\0\0");
 }
 
+#[cfg(feature = "next")]
+#[test]
+fn test_asset_info() {
+    let test = StellarAssetContractTest::setup_without_observations();
+    let contract = test.default_stellar_asset_contract();
+    let info = contract.asset_info().unwrap();
+    assert_eq!(info.asset_type.to_string(), "credit_alphanum4");
+    assert_eq!(info.asset_code.unwrap().to_string(), "aaaa");
+    assert_eq!(
+        info.issuer.unwrap().to_string(),
+        ed25519::PublicKey(test.issuer_key.verifying_key().to_bytes()).to_string()
+    );
+    // The default issuer is revocable and clawback-enabled.
+    assert!(!info.auth_required);
+    assert!(info.auth_revocable);
+    assert!(info.clawback_enabled);
+
+    // Issuer flag changes are reflected in the asset info.
+    let issuer_id = signing_key_to_account_id(&test.issuer_key);
+    test.update_account_flags(
+        &test.host.to_account_key(issuer_id).unwrap(),
+        AccountFlags::RequiredFlag as u32,
+    );
+    let info = contract.asset_info().unwrap();
+    assert!(info.auth_required);
+    assert!(!info.auth_revocable);
+    assert!(!info.clawback_enabled);
+
+    let asset12 = create_asset(
+        &signing_key_to_account_id(&test.issuer_key),
+        b"123def456XYZ",
+    );
+    let contract = TestStellarAssetContract::new_from_asset(&test.host, asset12).unwrap();
+    let info = contract.asset_info().unwrap();
+    assert_eq!(info.asset_type.to_string(), "credit_alphanum12");
+    assert_eq!(info.asset_code.unwrap().to_string(), "123def456XYZ");
+
+    let contract = TestStellarAssetContract::new_from_asset(&test.host, Asset::Native).unwrap();
+    let info = contract.asset_info().unwrap();
+    assert_eq!(info.asset_type.to_string(), "native");
+    assert!(info.asset_code.is_none());
+    assert!(info.issuer.is_none());
+    assert!(!info.auth_required);
+    assert!(!info.auth_revocable);
+    assert!(!info.clawback_enabled);
+}
+
+#[test]
+fn test_asset_info_is_not_available_before_protocol_25() {
+    let test = StellarAssetContractTest::setup_without_observations();
+    test.host
+        .with_mut_ledger_info(|li| li.protocol_version = 24)
+        .unwrap();
+    let contract = test.default_stellar_asset_contract();
+    let err = contract.asset_info().err().unwrap();
+    assert!(err.error.is_type(ScErrorType::Value));
+    assert!(err.error.is_code(ScErrorCode::InvalidInput));
+    // The remaining functions are still available.
+    assert_eq!(contract.symbol().unwrap().to_string(), "aaaa");
+}
+
 #[test]
 fn test_zero_amounts() {
     let test = StellarAssetContractTest::setup(function_name!());
@@ -846,6 +921,304 @@ fn test_cap_67_transfer_with_muxed_accounts() {
     );
 }
 
+// Muxed `mint` and `transfer_from` destinations are only supported starting
+// from protocol 25, before that they must be rejected.
+#[test]
+fn test_mint_and_transfer_from_with_muxed_accounts() {
+    let test = StellarAssetContractTest::setup(function_name!());
+    // Enable invocation metering to get the events to reset automatically on
+    // every contract call.
+    test.host.enable_invocation_metering();
+    let admin = TestSigner::account(&test.issuer_key);
+    let contract = test.default_stellar_asset_contract();
+
+    let user = TestSigner::account(&test.user_key);
+    let user_2 = TestSigner::account(&test.user_key_2);
+    let user_3 = TestSigner::account(&test.user_key_3);
+    test.create_default_account(&user);
+    test.create_default_account(&user_2);
+    test.create_default_account(&user_3);
+    test.create_default_trustline(&user);
+    test.create_default_trustline(&user_2);
+    test.create_default_trustline(&user_3);
+
+    if test.host.get_ledger_protocol_version().unwrap() < 25 {
+        assert!(HostError::result_matches_err(
+            contract.mint_muxed(&admin, user.muxed_address(&test.host, Some(1)), 100),
+            (ScErrorType::Value, ScErrorCode::UnexpectedType)
+        ));
+        contract
+            .mint(&admin, user.address(&test.host), 100)
+            .unwrap();
+        contract
+            .approve(&user, user_3.address(&test.host), 100, 200)
+            .unwrap();
+        assert!(HostError::result_matches_err(
+            contract.transfer_from_muxed(
+                &user_3,
+                user.address(&test.host),
+                user_2.muxed_address(&test.host, Some(2)),
+                100
+            ),
+            (ScErrorType::Value, ScErrorCode::UnexpectedType)
+        ));
+        // Non-muxed addresses passed via `MuxedAddress` still work.
+        contract
+            .transfer_from_muxed(
+                &user_3,
+                user.address(&test.host),
+                user_2.muxed_address(&test.host, None),
+                100,
+            )
+            .unwrap();
+        assert_eq!(contract.balance(user_2.address(&test.host)).unwrap(), 100);
+        return;
+    }
+
+    let token_name = contract.name().unwrap();
+
+    // Mint to a muxed destination.
+    contract
+        .mint_muxed(
+            &admin,
+            user.muxed_address(&test.host, Some(123_456)),
+            100_000_000,
+        )
+        .unwrap();
+    assert_eq!(
+        test.host.get_contract_events().unwrap().0,
+        vec![contract.test_event(
+            test_vec![
+                &test.host,
+                Symbol::try_from_small_str("mint").unwrap().to_val(),
+                user.address(&test.host),
+                &token_name,
+            ],
+            test_map![
+                &test.host,
+                ("amount", 100_000_000_i128),
+                ("to_muxed_id", 123_456_u64)
+            ]
+            .into()
+        )]
+    );
+    assert_eq!(
+        contract.balance(user.address(&test.host)).unwrap(),
+        100_000_000
+    );
+
+    // Mint to a non-muxed destination passed as `MuxedAddress` emits the
+    // regular event.
+    contract
+        .mint_muxed(&admin, user.muxed_address(&test.host, None), 1)
+        .unwrap();
+    assert_eq!(
+        test.host.get_contract_events().unwrap().0,
+        vec![contract.test_event(
+            test_vec![
+                &test.host,
+                Symbol::try_from_small_str("mint").unwrap().to_val(),
+                user.address(&test.host),
+                &token_name,
+            ],
+            1_i128.try_into_val(&test.host).unwrap()
+        )]
+    );
+
+    // Muxed mint to the issuer is still not allowed.
+    assert_eq!(
+        to_contract_err(
+            contract
+                .mint_muxed(&admin, admin.muxed_address(&test.host, Some(1)), 1)
+                .err()
+                .unwrap()
+        ),
+        ContractError::OperationNotSupportedError
+    );
+
+    contract
+        .approve(&user, user_3.address(&test.host), 10_000_000, 200)
+        .unwrap();
+
+    // Transfer with allowance to a muxed destination.
+    contract
+        .transfer_from_muxed(
+            &user_3,
+            user.address(&test.host),
+            user_2.muxed_address(&test.host, Some(u64::MAX)),
+            6_000_000,
+        )
+        .unwrap();
+    assert_eq!(
+        test.host.get_contract_events().unwrap().0,
+        vec![contract.test_event(
+            test_vec![
+                &test.host,
+                Symbol::try_from_small_str("transfer").unwrap().to_val(),
+                user.address(&test.host),
+                user_2.address(&test.host),
+                &token_name,
+            ],
+            test_map![
+                &test.host,
+                ("amount", 6_000_000_i128),
+                ("to_muxed_id", u64::MAX)
+            ]
+            .into()
+        )]
+    );
+    assert_eq!(
+        contract.balance(user.address(&test.host)).unwrap(),
+        94_000_001
+    );
+    assert_eq!(
+        contract.balance(user_2.address(&test.host)).unwrap(),
+        6_000_000
+    );
+    assert_eq!(
+        contract
+            .allowance(user.address(&test.host), user_3.address(&test.host))
+            .unwrap(),
+        4_000_000
+    );
+
+    // Transfer with allowance to the muxed issuer emits a burn event without
+    // the muxed id, same as `transfer`.
+    contract
+        .transfer_from_muxed(
+            &user_3,
+            user.address(&test.host),
+            admin.muxed_address(&test.host, Some(7)),
+            1_000_000,
+        )
+        .unwrap();
+    assert_eq!(
+        test.host.get_contract_events().unwrap().0,
+        vec![contract.test_event(
+            test_vec![
+                &test.host,
+                Symbol::try_from_small_str("burn").unwrap().to_val(),
+                user.address(&test.host),
+                &token_name,
+            ],
+            1_000_000_i128.try_into_val(&test.host).unwrap()
+        )]
+    );
+    assert_eq!(
+        contract.balance(user.address(&test.host)).unwrap(),
+        93_000_001
+    );
+}
+
+#[test]
+fn test_sep41_conformance_of_another_stellar_asset_contract() {
+    let test = StellarAssetContractTest::setup_without_observations();
+    let reference = create_reference_token(
+        &test.host,
+        &test.issuer_key,
+        &[
+            (&test.user_key, 10_000),
+            (&test.user_key_2, 0),
+            (&test.user_key_3, 0),
+        ],
+    );
+
+    let candidate_issuer_id = signing_key_to_account_id(&test.user_key_4);
+    let candidate_asset_code = [b'b'; 12];
+    test.create_account(
+        &candidate_issuer_id,
+        vec![(&test.user_key_4, 100)],
+        10_000_000,
+        1,
+        [1, 0, 0, 0],
+        None,
+        None,
+        0,
+    );
+    let candidate = TestStellarAssetContract::new_from_asset(
+        &test.host,
+        Asset::CreditAlphanum12(AlphaNum12 {
+            asset_code: AssetCode12(candidate_asset_code),
+            issuer: candidate_issuer_id.clone(),
+        }),
+    )
+    .unwrap();
+    // The harness accepts any token contract address.
+    let candidate = TestStellarAssetContract::new_from_address_object(
+        &test.host,
+        candidate.address.as_object(),
+    )
+    .unwrap();
+    for (key, balance) in [
+        (&test.user_key, 10_000),
+        (&test.user_key_2, 0),
+        (&test.user_key_3, 0),
+    ] {
+        test.create_trustline(
+            &signing_key_to_account_id(key),
+            &candidate_issuer_id,
+            candidate_asset_code.as_slice(),
+            balance,
+            i64::MAX,
+            TrustLineFlags::AuthorizedFlag as u32,
+            None,
+        );
+    }
+
+    Sep41ConformanceTest::new(&test.host, reference, candidate).run(
+        &test.user_key,
+        &test.user_key_2,
+        &test.user_key_3,
+    );
+}
+
+fn sep41_conformance_test_for_wasm_token(test: &StellarAssetContractTest, spends_allowance: bool) {
+    let reference = create_reference_token(
+        &test.host,
+        &test.issuer_key,
+        &[
+            (&test.user_key, 10_000),
+            (&test.user_key_2, 0),
+            (&test.user_key_3, 0),
+        ],
+    );
+    let wasm = wasm_module_with_sep41_token(spends_allowance);
+    let candidate_address = test.host.register_test_contract_wasm(&wasm);
+    let candidate =
+        TestStellarAssetContract::new_from_address_object(&test.host, candidate_address).unwrap();
+    test.host
+        .call(
+            candidate_address,
+            Symbol::try_from_small_str("mint").unwrap(),
+            test_vec![
+                &test.host,
+                TestSigner::account(&test.user_key).address(&test.host),
+                10_000_i128
+            ]
+            .into(),
+        )
+        .unwrap();
+
+    Sep41ConformanceTest::new(&test.host, reference, candidate).run(
+        &test.user_key,
+        &test.user_key_2,
+        &test.user_key_3,
+    );
+}
+
+#[test]
+fn test_sep41_conformance_of_wasm_token() {
+    let test = StellarAssetContractTest::setup_without_observations();
+    sep41_conformance_test_for_wasm_token(&test, true);
+}
+
+#[test]
+#[should_panic(expected = "SEP-41 conformance: `allowance` result mismatch")]
+fn test_sep41_conformance_of_wasm_token_not_spending_allowance() {
+    let test = StellarAssetContractTest::setup_without_observations();
+    sep41_conformance_test_for_wasm_token(&test, false);
+}
+
 #[test]
 fn test_transfer_with_allowance() {
     let test = StellarAssetContractTest::setup(function_name!());
@@ -3364,6 +3737,52 @@ fn test_classic_transfers_not_possible_for_unauthorized_asset() {
     assert_eq!(test.get_trustline_balance(&trustline_key), 100_000_000);
 }
 
+#[test]
+fn test_transfer_to_account_without_trustline() {
+    let test = StellarAssetContractTest::setup_without_observations();
+    test.host.enable_debug().unwrap();
+    let admin = TestSigner::account(&test.issuer_key);
+    let contract = test.default_stellar_asset_contract();
+
+    let user = TestSigner::account(&test.user_key);
+    let user_2 = TestSigner::account(&test.user_key_2);
+    test.create_default_account(&user);
+    test.create_default_account(&user_2);
+    test.create_default_trustline(&user);
+
+    contract
+        .mint(&admin, user.address(&test.host), 100_000_000)
+        .unwrap();
+    assert_eq!(
+        to_contract_err(
+            contract
+                .transfer(&user, user_2.address(&test.host), 1)
+                .err()
+                .unwrap()
+        ),
+        ContractError::TrustlineMissingError
+    );
+
+    // The diagnostics name both the account and the asset of the missing
+    // trustline.
+    let asset_name = format!(
+        "aaaa:{}",
+        ed25519::PublicKey(test.issuer_key.verifying_key().to_bytes())
+    );
+    let user_2_strkey = ed25519::PublicKey(test.user_key_2.verifying_key().to_bytes()).to_string();
+    let events = test.host.get_diagnostic_events().unwrap().0;
+    assert!(events.iter().any(|e| {
+        let event = format!("{}", e);
+        event.contains("trustline entry is missing for account and asset")
+            && event.contains(&user_2_strkey)
+            && event.contains(&asset_name)
+    }));
+    assert_eq!(
+        contract.balance(user.address(&test.host)).unwrap(),
+        100_000_000
+    );
+}
+
 #[test]
 fn test_custom_account_auth() {
     let test = StellarAssetContractTest::setup(function_name!());
@@ -3502,8 +3921,14 @@ fn test_custom_account_auth() {
                         temporary_entry_rent_bumps: 0,
                     },
                     sub_call_resources: [],
+                    wasm_profile: [],
+                    wasm_profile_hook_fuel: 0,
+                    ledger_entries: [],
                 },
             ],
+            wasm_profile: [],
+            wasm_profile_hook_fuel: 0,
+            ledger_entries: [],
         }"#]]
     .assert_eq(
         format!(
@@ -13,6 +13,10 @@ use crate::{
     StorageType, SymbolSmall, Val, VecObject,
 };
 use ed25519_dalek::SigningKey;
+
+pub use crate::builtin_contracts::stellar_asset_contract::{
+    sep41_conformance, test_stellar_asset_contract::TestStellarAssetContract,
+};
 use rand::RngCore;
 use std::panic::{catch_unwind, set_hook, take_hook, UnwindSafe};
 use std::{cell::Cell, collections::BTreeMap, rc::Rc, sync::Once};
@@ -583,9 +587,9 @@ impl Host {
 
 #[cfg(test)]
 pub(crate) mod wasm {
-    use crate::{Symbol, Tag, U32Val, Val};
-    use soroban_synth_wasm::{Arity, FuncRef, LocalRef, ModEmitter, Operand};
-    use wasm_encoder::{ConstExpr, Elements, RefType};
+    use crate::{StorageType, Symbol, Tag, U32Val, Val};
+    use soroban_synth_wasm::{Arity, FuncEmitter, FuncRef, LocalRef, ModEmitter, Operand};
+    use wasm_encoder::{BlockType, ConstExpr, Elements, Instruction, RefType, ValType};
 
     pub(crate) fn wasm_module_with_4n_insns(n: usize) -> Vec<u8> {
         let mut fe = ModEmitter::default_with_test_protocol().func(Arity(1), 0);
@@ -1125,6 +1129,60 @@ pub(crate) mod wasm {
         fe.finish_and_export("test").finish()
     }
 
+    pub(crate) fn wasm_module_calling_get_id_from_muxed_address() -> Vec<u8> {
+        let mut me = ModEmitter::default_with_test_protocol();
+        // get_id_from_muxed_address
+        let f0 = me.import_func("a", "5", Arity(1));
+        // the caller
+        let mut fe = me.func(Arity(1), 0);
+        fe.push(Operand::Local(LocalRef(0)));
+        fe.call_func(f0);
+        fe.finish_and_export("test").finish()
+    }
+
+    pub(crate) fn wasm_module_calling_try_call_with_budget_then_looping(
+        cpu_limit: Val,
+        mem_limit: Val,
+        n_iters: i64,
+        expected_res: Val,
+    ) -> Vec<u8> {
+        let mut me = ModEmitter::default_with_test_protocol();
+        // try_call_with_budget
+        let f0 = me.import_func("d", "1", Arity(5));
+        // the caller, takes the callee address and its arguments
+        let mut fe = me.func(Arity(2), 2);
+        let (res, ctr) = (fe.locals[0].0, fe.locals[1].0);
+        fe.push(Operand::Local(LocalRef(0)));
+        fe.push(Symbol::try_from_small_str("main").unwrap());
+        fe.push(Operand::Local(LocalRef(1)));
+        fe.push(cpu_limit);
+        fe.push(mem_limit);
+        fe.call_func(f0);
+        fe.local_set(res);
+        // The loop is metered on its own, so its fuel has to be supplied
+        // after the call above returns.
+        fe.push(Operand::Const64(n_iters));
+        fe.local_set(ctr);
+        fe.insn(&Instruction::Loop(BlockType::Empty));
+        fe.local_get(ctr);
+        fe.push(Operand::Const64(1));
+        fe.i64_sub();
+        fe.local_tee(ctr);
+        fe.push(Operand::Const64(0));
+        fe.i64_ne();
+        fe.insn(&Instruction::BrIf(0));
+        fe.insn(&Instruction::End);
+        // Contracts can't return non-contract errors, so return whether the
+        // call has returned `expected_res` instead.
+        fe.push(Val::from_bool(true).to_val());
+        fe.push(Val::from_bool(false).to_val());
+        fe.local_get(res);
+        fe.push(expected_res);
+        fe.i64_eq();
+        fe.select();
+        fe.finish_and_export("test").finish()
+    }
+
     pub(crate) fn wasm_module_with_a_bit_of_everything(wasm_proto: u32) -> Vec<u8> {
         let mut me = ModEmitter::new();
         me.add_protocol_version_meta(wasm_proto);
@@ -1145,6 +1203,330 @@ pub(crate) mod wasm {
         me.define_data_segment(0x1234, vec![0; 512]);
         me.finish()
     }
+
+    // Host functions called by the token built in
+    // `wasm_module_with_sep41_token`, along with the code snippets it is
+    // built from.
+    struct Sep41TokenEmitter {
+        put_contract_data: FuncRef,
+        has_contract_data: FuncRef,
+        get_contract_data: FuncRef,
+        require_auth: FuncRef,
+        contract_event: FuncRef,
+        get_ledger_sequence: FuncRef,
+        vec_new: FuncRef,
+        vec_push_back: FuncRef,
+        vec_get: FuncRef,
+        string_new_from_linear_memory: FuncRef,
+        spends_allowance: bool,
+    }
+
+    impl Sep41TokenEmitter {
+        // `I128Small` values are `body << 8 | tag`, so they can be compared
+        // as is, and added or subtracted with a correction of the tag.
+        const I128_TAG: i64 = Tag::I128Small as i64;
+        const BALANCE_STORAGE: StorageType = StorageType::Persistent;
+        const ALLOWANCE_STORAGE: StorageType = StorageType::Temporary;
+
+        fn new(me: &mut ModEmitter, spends_allowance: bool) -> Self {
+            Self {
+                put_contract_data: me.import_func("l", "_", Arity(3)),
+                has_contract_data: me.import_func("l", "0", Arity(2)),
+                get_contract_data: me.import_func("l", "1", Arity(2)),
+                require_auth: me.import_func("a", "0", Arity(1)),
+                contract_event: me.import_func("x", "1", Arity(2)),
+                get_ledger_sequence: me.import_func("x", "3", Arity(0)),
+                vec_new: me.import_func("v", "_", Arity(0)),
+                vec_push_back: me.import_func("v", "6", Arity(2)),
+                vec_get: me.import_func("v", "1", Arity(2)),
+                string_new_from_linear_memory: me.import_func("b", "i", Arity(2)),
+                spends_allowance,
+            }
+        }
+
+        // Traps unless `amount` is a non-negative `I128Small`.
+        fn check_amount(&self, fe: &mut FuncEmitter, amount: LocalRef) {
+            fe.local_get(amount).assert_val_tag(Tag::I128Small);
+            fe.local_get(amount).i64_const(0).i64_lt_s().if_then_trap();
+        }
+
+        fn require_auth(&self, fe: &mut FuncEmitter, address: LocalRef) {
+            fe.local_get(address).call_func(self.require_auth).drop();
+        }
+
+        // Pushes the value stored under `key`, or zero if there is none.
+        fn get_or_zero(&self, fe: &mut FuncEmitter, key: LocalRef, storage: StorageType) {
+            fe.local_get(key)
+                .push(storage)
+                .call_func(self.has_contract_data)
+                .push(Val::from_bool(true).to_val())
+                .i64_eq();
+            fe.insn(&Instruction::If(BlockType::Result(ValType::I64)));
+            fe.local_get(key)
+                .push(storage)
+                .call_func(self.get_contract_data);
+            fe.insn(&Instruction::Else);
+            fe.i64_const(Self::I128_TAG);
+            fe.end();
+        }
+
+        // Debits `amount` from the balance of `address`, trapping if the
+        // balance is too low.
+        fn spend_balance(
+            &self,
+            fe: &mut FuncEmitter,
+            address: LocalRef,
+            amount: LocalRef,
+            balance: LocalRef,
+        ) {
+            self.get_or_zero(fe, address, Self::BALANCE_STORAGE);
+            fe.local_tee(balance)
+                .local_get(amount)
+                .i64_lt_s()
+                .if_then_trap();
+            fe.local_get(address)
+                .local_get(balance)
+                .local_get(amount)
+                .i64_sub()
+                .i64_const(Self::I128_TAG)
+                .i64_add()
+                .push(Self::BALANCE_STORAGE)
+                .call_func(self.put_contract_data)
+                .drop();
+        }
+
+        fn receive_balance(&self, fe: &mut FuncEmitter, address: LocalRef, amount: LocalRef) {
+            fe.local_get(address);
+            self.get_or_zero(fe, address, Self::BALANCE_STORAGE);
+            fe.local_get(amount)
+                .i64_add()
+                .i64_const(Self::I128_TAG)
+                .i64_sub()
+                .push(Self::BALANCE_STORAGE)
+                .call_func(self.put_contract_data)
+                .drop();
+        }
+
+        // Pushes a vector of `vals`.
+        fn vec(&self, fe: &mut FuncEmitter, vals: &[Operand]) {
+            fe.call_func(self.vec_new);
+            for v in vals {
+                fe.push(*v).call_func(self.vec_push_back);
+            }
+        }
+
+        // Stores the allowance as a `[amount, live_until_ledger]` vector
+        // under the `[from, spender]` vector.
+        fn allowance_key(
+            &self,
+            fe: &mut FuncEmitter,
+            from: LocalRef,
+            spender: LocalRef,
+            key: LocalRef,
+        ) {
+            self.vec(fe, &[from.into(), spender.into()]);
+            fe.local_set(key);
+        }
+
+        // Pushes the allowance stored under `key`, or zero if it has expired.
+        // Leaves the stored vector in `entry`.
+        fn read_allowance(&self, fe: &mut FuncEmitter, key: LocalRef, entry: LocalRef) {
+            self.get_or_zero(fe, key, Self::ALLOWANCE_STORAGE);
+            fe.local_tee(entry).i64_const(Self::I128_TAG).i64_eq();
+            fe.insn(&Instruction::If(BlockType::Result(ValType::I64)));
+            fe.i64_const(Self::I128_TAG);
+            fe.insn(&Instruction::Else);
+            fe.local_get(entry)
+                .push(U32Val::from(1))
+                .call_func(self.vec_get)
+                .call_func(self.get_ledger_sequence)
+                .i64_lt_u();
+            fe.insn(&Instruction::If(BlockType::Result(ValType::I64)));
+            fe.i64_const(Self::I128_TAG);
+            fe.insn(&Instruction::Else);
+            fe.local_get(entry)
+                .push(U32Val::from(0))
+                .call_func(self.vec_get);
+            fe.end();
+            fe.end();
+        }
+
+        // Debits `amount` from the allowance, trapping if it is too low.
+        fn spend_allowance(
+            &self,
+            fe: &mut FuncEmitter,
+            from: LocalRef,
+            spender: LocalRef,
+            amount: LocalRef,
+            locals: [LocalRef; 3],
+        ) {
+            let [key, entry, allowance] = locals;
+            self.allowance_key(fe, from, spender, key);
+            self.read_allowance(fe, key, entry);
+            fe.local_tee(allowance)
+                .local_get(amount)
+                .i64_lt_s()
+                .if_then_trap();
+            if !self.spends_allowance {
+                return;
+            }
+            fe.local_get(amount)
+                .i64_const(Self::I128_TAG)
+                .i64_gt_s()
+                .if_then(|fe| {
+                    fe.local_get(key);
+                    fe.call_func(self.vec_new)
+                        .local_get(allowance)
+                        .local_get(amount)
+                        .i64_sub()
+                        .i64_const(Self::I128_TAG)
+                        .i64_add()
+                        .call_func(self.vec_push_back)
+                        .local_get(entry)
+                        .push(U32Val::from(1))
+                        .call_func(self.vec_get)
+                        .call_func(self.vec_push_back)
+                        .push(Self::ALLOWANCE_STORAGE)
+                        .call_func(self.put_contract_data)
+                        .drop()
+                });
+        }
+
+        // Emits an event with the `name` symbol and `addresses` as its
+        // topics, then returns `Void`.
+        fn event_and_return(
+            &self,
+            fe: &mut FuncEmitter,
+            name: &str,
+            addresses: &[LocalRef],
+            data: impl FnOnce(&mut FuncEmitter),
+        ) {
+            let mut topics = vec![Symbol::try_from_small_str(name).unwrap().into()];
+            topics.extend(addresses.iter().map(|a| Operand::from(*a)));
+            self.vec(fe, &topics);
+            data(fe);
+            fe.call_func(self.contract_event).drop();
+            fe.push(Val::VOID.to_val());
+        }
+    }
+
+    // A minimal token implementing the SEP-41 functions the way the Stellar
+    // Asset Contract does, for amounts that fit in `I128Small`. `mint`
+    // credits a balance without any authorization, to set up the tests. If
+    // `spends_allowance` is false, `transfer_from` and `burn_from` don't
+    // debit the allowance, which is a deliberate SEP-41 violation.
+    pub(crate) fn wasm_module_with_sep41_token(spends_allowance: bool) -> Vec<u8> {
+        const NAME: &[u8] = b"TEST";
+        let mut me = ModEmitter::default_with_test_protocol();
+        let t = Sep41TokenEmitter::new(&mut me, spends_allowance);
+
+        let mut fe = me.func(Arity(1), 0);
+        let address = fe.args[0].0;
+        t.get_or_zero(&mut fe, address, Sep41TokenEmitter::BALANCE_STORAGE);
+        let me = fe.finish_and_export("balance");
+
+        let mut fe = me.func(Arity(2), 2);
+        let (from, spender) = (fe.args[0].0, fe.args[1].0);
+        let (key, entry) = (fe.locals[0].0, fe.locals[1].0);
+        t.allowance_key(&mut fe, from, spender, key);
+        t.read_allowance(&mut fe, key, entry);
+        let me = fe.finish_and_export("allowance");
+
+        let mut fe = me.func(Arity(4), 1);
+        let (from, spender, amount, live_until) =
+            (fe.args[0].0, fe.args[1].0, fe.args[2].0, fe.args[3].0);
+        let key = fe.locals[0].0;
+        t.check_amount(&mut fe, amount);
+        t.require_auth(&mut fe, from);
+        fe.local_get(amount)
+            .i64_const(Sep41TokenEmitter::I128_TAG)
+            .i64_gt_s()
+            .local_get(live_until)
+            .call_func(t.get_ledger_sequence)
+            .i64_lt_u()
+            .insn(&Instruction::I32And)
+            .if_then_trap();
+        t.allowance_key(&mut fe, from, spender, key);
+        fe.local_get(key);
+        t.vec(&mut fe, &[amount.into(), live_until.into()]);
+        fe.push(Sep41TokenEmitter::ALLOWANCE_STORAGE)
+            .call_func(t.put_contract_data)
+            .drop();
+        t.event_and_return(&mut fe, "approve", &[from, spender], |fe| {
+            t.vec(fe, &[amount.into(), live_until.into()])
+        });
+        let me = fe.finish_and_export("approve");
+
+        let mut fe = me.func(Arity(3), 1);
+        let (from, to, amount) = (fe.args[0].0, fe.args[1].0, fe.args[2].0);
+        let balance = fe.locals[0].0;
+        t.check_amount(&mut fe, amount);
+        t.require_auth(&mut fe, from);
+        t.spend_balance(&mut fe, from, amount, balance);
+        t.receive_balance(&mut fe, to, amount);
+        t.event_and_return(&mut fe, "transfer", &[from, to], |fe| {
+            fe.local_get(amount);
+        });
+        let me = fe.finish_and_export("transfer");
+
+        let mut fe = me.func(Arity(4), 3);
+        let (spender, from, to, amount) = (fe.args[0].0, fe.args[1].0, fe.args[2].0, fe.args[3].0);
+        let locals = [fe.locals[0].0, fe.locals[1].0, fe.locals[2].0];
+        t.check_amount(&mut fe, amount);
+        t.require_auth(&mut fe, spender);
+        t.spend_allowance(&mut fe, from, spender, amount, locals);
+        t.spend_balance(&mut fe, from, amount, locals[2]);
+        t.receive_balance(&mut fe, to, amount);
+        t.event_and_return(&mut fe, "transfer", &[from, to], |fe| {
+            fe.local_get(amount);
+        });
+        let me = fe.finish_and_export("transfer_from");
+
+        let mut fe = me.func(Arity(2), 1);
+        let (from, amount) = (fe.args[0].0, fe.args[1].0);
+        let balance = fe.locals[0].0;
+        t.check_amount(&mut fe, amount);
+        t.require_auth(&mut fe, from);
+        t.spend_balance(&mut fe, from, amount, balance);
+        t.event_and_return(&mut fe, "burn", &[from], |fe| {
+            fe.local_get(amount);
+        });
+        let me = fe.finish_and_export("burn");
+
+        let mut fe = me.func(Arity(3), 3);
+        let (spender, from, amount) = (fe.args[0].0, fe.args[1].0, fe.args[2].0);
+        let locals = [fe.locals[0].0, fe.locals[1].0, fe.locals[2].0];
+        t.check_amount(&mut fe, amount);
+        t.require_auth(&mut fe, spender);
+        t.spend_allowance(&mut fe, from, spender, amount, locals);
+        t.spend_balance(&mut fe, from, amount, locals[2]);
+        t.event_and_return(&mut fe, "burn", &[from], |fe| {
+            fe.local_get(amount);
+        });
+        let me = fe.finish_and_export("burn_from");
+
+        let mut fe = me.func(Arity(2), 0);
+        let (to, amount) = (fe.args[0].0, fe.args[1].0);
+        t.check_amount(&mut fe, amount);
+        t.receive_balance(&mut fe, to, amount);
+        fe.push(Val::VOID.to_val());
+        let me = fe.finish_and_export("mint");
+
+        let mut fe = me.func(Arity(0), 0);
+        fe.push(U32Val::from(7));
+        let me = fe.finish_and_export("decimals");
+
+        let mut me = me;
+        for name in ["name", "symbol"] {
+            let mut fe = me.func(Arity(0), 0);
+            fe.push(U32Val::from(0))
+                .push(U32Val::from(NAME.len() as u32))
+                .call_func(t.string_new_from_linear_memory);
+            me = fe.finish_and_export(name);
+        }
+        me.define_data_segment(0, NAME.to_vec());
+        me.finish()
+    }
 }
 
 #[allow(clippy::type_complexity)]
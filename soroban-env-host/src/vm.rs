@@ -13,6 +13,8 @@ mod fuel_refillable;
 mod func_info;
 mod module_cache;
 mod parsed_module;
+#[cfg(any(test, feature = "testutils"))]
+mod profiling;
 
 #[cfg(feature = "bench")]
 pub(crate) use dispatch::dummy0;
@@ -34,6 +36,8 @@ use std::{cell::RefCell, collections::BTreeSet, rc::Rc, sync::Arc};
 
 use fuel_refillable::FuelRefillable;
 use func_info::HOST_FUNCTIONS;
+#[cfg(any(test, feature = "testutils"))]
+pub(crate) use profiling::{ProfiledModule, WasmProfiler};
 
 pub use module_cache::ModuleCache;
 pub use parsed_module::{
@@ -91,6 +95,9 @@ pub struct Vm {
     wasmi_store: RefCell<wasmi::Store<Host>>,
     wasmi_instance: wasmi::Instance,
     pub(crate) wasmi_memory: Option<wasmi::Memory>,
+    // Only set for the VMs instantiated with Wasm profiling enabled.
+    #[cfg(any(test, feature = "testutils"))]
+    pub(crate) wasm_profiler: RefCell<Option<WasmProfiler>>,
 }
 
 impl std::hash::Hash for Vm {
@@ -214,6 +221,8 @@ impl Vm {
             wasmi_store: RefCell::new(wasmi_store),
             wasmi_instance,
             wasmi_memory,
+            #[cfg(any(test, feature = "testutils"))]
+            wasm_profiler: RefCell::new(None),
         }))
     }
 
@@ -343,6 +352,8 @@ impl Vm {
         self.wasmi_store
             .try_borrow_mut_or_err()?
             .return_fuel_to_host(host)?;
+        #[cfg(any(test, feature = "testutils"))]
+        self.report_wasm_profile(host)?;
 
         if let Err(e) = res {
             use std::borrow::Cow;
@@ -353,6 +364,9 @@ impl Vm {
             match e {
                 wasmi::Error::Trap(trap) => {
                     if let Some(code) = trap.trap_code() {
+                        if matches!(code, wasmi::core::TrapCode::OutOfFuel) {
+                            host.as_budget().set_out_of_fuel()?;
+                        }
                         let err = code.into();
                         let mut msg = Cow::Borrowed("VM call trapped");
                         host.with_debug_mode(|| {
@@ -36,6 +36,8 @@ pub(crate) trait FuelRefillable {
         let fuel = self.fuel_consumed()?;
         host.as_budget()
             .bulk_charge(ContractCostType::WasmInsnExec, fuel, None)?;
+        #[cfg(any(test, feature = "testutils"))]
+        host.with_current_wasm_profiler(|p| p.charge_fuel(fuel))?;
         self.reset_fuel()
     }
 }
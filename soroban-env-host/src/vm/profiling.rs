@@ -0,0 +1,527 @@
+//! Per-function profiling of the fuel consumed by contract Wasm.
+//!
+//! wasmi doesn't expose the call stack of the running code, so the profiled
+//! contracts are run from an instrumented copy of their Wasm module instead.
+//! The instrumentation imports two hooks from the host: `enter`, called with
+//! the (original) index of the function at the start of every defined
+//! function, and `resume`, called after every call to a defined function
+//! returns. Every hook call returns the fuel consumed so far to the host, just
+//! like the host function dispatch does, so the host can attribute all the
+//! fuel to the function on top of the profiled call stack.
+//!
+//! The hook calls consume fuel themselves. That fuel is included in the
+//! profile, as the instrumented code is what actually runs, and it is also
+//! reported separately, so that it can be told apart from the fuel the
+//! original code would consume.
+//!
+//! The function names come from the `name` custom section, when present.
+use std::{collections::BTreeMap, rc::Rc, sync::Arc};
+
+use wasmi::{core::Trap, Caller};
+use wasmparser::{
+    BinaryReader, ElementItems, ExternalKind, Name, NameSectionReader, Operator, Parser,
+    Payload::*, TypeRef,
+};
+
+use super::{FuelRefillable, ParsedModule, VersionedContractCodeCostInputs, Vm};
+use crate::{
+    host::{error::TryBorrowOrErr, invocation_metering::WasmFunctionProfile},
+    xdr::{ContractId, ScErrorCode, ScErrorType},
+    ErrorHandler, Host, HostError,
+};
+
+const PROFILE_HOOKS_MODULE: &str = "profile";
+const PROFILE_ENTER_FN: &str = "enter";
+const PROFILE_RESUME_FN: &str = "resume";
+
+// Function types of the hooks: `enter: (i32) -> ()` and `resume: () -> ()`.
+const PROFILE_ENTER_TYPE: &[u8] = &[0x60, 0x01, 0x7f, 0x00];
+const PROFILE_RESUME_TYPE: &[u8] = &[0x60, 0x00, 0x00];
+
+const OP_CALL: u8 = 0x10;
+const OP_I32_CONST: u8 = 0x41;
+
+#[derive(Clone, Copy, Default)]
+struct FunctionCost {
+    calls: u64,
+    fuel: u64,
+}
+
+/// Call stack and fuel consumption of the functions of a single profiled VM.
+#[derive(Default)]
+pub(crate) struct WasmProfiler {
+    names: BTreeMap<u32, String>,
+    stack: Vec<u32>,
+    // Fuel consumed before the first function has been entered.
+    pending_fuel: u64,
+    functions: BTreeMap<u32, FunctionCost>,
+    // Fuel consumed by the instructions calling `enter` (`i32.const` and
+    // `call`) and `resume` (`call`).
+    enter_hook_fuel: u64,
+    resume_hook_fuel: u64,
+    // Total fuel consumed by the hook calls.
+    hook_fuel: u64,
+}
+
+impl WasmProfiler {
+    fn new(names: BTreeMap<u32, String>, fuel_costs: &wasmi::FuelCosts) -> Self {
+        Self {
+            names,
+            enter_hook_fuel: fuel_costs.base.saturating_add(fuel_costs.call),
+            resume_hook_fuel: fuel_costs.call,
+            ..Default::default()
+        }
+    }
+
+    fn enter(&mut self, func_index: u32) {
+        self.hook_fuel = self.hook_fuel.saturating_add(self.enter_hook_fuel);
+        let cost = self.functions.entry(func_index).or_default();
+        cost.calls = cost.calls.saturating_add(1);
+        if self.stack.is_empty() {
+            cost.fuel = cost.fuel.saturating_add(self.pending_fuel);
+            self.pending_fuel = 0;
+        }
+        self.stack.push(func_index);
+    }
+
+    fn resume(&mut self) {
+        self.hook_fuel = self.hook_fuel.saturating_add(self.resume_hook_fuel);
+        self.stack.pop();
+    }
+
+    pub(crate) fn charge_fuel(&mut self, fuel: u64) {
+        match self.stack.last() {
+            Some(func_index) => {
+                let cost = self.functions.entry(*func_index).or_default();
+                cost.fuel = cost.fuel.saturating_add(fuel);
+            }
+            None => self.pending_fuel = self.pending_fuel.saturating_add(fuel),
+        }
+    }
+
+    // Returns the profile collected since the last call together with the
+    // fuel consumed by the hooks and starts over.
+    fn take_profile(&mut self) -> (Vec<WasmFunctionProfile>, u64) {
+        self.stack.clear();
+        self.pending_fuel = 0;
+        let profile = std::mem::take(&mut self.functions)
+            .into_iter()
+            .map(|(function_index, cost)| WasmFunctionProfile {
+                function_index,
+                name: self.names.get(&function_index).cloned(),
+                calls: cost.calls,
+                fuel: cost.fuel,
+            })
+            .collect();
+        (profile, std::mem::take(&mut self.hook_fuel))
+    }
+}
+
+fn profile_enter(mut caller: Caller<Host>, func_index: i32) -> Result<(), Trap> {
+    let host = caller.data().clone();
+    // wasmi charges the fuel of a basic block when entering it, so the fuel
+    // consumed since the last hook call is mostly the first block of the
+    // entered function. Enter it before returning the fuel to the host.
+    host.with_current_wasm_profiler(|p| p.enter(func_index as u32))?;
+    FuelRefillable::return_fuel_to_host(&mut caller, &host)?;
+    FuelRefillable::add_fuel_to_vm(&mut caller, &host)?;
+    Ok(())
+}
+
+fn profile_resume(mut caller: Caller<Host>) -> Result<(), Trap> {
+    let host = caller.data().clone();
+    FuelRefillable::return_fuel_to_host(&mut caller, &host)?;
+    host.with_current_wasm_profiler(|p| p.resume())?;
+    FuelRefillable::add_fuel_to_vm(&mut caller, &host)?;
+    Ok(())
+}
+
+/// Instrumented copy of a contract Wasm module. It is shared by all the
+/// profiled VMs running the same Wasm, so that every contract call doesn't
+/// have to instrument and parse the module again.
+pub(crate) struct ProfiledModule {
+    parsed_module: Arc<ParsedModule>,
+    names: BTreeMap<u32, String>,
+}
+
+impl ProfiledModule {
+    pub(crate) fn new(
+        host: &Host,
+        wasm: &[u8],
+        cost_inputs: VersionedContractCodeCostInputs,
+    ) -> Result<Rc<Self>, HostError> {
+        let (instrumented, names) = instrument_wasm(host, wasm)?;
+        let parsed_module =
+            ParsedModule::new_with_isolated_engine(host, &instrumented, cost_inputs)?;
+        Ok(Rc::new(Self {
+            parsed_module,
+            names,
+        }))
+    }
+}
+
+impl Vm {
+    /// Instantiates a VM running an instrumented copy of a Wasm module that
+    /// reports the fuel consumed by every function to the host. The caller is
+    /// responsible for charging the budget for the instantiation.
+    pub(crate) fn new_profiled(
+        host: &Host,
+        contract_id: ContractId,
+        module: &ProfiledModule,
+    ) -> Result<Rc<Self>, HostError> {
+        let parsed_module = module.parsed_module.clone();
+        let mut linker = parsed_module.make_wasmi_linker(host)?;
+        host.map_err(
+            linker
+                .func_wrap(PROFILE_HOOKS_MODULE, PROFILE_ENTER_FN, profile_enter)
+                .map_err(wasmi::Error::Linker),
+        )?;
+        host.map_err(
+            linker
+                .func_wrap(PROFILE_HOOKS_MODULE, PROFILE_RESUME_FN, profile_resume)
+                .map_err(wasmi::Error::Linker),
+        )?;
+        let vm =
+            Self::from_parsed_module_and_wasmi_linker(host, contract_id, parsed_module, &linker)?;
+        *vm.wasm_profiler.try_borrow_mut_or_err()? = Some(WasmProfiler::new(
+            module.names.clone(),
+            &host.budget_ref().get_wasmi_fuel_costs()?,
+        ));
+        Ok(vm)
+    }
+
+    // Moves the profile collected by this VM (if it is profiled) to the
+    // current metered invocation.
+    pub(crate) fn report_wasm_profile(&self, host: &Host) -> Result<(), HostError> {
+        let (profile, hook_fuel) = match self.wasm_profiler.try_borrow_mut_or_err()?.as_mut() {
+            Some(profiler) => profiler.take_profile(),
+            None => return Ok(()),
+        };
+        host.record_wasm_profile(profile, hook_fuel)
+    }
+}
+
+fn write_u32(out: &mut Vec<u8>, mut v: u32) {
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+        if v == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_i32(out: &mut Vec<u8>, mut v: i32) {
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+        if (v == 0 && byte & 0x40 == 0) || (v == -1 && byte & 0x40 != 0) {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_u32(out, bytes.len() as u32);
+    out.extend_from_slice(bytes);
+}
+
+fn write_section(out: &mut Vec<u8>, id: u8, contents: &[u8]) {
+    out.push(id);
+    write_bytes(out, contents);
+}
+
+fn read_function_names(
+    data: &[u8],
+    offset: usize,
+) -> Result<BTreeMap<u32, String>, wasmparser::BinaryReaderError> {
+    let mut names = BTreeMap::new();
+    for subsection in NameSectionReader::new(data, offset) {
+        if let Name::Function(map) = subsection? {
+            for naming in map {
+                let naming = naming?;
+                names.insert(naming.index, naming.name.to_string());
+            }
+        }
+    }
+    Ok(names)
+}
+
+struct Instrumenter<'a> {
+    host: &'a Host,
+    wasm: &'a [u8],
+    out: Vec<u8>,
+    num_types: u32,
+    num_imported_funcs: u32,
+    types_written: bool,
+    imports_written: bool,
+}
+
+impl<'a> Instrumenter<'a> {
+    fn unsupported(&self, msg: &'static str) -> HostError {
+        self.host
+            .err(ScErrorType::WasmVm, ScErrorCode::InvalidInput, msg, &[])
+    }
+
+    fn enter_hook(&self) -> u32 {
+        self.num_imported_funcs
+    }
+
+    fn resume_hook(&self) -> u32 {
+        self.num_imported_funcs + 1
+    }
+
+    fn is_imported(&self, func_index: u32) -> bool {
+        func_index < self.num_imported_funcs
+    }
+
+    // The hooks are appended to the imported functions, which shifts the
+    // indices of all the defined functions.
+    fn shift(&self, func_index: u32) -> u32 {
+        if self.is_imported(func_index) {
+            func_index
+        } else {
+            func_index + 2
+        }
+    }
+
+    // Splits a vector-shaped section into its item count and its items.
+    fn section_items(&self, range: std::ops::Range<usize>) -> Result<(u32, &'a [u8]), HostError> {
+        let mut reader = BinaryReader::new_with_offset(&self.wasm[range.clone()], range.start);
+        let count = self.host.map_err(reader.read_var_u32())?;
+        Ok((count, &self.wasm[reader.original_position()..range.end]))
+    }
+
+    fn write_types(&mut self, count: u32, items: &[u8]) {
+        let mut contents = vec![];
+        write_u32(&mut contents, count + 2);
+        contents.extend_from_slice(items);
+        contents.extend_from_slice(PROFILE_ENTER_TYPE);
+        contents.extend_from_slice(PROFILE_RESUME_TYPE);
+        write_section(&mut self.out, 1, &contents);
+        self.num_types = count;
+        self.types_written = true;
+    }
+
+    fn write_imports(&mut self, count: u32, items: &[u8]) {
+        if !self.types_written {
+            self.write_types(0, &[]);
+        }
+        let mut contents = vec![];
+        write_u32(&mut contents, count + 2);
+        contents.extend_from_slice(items);
+        for (name, ty) in [
+            (PROFILE_ENTER_FN, self.num_types),
+            (PROFILE_RESUME_FN, self.num_types + 1),
+        ] {
+            write_bytes(&mut contents, PROFILE_HOOKS_MODULE.as_bytes());
+            write_bytes(&mut contents, name.as_bytes());
+            contents.push(0x00);
+            write_u32(&mut contents, ty);
+        }
+        write_section(&mut self.out, 2, &contents);
+        self.imports_written = true;
+    }
+
+    // Makes sure that the hooks have been declared before any section that
+    // follows the import section.
+    fn write_hooks(&mut self) {
+        if !self.imports_written {
+            self.write_imports(0, &[]);
+        }
+    }
+
+    fn copy_section(&mut self, id: u8, range: std::ops::Range<usize>) {
+        write_section(&mut self.out, id, &self.wasm[range]);
+    }
+
+    fn instrument_body(
+        &self,
+        func_index: u32,
+        body: &wasmparser::FunctionBody<'a>,
+    ) -> Result<Vec<u8>, HostError> {
+        let host = self.host;
+        let mut ops = host.map_err(body.get_operators_reader())?;
+        let mut res = self.wasm[body.range().start..ops.original_position()].to_vec();
+        res.push(OP_I32_CONST);
+        write_i32(&mut res, func_index as i32);
+        res.push(OP_CALL);
+        write_u32(&mut res, self.enter_hook());
+        while !ops.eof() {
+            let start = ops.original_position();
+            let op = host.map_err(ops.read())?;
+            match op {
+                Operator::Call { function_index } => {
+                    res.push(OP_CALL);
+                    write_u32(&mut res, self.shift(function_index));
+                    // Imported functions don't call `enter`, so there is
+                    // nothing to pop from the profiled call stack.
+                    if !self.is_imported(function_index) {
+                        res.push(OP_CALL);
+                        write_u32(&mut res, self.resume_hook());
+                    }
+                }
+                Operator::CallIndirect { .. } => {
+                    res.extend_from_slice(&self.wasm[start..ops.original_position()]);
+                    res.push(OP_CALL);
+                    write_u32(&mut res, self.resume_hook());
+                }
+                Operator::RefFunc { .. } => {
+                    return Err(self.unsupported("profiling: unsupported function reference"))
+                }
+                _ => res.extend_from_slice(&self.wasm[start..ops.original_position()]),
+            }
+        }
+        Ok(res)
+    }
+}
+
+// Returns the instrumented module and the names of its functions.
+fn instrument_wasm(
+    host: &Host,
+    wasm: &[u8],
+) -> Result<(Vec<u8>, BTreeMap<u32, String>), HostError> {
+    let mut ins = Instrumenter {
+        host,
+        wasm,
+        out: vec![],
+        num_types: 0,
+        num_imported_funcs: 0,
+        types_written: false,
+        imports_written: false,
+    };
+    let mut names = BTreeMap::new();
+    let mut code = vec![];
+    let mut code_count = 0;
+    let mut next_body_index = 0;
+    for payload in Parser::new(0).parse_all(wasm) {
+        match host.map_err(payload)? {
+            Version { range, .. } => ins.out.extend_from_slice(&wasm[range]),
+            TypeSection(s) => {
+                let (count, items) = ins.section_items(s.range())?;
+                ins.write_types(count, items);
+            }
+            ImportSection(s) => {
+                for import in s.clone() {
+                    if let TypeRef::Func(_) = host.map_err(import)?.ty {
+                        ins.num_imported_funcs += 1;
+                    }
+                }
+                let (count, items) = ins.section_items(s.range())?;
+                ins.write_imports(count, items);
+            }
+            FunctionSection(s) => {
+                ins.write_hooks();
+                next_body_index = ins.num_imported_funcs;
+                ins.copy_section(3, s.range());
+            }
+            TableSection(s) => {
+                ins.write_hooks();
+                ins.copy_section(4, s.range());
+            }
+            MemorySection(s) => {
+                ins.write_hooks();
+                ins.copy_section(5, s.range());
+            }
+            GlobalSection(s) => {
+                ins.write_hooks();
+                ins.copy_section(6, s.range());
+            }
+            ExportSection(s) => {
+                ins.write_hooks();
+                let mut contents = vec![];
+                write_u32(&mut contents, s.count());
+                for export in s {
+                    let export = host.map_err(export)?;
+                    write_bytes(&mut contents, export.name.as_bytes());
+                    let (kind, index) = match export.kind {
+                        ExternalKind::Func => (0x00, ins.shift(export.index)),
+                        ExternalKind::Table => (0x01, export.index),
+                        ExternalKind::Memory => (0x02, export.index),
+                        ExternalKind::Global => (0x03, export.index),
+                        ExternalKind::Tag => (0x04, export.index),
+                    };
+                    contents.push(kind);
+                    write_u32(&mut contents, index);
+                }
+                write_section(&mut ins.out, 7, &contents);
+            }
+            StartSection { func, .. } => {
+                ins.write_hooks();
+                let mut contents = vec![];
+                write_u32(&mut contents, ins.shift(func));
+                write_section(&mut ins.out, 8, &contents);
+            }
+            ElementSection(s) => {
+                ins.write_hooks();
+                let mut contents = vec![];
+                write_u32(&mut contents, s.count());
+                for element in s.into_iter_with_offsets() {
+                    let (start, element) = host.map_err(element)?;
+                    let ElementItems::Functions(funcs) = element.items else {
+                        return Err(ins.unsupported("profiling: unsupported element segment"));
+                    };
+                    // Only the function indices at the end of the segment
+                    // need to be rewritten.
+                    contents.extend_from_slice(&wasm[start..funcs.range().start]);
+                    write_u32(&mut contents, funcs.count());
+                    for func in funcs {
+                        let func = host.map_err(func)?;
+                        // Tables may only contain the defined functions, as
+                        // `call_indirect` always resumes the caller.
+                        if ins.is_imported(func) {
+                            return Err(ins.unsupported("profiling: imported function in a table"));
+                        }
+                        write_u32(&mut contents, ins.shift(func));
+                    }
+                }
+                write_section(&mut ins.out, 9, &contents);
+            }
+            DataCountSection { range, .. } => {
+                ins.write_hooks();
+                ins.copy_section(12, range);
+            }
+            CodeSectionStart { count, .. } => {
+                ins.write_hooks();
+                code_count = count;
+                write_u32(&mut code, count);
+                if count == 0 {
+                    write_section(&mut ins.out, 10, &code);
+                }
+            }
+            CodeSectionEntry(body) => {
+                let instrumented = ins.instrument_body(next_body_index, &body)?;
+                write_bytes(&mut code, &instrumented);
+                next_body_index += 1;
+                code_count -= 1;
+                if code_count == 0 {
+                    write_section(&mut ins.out, 10, &code);
+                }
+            }
+            DataSection(s) => {
+                ins.write_hooks();
+                ins.copy_section(11, s.range());
+            }
+            CustomSection(s) => {
+                if s.name() == "name" {
+                    // The names are only used for reporting, so a malformed
+                    // name section is ignored. The section itself is dropped
+                    // as it refers to the original function indices.
+                    names = read_function_names(s.data(), s.data_offset()).unwrap_or_default();
+                } else {
+                    let mut contents = vec![];
+                    write_bytes(&mut contents, s.name().as_bytes());
+                    contents.extend_from_slice(s.data());
+                    write_section(&mut ins.out, 0, &contents);
+                }
+            }
+            End(_) => ins.write_hooks(),
+            _ => return Err(ins.unsupported("profiling: unsupported Wasm section")),
+        }
+    }
+    Ok((ins.out, names))
+}
@@ -58,7 +58,15 @@
 //! corresponding set of tests with the same test vectors in stellar-core to
 //! confirm identical behaviour with libsodium.
 
-use ed25519_dalek::{Signature, VerifyingKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+use curve25519_dalek::{
+    constants::{ED25519_BASEPOINT_POINT, EIGHT_TORSION},
+    edwards::CompressedEdwardsY,
+    scalar::Scalar,
+};
+use ed25519_dalek::{
+    Signature, Signer, SigningKey, VerifyingKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH,
+};
+use sha2::{Digest, Sha512};
 use soroban_env_common::{Env, EnvBase};
 use soroban_env_host::{
     xdr::{ScErrorCode, ScErrorType},
@@ -144,6 +152,166 @@ fn check_iacr_2020_1244_test_vectors() {
     }
 }
 
+// `verify_sig_ed25519_batch` must accept or reject exactly the same
+// signatures as `verify_sig_ed25519`. In particular it must not use the
+// cofactored ("batched") equality check, which would accept IACR 2020/1244
+// cases 4 and 5.
+fn verify_batch(host: &Host, inputs: &[(&[u8], &[u8], &[u8])]) -> bool {
+    let mut ks = vec![];
+    let mut xs = vec![];
+    let mut ss = vec![];
+    for (k, x, s) in inputs {
+        ks.push(host.bytes_new_from_slice(k).unwrap().to_val());
+        xs.push(host.bytes_new_from_slice(x).unwrap().to_val());
+        ss.push(host.bytes_new_from_slice(s).unwrap().to_val());
+    }
+    let res = host.verify_sig_ed25519_batch(
+        host.vec_new_from_slice(&ks).unwrap(),
+        host.vec_new_from_slice(&xs).unwrap(),
+        host.vec_new_from_slice(&ss).unwrap(),
+    );
+    match res {
+        Ok(_) => true,
+        Err(e) => {
+            assert!(
+                e.error.is_type(ScErrorType::Crypto) && e.error.is_code(ScErrorCode::InvalidInput)
+            );
+            false
+        }
+    }
+}
+
+fn valid_signature(msg: &[u8]) -> ([u8; PUBLIC_KEY_LENGTH], [u8; SIGNATURE_LENGTH]) {
+    let signing_key = SigningKey::from_bytes(&[0x11; 32]);
+    (
+        signing_key.verifying_key().to_bytes(),
+        signing_key.sign(msg).to_bytes(),
+    )
+}
+
+#[test]
+fn check_batch_verification_matches_strict_verification() {
+    if Host::current_test_protocol() < 25 {
+        return;
+    }
+    let (valid_key, valid_sig) = valid_signature(b"batch");
+
+    for (i, test_vector) in IACR_2020_1244_TEST_VECTORS.iter().enumerate() {
+        println!("IACR 2020/1244 Test vector {}", i);
+        let host = Host::test_host();
+        let public_key = hex::decode(test_vector.pub_key).unwrap();
+        let signature = hex::decode(test_vector.signature).unwrap();
+        let message = hex::decode(test_vector.message).unwrap();
+        let single = verify_batch(&host, &[(&public_key[..], &message[..], &signature[..])]);
+        assert_eq!(single, !test_vector.should_fail);
+        // Mixing in a valid signature doesn't change the outcome.
+        for inputs in [
+            [
+                (&valid_key[..], &b"batch"[..], &valid_sig[..]),
+                (&public_key[..], &message[..], &signature[..]),
+            ],
+            [
+                (&public_key[..], &message[..], &signature[..]),
+                (&valid_key[..], &b"batch"[..], &valid_sig[..]),
+            ],
+        ] {
+            assert_eq!(verify_batch(&host, &inputs), !test_vector.should_fail);
+        }
+    }
+
+    const ZIP215_TEST_MESSAGE: &[u8] = b"Zcash";
+    for (i, test_vector) in ZCASH_TEST_VECTORS.iter().enumerate() {
+        println!("Zcash Test vector {}", i);
+        let host = Host::test_host();
+        let public_key = hex::decode(test_vector.public_key).unwrap();
+        let signature = hex::decode(test_vector.signature).unwrap();
+        assert!(!verify_batch(
+            &host,
+            &[
+                (&valid_key[..], &b"batch"[..], &valid_sig[..]),
+                (&public_key[..], ZIP215_TEST_MESSAGE, &signature[..]),
+            ]
+        ));
+    }
+}
+
+// Signs `msg` with a key that has a torsion component, choosing the nonce such
+// that `verify_strict` accepts (when the torsion components of the key and of
+// `R` cancel out) or rejects the signature.
+fn mixed_order_signature(
+    key_torsion: usize,
+    msg: &[u8],
+    accepted: bool,
+) -> ([u8; PUBLIC_KEY_LENGTH], [u8; SIGNATURE_LENGTH]) {
+    let a = Scalar::from(0x1234_u64 + key_torsion as u64);
+    let mixed_key = ED25519_BASEPOINT_POINT * a + EIGHT_TORSION[key_torsion];
+    let key = mixed_key.compress().to_bytes();
+    for nonce in 1_u64.. {
+        let r = Scalar::from(nonce);
+        let r_point = (ED25519_BASEPOINT_POINT * r + EIGHT_TORSION[nonce as usize % 8]).compress();
+        let k = Scalar::from_hash(
+            Sha512::new()
+                .chain_update(r_point.as_bytes())
+                .chain_update(key)
+                .chain_update(msg),
+        );
+        let mut signature = [0; SIGNATURE_LENGTH];
+        signature[..32].copy_from_slice(r_point.as_bytes());
+        signature[32..].copy_from_slice((r + k * a).as_bytes());
+        let vk = VerifyingKey::from_bytes(&key).unwrap();
+        if vk
+            .verify_strict(msg, &Signature::from_bytes(&signature))
+            .is_ok()
+            == accepted
+        {
+            return (key, signature);
+        }
+    }
+    unreachable!()
+}
+
+#[test]
+fn check_batch_verification_of_mixed_order_keys() {
+    if Host::current_test_protocol() < 25 {
+        return;
+    }
+    let host = Host::test_host();
+    let (valid_key, valid_sig) = valid_signature(b"batch");
+    for key_torsion in 1..8 {
+        let (key, accepted_sig) = mixed_order_signature(key_torsion, b"mixed", true);
+        let (_, rejected_sig) = mixed_order_signature(key_torsion, b"mixed", false);
+        assert!(verify_batch(
+            &host,
+            &[
+                (&valid_key[..], &b"batch"[..], &valid_sig[..]),
+                (&key[..], &b"mixed"[..], &accepted_sig[..]),
+            ]
+        ));
+        assert!(!verify_batch(
+            &host,
+            &[
+                (&valid_key[..], &b"batch"[..], &valid_sig[..]),
+                (&key[..], &b"mixed"[..], &accepted_sig[..]),
+                (&key[..], &b"mixed"[..], &rejected_sig[..]),
+            ]
+        ));
+    }
+
+    // A torsion-free key with a mixed-order `R`.
+    let (_, mut mixed_r_sig) = valid_signature(b"mixed r");
+    let r = CompressedEdwardsY(mixed_r_sig[..32].try_into().unwrap())
+        .decompress()
+        .unwrap();
+    mixed_r_sig[..32].copy_from_slice((r + EIGHT_TORSION[1]).compress().as_bytes());
+    assert!(!verify_batch(
+        &host,
+        &[
+            (&valid_key[..], &b"batch"[..], &valid_sig[..]),
+            (&valid_key[..], &b"mixed r"[..], &mixed_r_sig[..]),
+        ]
+    ));
+}
+
 struct ZcashTestVector {
     public_key: &'static str,
     signature: &'static str,
@@ -2,8 +2,10 @@ use soroban_env_common::xdr::{Hash, LedgerEntry, LedgerEntryData, LedgerEntryExt
 use soroban_env_host::{
     fees::{
         compute_rent_fee, compute_rent_write_fee_per_1kb, compute_transaction_resource_fee,
-        FeeConfiguration, LedgerEntryRentChange, RentFeeConfiguration, RentWriteFeeConfiguration,
-        TransactionResources, MINIMUM_RENT_WRITE_FEE_PER_1KB, TTL_ENTRY_SIZE,
+        project_rent_fee, FeeConfiguration, LedgerEntryRentChange, ProjectedRentExtension,
+        RentFeeConfiguration, RentProjection, RentProjectionEntry, RentWriteFeeConfiguration,
+        SorobanStateSizeProjection, TransactionResources, MINIMUM_RENT_WRITE_FEE_PER_1KB,
+        TTL_ENTRY_SIZE,
     },
     xdr::TtlEntry,
     DEFAULT_XDR_RW_LIMITS,
@@ -1069,3 +1071,227 @@ fn test_compute_write_fee_clamp_after_target() {
         MINIMUM_RENT_WRITE_FEE_PER_1KB
     );
 }
+
+fn rent_projection_fee_config() -> RentFeeConfiguration {
+    RentFeeConfiguration {
+        fee_per_write_entry: 10,
+        fee_per_rent_1kb: 1000,
+        fee_per_write_1kb: 500,
+        persistent_rent_rate_denominator: 10_000,
+        temporary_rent_rate_denominator: 100_000,
+    }
+}
+
+#[test]
+fn test_rent_projection_with_fixed_rent_fee() {
+    let fee_config = rent_projection_fee_config();
+    let entry = RentProjectionEntry {
+        is_persistent: true,
+        is_code_entry: false,
+        size_bytes: 1024,
+        live_until_ledger: 100_000,
+    };
+
+    // Single extension covering the whole horizon.
+    assert_eq!(
+        project_rent_fee(
+            std::slice::from_ref(&entry),
+            &fee_config,
+            None,
+            50_000,
+            100_000,
+            200_000
+        ),
+        RentProjection {
+            // Rent: ceil(1024 * 1000 * 50_000 / (10_000 * 1024)) (=5000) +
+            // TTL entry write bytes: ceil(500 * 48 / 1024) (=24) +
+            // TTL entry write: 10
+            total_fee: 5000 + 24 + 10,
+            extensions: vec![ProjectedRentExtension {
+                ledger_seq: 50_000,
+                extended_entries: 1,
+                fee_per_rent_1kb: 1000,
+                fee: 5034,
+            }],
+        }
+    );
+    // The projection matches `compute_rent_fee` for the same extension.
+    assert_eq!(
+        project_rent_fee(
+            std::slice::from_ref(&entry),
+            &fee_config,
+            None,
+            50_000,
+            100_000,
+            200_000
+        )
+        .total_fee,
+        compute_rent_fee(
+            &[LedgerEntryRentChange {
+                is_persistent: true,
+                is_code_entry: false,
+                old_size_bytes: 1024,
+                new_size_bytes: 1024,
+                old_live_until_ledger: 100_000,
+                new_live_until_ledger: 150_000,
+            }],
+            &fee_config,
+            50_000,
+        )
+    );
+
+    // More frequent extensions pay for more TTL entry writes. No extensions
+    // are needed until the entry is about to expire.
+    assert_eq!(
+        project_rent_fee(
+            std::slice::from_ref(&entry),
+            &fee_config,
+            None,
+            50_000,
+            100_000,
+            25_000
+        ),
+        RentProjection {
+            // Rent: 2 * ceil(1024 * 1000 * 25_000 / (10_000 * 1024)) (=2 * 2500) +
+            // TTL entry writes: 2 * 34
+            total_fee: 5000 + 68,
+            extensions: vec![
+                ProjectedRentExtension {
+                    ledger_seq: 100_000,
+                    extended_entries: 1,
+                    fee_per_rent_1kb: 1000,
+                    fee: 2534,
+                },
+                ProjectedRentExtension {
+                    ledger_seq: 125_000,
+                    extended_entries: 1,
+                    fee_per_rent_1kb: 1000,
+                    fee: 2534,
+                },
+            ],
+        }
+    );
+
+    // Entry is already live past the horizon.
+    assert_eq!(
+        project_rent_fee(&[entry], &fee_config, None, 50_000, 40_000, 10_000),
+        RentProjection::default()
+    );
+}
+
+#[test]
+fn test_rent_projection_with_mixed_entries() {
+    let fee_config = rent_projection_fee_config();
+    let entries = [
+        RentProjectionEntry {
+            is_persistent: false,
+            is_code_entry: false,
+            size_bytes: 2048,
+            live_until_ledger: 60_000,
+        },
+        RentProjectionEntry {
+            is_persistent: true,
+            is_code_entry: true,
+            size_bytes: 3072,
+            live_until_ledger: 65_000,
+        },
+        RentProjectionEntry {
+            is_persistent: true,
+            is_code_entry: false,
+            size_bytes: 4096,
+            live_until_ledger: 200_000,
+        },
+    ];
+    assert_eq!(
+        project_rent_fee(&entries, &fee_config, None, 50_000, 20_000, 100_000),
+        RentProjection {
+            // Temp entry rent: ceil(2048 * 1000 * 10_000 / (100_000 * 1024)) (=200) +
+            // Code entry rent: ceil(3072 * 1000 * 5_000 / (10_000 * 1024)) / 3 (=500) +
+            // TTL entry write bytes: ceil(500 * 96 / 1024) (=47) +
+            // TTL entry writes: 2 * 10
+            total_fee: 200 + 500 + 47 + 20,
+            extensions: vec![ProjectedRentExtension {
+                ledger_seq: 50_000,
+                extended_entries: 2,
+                fee_per_rent_1kb: 1000,
+                fee: 767,
+            }],
+        }
+    );
+}
+
+#[test]
+fn test_rent_projection_with_state_size_growth() {
+    let fee_config = rent_projection_fee_config();
+    let entries = [RentProjectionEntry {
+        is_persistent: true,
+        is_code_entry: false,
+        size_bytes: 1024,
+        live_until_ledger: 100_000,
+    }];
+    let mut state_size_projection = SorobanStateSizeProjection {
+        rent_write_fee_configuration: RentWriteFeeConfiguration {
+            state_target_size_bytes: 100_000,
+            rent_fee_1kb_state_size_low: 1000,
+            rent_fee_1kb_state_size_high: 10_000,
+            state_size_rent_fee_growth_factor: 2,
+        },
+        current_state_size_bytes: 50_000,
+        state_size_growth_bytes_per_ledger: 1,
+    };
+    assert_eq!(state_size_projection.state_size_at(75_000), 125_000);
+
+    assert_eq!(
+        project_rent_fee(
+            &entries,
+            &fee_config,
+            Some(&state_size_projection),
+            50_000,
+            100_000,
+            25_000
+        ),
+        RentProjection {
+            // State size reaches the target (100_000) at the first extension,
+            // and exceeds it by 25_000 at the second one:
+            // 10_000 + ceil(9000 * 25_000 * 2 / 100_000) (=14_500)
+            // Rent: ceil(1024 * 10_000 * 25_000 / (10_000 * 1024)) (=25_000) +
+            // ceil(1024 * 14_500 * 25_000 / (10_000 * 1024)) (=36_250) +
+            // TTL entry writes: 2 * 34
+            total_fee: 25_000 + 36_250 + 68,
+            extensions: vec![
+                ProjectedRentExtension {
+                    ledger_seq: 100_000,
+                    extended_entries: 1,
+                    fee_per_rent_1kb: 10_000,
+                    fee: 25_034,
+                },
+                ProjectedRentExtension {
+                    ledger_seq: 125_000,
+                    extended_entries: 1,
+                    fee_per_rent_1kb: 14_500,
+                    fee: 36_284,
+                },
+            ],
+        }
+    );
+
+    // Without growth the rent fee stays at the current state size rate:
+    // ceil(9000 * 50_000 / 100_000) + 1000 (=5500), and the `fee_per_rent_1kb`
+    // of the fee config is ignored.
+    state_size_projection.state_size_growth_bytes_per_ledger = 0;
+    let projection = project_rent_fee(
+        &entries,
+        &fee_config,
+        Some(&state_size_projection),
+        50_000,
+        100_000,
+        25_000,
+    );
+    assert!(projection
+        .extensions
+        .iter()
+        .all(|e| e.fee_per_rent_1kb == 5500));
+    // Rent: 2 * ceil(1024 * 5500 * 25_000 / (10_000 * 1024)) (=2 * 13_750) +
+    // TTL entry writes: 2 * 34
+    assert_eq!(projection.total_fee, 2 * 13_750 + 68);
+}
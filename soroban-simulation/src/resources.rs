@@ -4,6 +4,7 @@ use anyhow::{anyhow, ensure, Context, Result};
 
 use soroban_env_host::e2e_invoke::entry_size_for_rent;
 use soroban_env_host::{
+    budget::{CostTracker, MeteredCostComponent},
     fees::{
         compute_rent_fee, compute_transaction_resource_fee, LedgerEntryRentChange,
         TransactionResources,
@@ -11,12 +12,13 @@ use soroban_env_host::{
     ledger_info::get_key_durability,
     storage::SnapshotSource,
     xdr::{
-        BytesM, ContractDataDurability, DecoratedSignature, Duration, Hash, LedgerBounds,
-        LedgerEntryType, LedgerFootprint, LedgerKey, Memo, MuxedAccount, MuxedAccountMed25519,
-        Operation, OperationBody, Preconditions, PreconditionsV2, SequenceNumber, Signature,
-        SignatureHint, SignerKey, SignerKeyEd25519SignedPayload, SorobanResources,
-        SorobanResourcesExtV0, SorobanTransactionData, SorobanTransactionDataExt, TimeBounds,
-        TimePoint, Transaction, TransactionExt, TransactionV1Envelope, Uint256, WriteXdr,
+        BytesM, ContractCostParams, ContractCostType, ContractDataDurability, DecoratedSignature,
+        Duration, Hash, LedgerBounds, LedgerEntryType, LedgerFootprint, LedgerKey, Memo,
+        MuxedAccount, MuxedAccountMed25519, Operation, OperationBody, Preconditions,
+        PreconditionsV2, SequenceNumber, Signature, SignatureHint, SignerKey,
+        SignerKeyEd25519SignedPayload, SorobanResources, SorobanResourcesExtV0,
+        SorobanTransactionData, SorobanTransactionDataExt, TimeBounds, TimePoint, Transaction,
+        TransactionExt, TransactionV1Envelope, Uint256, WriteXdr,
     },
     LedgerInfo, DEFAULT_XDR_RW_LIMITS,
 };
@@ -32,7 +34,7 @@ impl SimulationAdjustmentConfig {
 }
 
 impl SimulationAdjustmentFactor {
-    fn adjust_u32(&self, value: u32) -> u32 {
+    pub(crate) fn adjust_u32(&self, value: u32) -> u32 {
         // `0` typically means that resource hasn't been used at all,
         // so adjusting it with an additive factor would likely waste
         // resources unnecessarily.
@@ -68,17 +70,64 @@ pub(crate) fn compute_resource_fee(
     rent_changes: &[LedgerEntryRentChange],
     adjustment_config: &SimulationAdjustmentConfig,
 ) -> i64 {
+    let (non_refundable_fee, refundable_fee, _) = compute_resource_fee_components(
+        network_config,
+        ledger_info.sequence_number,
+        resources,
+        rent_changes,
+        adjustment_config,
+    );
+    non_refundable_fee.saturating_add(refundable_fee)
+}
+
+// Returns the non-refundable fee, the adjusted refundable fee (that includes
+// the rent fee) and the unadjusted rent fee.
+pub(crate) fn compute_resource_fee_components(
+    network_config: &NetworkConfig,
+    ledger_seq: u32,
+    resources: &TransactionResources,
+    rent_changes: &[LedgerEntryRentChange],
+    adjustment_config: &SimulationAdjustmentConfig,
+) -> (i64, i64, i64) {
     let (non_refundable_fee, refundable_fee) =
         compute_transaction_resource_fee(resources, &network_config.fee_configuration);
     let rent_fee = compute_rent_fee(
         rent_changes,
         &network_config.rent_fee_configuration,
-        ledger_info.sequence_number,
+        ledger_seq,
     );
     let refundable_fee = adjustment_config
         .refundable_fee
         .adjust_i64(refundable_fee.saturating_add(rent_fee));
-    non_refundable_fee.saturating_add(refundable_fee)
+    (non_refundable_fee, refundable_fee, rent_fee)
+}
+
+// Estimates the total cost of the charges recorded in `cost_trackers` under
+// the given cost parameters.
+//
+// The trackers only keep the totals of the iterations and inputs, so the
+// linear terms are rounded once instead of once per charge. Thus the estimate
+// may exceed the cost that would be actually charged by at most one unit per
+// charge.
+pub(crate) fn estimate_cost_from_trackers(
+    cost_trackers: &[(ContractCostType, CostTracker)],
+    cost_params: &ContractCostParams,
+) -> Result<u64> {
+    let mut total: u64 = 0;
+    for (ty, tracker) in cost_trackers {
+        let entry = cost_params
+            .0
+            .get(*ty as usize)
+            .ok_or_else(|| anyhow!("cost params are missing an entry for {ty:?}"))?;
+        let model = MeteredCostComponent::try_from(entry)
+            .with_context(|| format!("invalid cost params entry for {ty:?}"))?;
+        let mut cost = model.const_term.saturating_mul(tracker.iterations);
+        if let Some(inputs) = tracker.inputs {
+            cost = cost.saturating_add(model.lin_term.saturating_mul(inputs).unscale());
+        }
+        total = total.saturating_add(cost);
+    }
+    Ok(total)
 }
 
 pub(crate) fn compute_adjusted_transaction_resources(
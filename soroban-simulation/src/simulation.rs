@@ -1,24 +1,29 @@
 use crate::network_config::NetworkConfig;
 use crate::resources::{
-    compute_adjusted_transaction_resources, compute_resource_fee, simulate_extend_ttl_op_resources,
-    simulate_restore_op_resources,
+    compute_adjusted_transaction_resources, compute_resource_fee, compute_resource_fee_components,
+    estimate_cost_from_trackers, simulate_extend_ttl_op_resources, simulate_restore_op_resources,
 };
 use crate::snapshot_source::SimulationSnapshotSource;
-use anyhow::Result;
+use anyhow::{bail, Result};
+use soroban_env_host::budget::CostTracker;
 use soroban_env_host::e2e_invoke::extract_rent_changes;
+use soroban_env_host::fees::{LedgerEntryRentChange, TransactionResources};
+use soroban_env_host::ledger_info::get_account_balance_bounds;
 use soroban_env_host::xdr::SorobanResourcesExtV0;
 use soroban_env_host::{
     e2e_invoke::invoke_host_function_in_recording_mode,
     e2e_invoke::{LedgerEntryChange, RecordingInvocationAuthMode},
     storage::SnapshotSource,
     xdr::{
-        AccountId, ContractEvent, DiagnosticEvent, HostFunction, InvokeHostFunctionOp, LedgerKey,
-        OperationBody, ScVal, SorobanAuthorizationEntry, SorobanResources, SorobanTransactionData,
-        SorobanTransactionDataExt,
+        AccountId, ContractCostType, ContractEvent, DiagnosticEvent, HostFunction,
+        InvokeHostFunctionOp, LedgerEntryData, LedgerKey, LedgerKeyAccount, OperationBody, ScVal,
+        SorobanAuthorizationEntry, SorobanResources, SorobanTransactionData,
+        SorobanTransactionDataExt, TrustLineAsset, TrustLineEntryExt,
     },
     xdr::{ExtendFootprintTtlOp, ExtensionPoint, LedgerEntry, ReadXdr, RestoreFootprintOp},
     HostError, LedgerInfo, DEFAULT_XDR_RW_LIMITS,
 };
+use std::collections::{BTreeMap, BTreeSet};
 use std::rc::Rc;
 
 /// Configures the adjustment of a simulated value (e.g. resource or fee).
@@ -79,6 +84,54 @@ pub struct InvokeHostFunctionSimulationResult {
     /// the transaction execution.
     /// Empty for failed invocations.
     pub modified_entries: Vec<LedgerEntryDiff>,
+    /// Inputs of the resource fee computation, that allow re-pricing the
+    /// simulation under a different network configuration via
+    /// `reprice_invoke_host_function_simulation`.
+    /// `None` for failed invocations.
+    pub fee_inputs: Option<InvokeHostFunctionFeeInputs>,
+}
+
+/// Inputs of the resource fee computation for a simulated
+/// `InvokeHostFunctionOp` operation.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct InvokeHostFunctionFeeInputs {
+    /// Transaction resources the resource fee has been computed for, with the
+    /// simulation adjustments applied.
+    pub transaction_resources: TransactionResources,
+    /// Rent changes of the ledger entries modified by the invocation.
+    pub rent_changes: Vec<LedgerEntryRentChange>,
+    /// Sequence number of the ledger the invocation has been simulated at,
+    /// which the rent fee is computed for.
+    pub ledger_seq: u32,
+    /// Budget trackers of the cost types that have been charged during the
+    /// invocation.
+    pub cost_trackers: Vec<(ContractCostType, CostTracker)>,
+}
+
+/// Result of re-pricing a simulated `InvokeHostFunctionOp` operation via
+/// `reprice_invoke_host_function_simulation`.
+#[derive(Eq, PartialEq, Debug)]
+pub struct RepricedSimulationResult {
+    /// The number of CPU instructions the invocation would be charged under
+    /// the new cost parameters, without any adjustments applied.
+    pub instructions: u64,
+    /// The number of memory bytes the invocation would be charged under the
+    /// new cost parameters, without any adjustments applied.
+    pub memory: u64,
+    /// Whether `instructions` and `memory` fit into the per-transaction
+    /// limits of the new network configuration. The invocation would fail
+    /// with the budget error otherwise.
+    pub within_limits: bool,
+    /// Non-refundable part of the resource fee.
+    pub non_refundable_fee: i64,
+    /// Refundable part of the resource fee, including the rent fee.
+    pub refundable_fee: i64,
+    /// Rent fee for the modified ledger entries, without any adjustments
+    /// applied.
+    pub rent_fee: i64,
+    /// Simulated transaction data with the re-priced instructions and the
+    /// resource fee.
+    pub transaction_data: SorobanTransactionData,
 }
 
 /// Result of simulating `ExtendFootprintTtlOp` operation.
@@ -162,6 +215,7 @@ pub fn simulate_invoke_host_function_op(
         contract_events: vec![],
         transaction_data: None,
         modified_entries: vec![],
+        fee_inputs: None,
     };
     let Ok(recording_result) = recording_result else {
         return Ok(simulation_result);
@@ -202,10 +256,88 @@ pub fn simulate_invoke_host_function_op(
         &recording_result.restored_rw_entry_indices,
         resource_fee,
     )?);
+    let mut cost_trackers = vec![];
+    for ty in ContractCostType::variants() {
+        let tracker = budget.get_tracker(ty)?;
+        if tracker.iterations > 0 {
+            cost_trackers.push((ty, tracker));
+        }
+    }
+    simulation_result.fee_inputs = Some(InvokeHostFunctionFeeInputs {
+        transaction_resources,
+        rent_changes,
+        ledger_seq: ledger_info.sequence_number,
+        cost_trackers,
+    });
 
     Ok(simulation_result)
 }
 
+/// Re-prices a successful `InvokeHostFunctionOp` simulation under a different
+/// network configuration, without re-executing the invocation.
+///
+/// The fees are re-computed from the recorded resources and rent changes
+/// using the fee configurations of `network_config`. The instructions and
+/// memory are re-computed from the per-cost-type budget trackers using the
+/// cost parameters of `network_config`. The recorded trackers don't keep the
+/// individual charges, so the re-priced values may slightly exceed the values
+/// that would actually be charged (by at most one unit per charge). Changes
+/// in the cost parameters can't change the invocation path, so the estimate
+/// is only meaningful while it stays `within_limits`.
+///
+/// The rent fee is computed for the ledger the invocation has been simulated
+/// at. `adjustment_config` must be the same as the one used for the
+/// simulation, as the recorded resources already have the adjustments
+/// applied.
+///
+/// This returns error for failed invocations, which have no resources to
+/// re-price, and for mis-configured cost parameters.
+pub fn reprice_invoke_host_function_simulation(
+    simulation_result: &InvokeHostFunctionSimulationResult,
+    network_config: &NetworkConfig,
+    adjustment_config: &SimulationAdjustmentConfig,
+) -> Result<RepricedSimulationResult> {
+    let (Some(fee_inputs), Some(transaction_data)) = (
+        &simulation_result.fee_inputs,
+        &simulation_result.transaction_data,
+    ) else {
+        bail!("can't re-price a simulation of the failed invocation");
+    };
+    let instructions =
+        estimate_cost_from_trackers(&fee_inputs.cost_trackers, &network_config.cpu_cost_params)?;
+    let memory = estimate_cost_from_trackers(
+        &fee_inputs.cost_trackers,
+        &network_config.memory_cost_params,
+    )?;
+    let within_limits = instructions <= network_config.tx_max_instructions.max(0) as u64
+        && memory <= network_config.tx_memory_limit as u64;
+
+    let mut transaction_resources = fee_inputs.transaction_resources.clone();
+    transaction_resources.instructions = adjustment_config
+        .instructions
+        .adjust_u32(instructions.try_into().unwrap_or(u32::MAX));
+    let (non_refundable_fee, refundable_fee, rent_fee) = compute_resource_fee_components(
+        network_config,
+        fee_inputs.ledger_seq,
+        &transaction_resources,
+        &fee_inputs.rent_changes,
+        adjustment_config,
+    );
+    let mut transaction_data = transaction_data.clone();
+    transaction_data.resources.instructions = transaction_resources.instructions;
+    transaction_data.resource_fee = non_refundable_fee.saturating_add(refundable_fee);
+
+    Ok(RepricedSimulationResult {
+        instructions,
+        memory,
+        within_limits,
+        non_refundable_fee,
+        refundable_fee,
+        rent_fee,
+        transaction_data,
+    })
+}
+
 /// Simulates `ExtendFootprintTtlOp` operation specified via its
 /// relevant payload parts.
 ///
@@ -312,6 +444,136 @@ pub fn simulate_restore_op(
     })
 }
 
+/// Issue that would make the Stellar Asset Contract transfers fail, as
+/// detected by `preflight_check_sac_transfers`.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum SacTransferPreflightIssue {
+    /// The account involved in the transfer doesn't exist.
+    MissingAccount { account_id: AccountId },
+    /// The account doesn't have a trustline for the transferred asset.
+    MissingTrustline {
+        account_id: AccountId,
+        asset: TrustLineAsset,
+    },
+    /// The account's native balance is below the minimum balance required
+    /// for the account and its sub-entries (including the trustlines that are
+    /// missing and would need to be created), i.e. it can't send the native
+    /// asset or get new trustlines without topping up the account first.
+    InsufficientReserve {
+        account_id: AccountId,
+        balance: i64,
+        min_balance: i64,
+    },
+    /// The balance has reached the maximum allowed by the trustline limit or
+    /// the buying liabilities, so the account can't receive more of the
+    /// asset. `asset` is `None` for the native balance.
+    BalanceAtLimit {
+        account_id: AccountId,
+        asset: Option<TrustLineAsset>,
+        balance: i64,
+        max_balance: i64,
+    },
+}
+
+/// Checks the accounts and trustlines in the footprint of the simulated
+/// transaction (`transaction_data`) that the Stellar Asset Contract would need
+/// to access for the transfers.
+///
+/// Such entries are still recorded in the footprint when the SAC transfer
+/// fails, so this allows flagging transfers to the accounts lacking a
+/// trustline before submitting the transaction, even when the failure has
+/// been handled by the contract (e.g. via `try_call`). The native balances of
+/// the accounts are checked against their reserve (that accounts for the
+/// trustlines that would need to be created) and their buying liabilities,
+/// and the balances of the existing trustlines are checked against their
+/// limits.
+///
+/// Every issue is reported once, in a deterministic order.
+///
+/// This only returns error if `snapshot_source` fails to provide an entry.
+pub fn preflight_check_sac_transfers(
+    snapshot_source: &(impl SnapshotSource + ?Sized),
+    ledger_info: &LedgerInfo,
+    transaction_data: &SorobanTransactionData,
+) -> Result<Vec<SacTransferPreflightIssue>> {
+    let footprint = &transaction_data.resources.footprint;
+    let mut issues = BTreeSet::new();
+    // Accounts to check, with the number of their trustlines that are
+    // missing.
+    let mut accounts: BTreeMap<AccountId, i64> = BTreeMap::new();
+    for key in footprint
+        .read_only
+        .iter()
+        .chain(footprint.read_write.iter())
+    {
+        match key {
+            LedgerKey::Account(k) => {
+                accounts.entry(k.account_id.clone()).or_default();
+            }
+            LedgerKey::Trustline(k) => {
+                let Some((entry, _)) = snapshot_source.get(&Rc::new(key.clone()))? else {
+                    issues.insert(SacTransferPreflightIssue::MissingTrustline {
+                        account_id: k.account_id.clone(),
+                        asset: k.asset.clone(),
+                    });
+                    *accounts.entry(k.account_id.clone()).or_default() += 1;
+                    continue;
+                };
+                let LedgerEntryData::Trustline(trustline) = &entry.data else {
+                    continue;
+                };
+                let buying_liabilities = match &trustline.ext {
+                    TrustLineEntryExt::V0 => 0,
+                    TrustLineEntryExt::V1(v1) => v1.liabilities.buying,
+                };
+                let max_balance = trustline.limit.saturating_sub(buying_liabilities);
+                if trustline.balance >= max_balance {
+                    issues.insert(SacTransferPreflightIssue::BalanceAtLimit {
+                        account_id: k.account_id.clone(),
+                        asset: Some(k.asset.clone()),
+                        balance: trustline.balance,
+                        max_balance,
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+    for (account_id, missing_trustlines) in accounts {
+        let account_key = Rc::new(LedgerKey::Account(LedgerKeyAccount {
+            account_id: account_id.clone(),
+        }));
+        let Some((account_entry, _)) = snapshot_source.get(&account_key)? else {
+            issues.insert(SacTransferPreflightIssue::MissingAccount { account_id });
+            continue;
+        };
+        let LedgerEntryData::Account(account) = &account_entry.data else {
+            continue;
+        };
+        let (min_balance, max_balance) =
+            get_account_balance_bounds(account, ledger_info.base_reserve);
+        // Every new trustline will be an additional sub-entry of the account.
+        let min_balance = min_balance
+            .saturating_add((ledger_info.base_reserve as i64).saturating_mul(missing_trustlines));
+        if account.balance < min_balance {
+            issues.insert(SacTransferPreflightIssue::InsufficientReserve {
+                account_id: account_id.clone(),
+                balance: account.balance,
+                min_balance,
+            });
+        }
+        if account.balance >= max_balance {
+            issues.insert(SacTransferPreflightIssue::BalanceAtLimit {
+                account_id,
+                asset: None,
+                balance: account.balance,
+                max_balance,
+            });
+        }
+    }
+    Ok(issues.into_iter().collect())
+}
+
 impl SimulationAdjustmentFactor {
     pub fn new(multiplicative_factor: f64, additive_factor: u32) -> Self {
         Self {
@@ -1,6 +1,7 @@
 use crate::simulation::{
-    simulate_extend_ttl_op, simulate_invoke_host_function_op, simulate_restore_op,
-    ExtendTtlOpSimulationResult, LedgerEntryDiff, RestoreOpSimulationResult,
+    preflight_check_sac_transfers, reprice_invoke_host_function_simulation, simulate_extend_ttl_op,
+    simulate_invoke_host_function_op, simulate_restore_op, ExtendTtlOpSimulationResult,
+    LedgerEntryDiff, RestoreOpSimulationResult, SacTransferPreflightIssue,
     SimulationAdjustmentConfig, SimulationAdjustmentFactor,
 };
 use crate::testutils::{ledger_entry_to_ledger_key, temp_entry, MockSnapshotSource};
@@ -15,15 +16,17 @@ use soroban_env_host::e2e_testutils::{
 };
 use soroban_env_host::fees::{FeeConfiguration, RentFeeConfiguration};
 use soroban_env_host::xdr::{
-    AccountId, AlphaNum4, AssetCode4, ContractCostParamEntry, ContractCostParams, ContractCostType,
+    AccountEntryExt, AccountEntryExtensionV1, AccountEntryExtensionV1Ext, AccountId, AlphaNum4,
+    AssetCode4, ContractCostParamEntry, ContractCostParams, ContractCostType,
     ContractDataDurability, ContractDataEntry, ContractExecutable, ContractId, ExtensionPoint,
     Hash, HostFunction, Int128Parts, InvokeContractArgs, LedgerEntry, LedgerEntryData,
-    LedgerFootprint, LedgerKey, LedgerKeyContractData, LedgerKeyTrustLine, Limits, PublicKey,
-    ScAddress, ScBytes, ScContractInstance, ScErrorCode, ScErrorType, ScMap, ScNonceKey, ScString,
-    ScSymbol, ScVal, SorobanAddressCredentials, SorobanAuthorizationEntry,
-    SorobanAuthorizedFunction, SorobanAuthorizedInvocation, SorobanCredentials, SorobanResources,
-    SorobanResourcesExtV0, SorobanTransactionData, SorobanTransactionDataExt, TrustLineAsset,
-    TrustLineEntry, TrustLineEntryExt, TrustLineFlags, Uint256, VecM, WriteXdr,
+    LedgerFootprint, LedgerKey, LedgerKeyAccount, LedgerKeyContractData, LedgerKeyTrustLine,
+    Liabilities, Limits, PublicKey, ScAddress, ScBytes, ScContractInstance, ScErrorCode,
+    ScErrorType, ScMap, ScNonceKey, ScString, ScSymbol, ScVal, SorobanAddressCredentials,
+    SorobanAuthorizationEntry, SorobanAuthorizedFunction, SorobanAuthorizedInvocation,
+    SorobanCredentials, SorobanResources, SorobanResourcesExtV0, SorobanTransactionData,
+    SorobanTransactionDataExt, TrustLineAsset, TrustLineEntry, TrustLineEntryExt, TrustLineEntryV1,
+    TrustLineEntryV1Ext, TrustLineFlags, Uint256, VecM, WriteXdr,
 };
 use soroban_env_host::HostError;
 use soroban_test_wasms::{ADD_I32, AUTH_TEST_CONTRACT, TRY_CALL_SAC};
@@ -273,6 +276,124 @@ fn test_simulation_returns_logic_error() {
     assert_eq!(res.modified_entries, vec![]);
 }
 
+#[test]
+fn test_reprice_simulation_under_different_network_config() {
+    let source_account = get_account_id([123; 32]);
+    let ledger_info = default_ledger_info();
+    let network_config = default_network_config();
+    let adjustment_config = test_adjustment_config();
+    let snapshot_source = Rc::new(MockSnapshotSource::from_entries(vec![]).unwrap());
+
+    let mut res = simulate_invoke_host_function_op(
+        snapshot_source,
+        &network_config,
+        &adjustment_config,
+        &ledger_info,
+        upload_wasm_host_fn(ADD_I32),
+        RecordingInvocationAuthMode::Recording(true),
+        &source_account,
+        [1; 32],
+        true,
+    )
+    .unwrap();
+    let fee_inputs = res.fee_inputs.as_ref().unwrap();
+    assert!(!fee_inputs.rent_changes.is_empty());
+    assert!(!fee_inputs.cost_trackers.is_empty());
+    assert_eq!(fee_inputs.ledger_seq, ledger_info.sequence_number);
+
+    // Re-pricing under the same config reproduces the simulation. The cpu
+    // linear terms of the test config are whole numbers, so there is no
+    // rounding difference.
+    let same =
+        reprice_invoke_host_function_simulation(&res, &network_config, &adjustment_config).unwrap();
+    assert_eq!(same.instructions, res.simulated_instructions as u64);
+    assert!(same.memory >= res.simulated_memory as u64);
+    assert!(same.within_limits);
+    assert!(same.rent_fee > 0);
+    assert_eq!(Some(&same.transaction_data), res.transaction_data.as_ref());
+
+    // Doubling all the cpu cost parameters doubles the instructions, and only
+    // the non-refundable fee depends on them.
+    let mut doubled_cpu_config = default_network_config();
+    for entry in doubled_cpu_config.cpu_cost_params.0.iter_mut() {
+        entry.const_term *= 2;
+        entry.linear_term *= 2;
+    }
+    let doubled_cpu =
+        reprice_invoke_host_function_simulation(&res, &doubled_cpu_config, &adjustment_config)
+            .unwrap();
+    assert_eq!(doubled_cpu.instructions, 2 * same.instructions);
+    assert_eq!(doubled_cpu.memory, same.memory);
+    assert_eq!(
+        doubled_cpu.transaction_data.resources.instructions,
+        adjustment_config
+            .instructions
+            .adjust_u32(2 * res.simulated_instructions)
+    );
+    assert!(doubled_cpu.non_refundable_fee > same.non_refundable_fee);
+    assert_eq!(doubled_cpu.refundable_fee, same.refundable_fee);
+    assert_eq!(doubled_cpu.rent_fee, same.rent_fee);
+
+    // Rent fee changes only affect the refundable fee.
+    let mut rent_config = default_network_config();
+    rent_config.rent_fee_configuration.fee_per_rent_1kb *= 3;
+    let repriced_rent =
+        reprice_invoke_host_function_simulation(&res, &rent_config, &adjustment_config).unwrap();
+    assert_eq!(repriced_rent.instructions, same.instructions);
+    assert_eq!(repriced_rent.non_refundable_fee, same.non_refundable_fee);
+    assert!(repriced_rent.rent_fee > same.rent_fee);
+    assert!(repriced_rent.refundable_fee > same.refundable_fee);
+    assert_eq!(
+        repriced_rent.transaction_data.resource_fee,
+        repriced_rent.non_refundable_fee + repriced_rent.refundable_fee
+    );
+
+    // The invocation doesn't fit into the lowered instruction limit anymore.
+    let mut limited_config = default_network_config();
+    limited_config.tx_max_instructions = res.simulated_instructions as i64 - 1;
+    assert!(
+        !reprice_invoke_host_function_simulation(&res, &limited_config, &adjustment_config,)
+            .unwrap()
+            .within_limits
+    );
+
+    // The rent fee is computed for the ledger of the simulation, so it
+    // decreases when the same entries are extended from a later ledger.
+    res.fee_inputs.as_mut().unwrap().ledger_seq += 1000;
+    let later_ledger =
+        reprice_invoke_host_function_simulation(&res, &network_config, &adjustment_config).unwrap();
+    assert!(later_ledger.rent_fee < same.rent_fee);
+    assert_eq!(later_ledger.non_refundable_fee, same.non_refundable_fee);
+}
+
+#[test]
+fn test_reprice_failed_simulation_returns_error() {
+    let source_account = get_account_id([123; 32]);
+    let ledger_info = default_ledger_info();
+    let network_config = default_network_config();
+    let snapshot_source = Rc::new(MockSnapshotSource::from_entries(vec![]).unwrap());
+
+    let res = simulate_invoke_host_function_op(
+        snapshot_source,
+        &network_config,
+        &SimulationAdjustmentConfig::no_adjustments(),
+        &ledger_info,
+        upload_wasm_host_fn(&[0; 1000]),
+        RecordingInvocationAuthMode::Recording(true),
+        &source_account,
+        [1; 32],
+        true,
+    )
+    .unwrap();
+    assert_eq!(res.fee_inputs, None);
+    assert!(reprice_invoke_host_function_simulation(
+        &res,
+        &network_config,
+        &SimulationAdjustmentConfig::no_adjustments(),
+    )
+    .is_err());
+}
+
 #[test]
 fn test_simulate_create_contract() {
     let source_account = get_account_id([123; 32]);
@@ -1107,7 +1228,7 @@ fn test_simulate_successful_sac_call() {
         .unwrap(),
     );
     let res = simulate_invoke_host_function_op(
-        snapshot_source,
+        snapshot_source.clone(),
         &network_config,
         &SimulationAdjustmentConfig::no_adjustments(),
         &ledger_info,
@@ -1154,6 +1275,15 @@ fn test_simulate_successful_sac_call() {
             resource_fee: 52979,
         })
     );
+    assert_eq!(
+        preflight_check_sac_transfers(
+            snapshot_source.as_ref(),
+            &ledger_info,
+            res.transaction_data.as_ref().unwrap()
+        )
+        .unwrap(),
+        vec![]
+    );
 }
 
 // This test covers an edge-case scenario of a SAC failure due to missing
@@ -1204,7 +1334,7 @@ fn test_simulate_unsuccessful_sac_call_with_try_call() {
     );
 
     let res = simulate_invoke_host_function_op(
-        snapshot_source,
+        snapshot_source.clone(),
         &network_config,
         &SimulationAdjustmentConfig::no_adjustments(),
         &ledger_info,
@@ -1236,7 +1366,7 @@ fn test_simulate_unsuccessful_sac_call_with_try_call() {
                     read_only: vec![
                         // Trustline key must appear in the footprint, even
                         // though it's not present in the storage.
-                        trustline_key,
+                        trustline_key.clone(),
                         contract.wasm_key.clone(),
                         contract.contract_key.clone(),
                         ledger_entry_to_ledger_key(&sac_instance_le).unwrap(),
@@ -1254,4 +1384,143 @@ fn test_simulate_unsuccessful_sac_call_with_try_call() {
             resource_fee: 5548,
         })
     );
+    // The missing trustline is flagged by the preflight check, even though
+    // the invocation has succeeded. The account also doesn't have enough
+    // balance to cover the reserve for the new trustline.
+    let LedgerKey::Trustline(trustline_key) = trustline_key else {
+        unreachable!();
+    };
+    assert_eq!(
+        preflight_check_sac_transfers(
+            snapshot_source.as_ref(),
+            &ledger_info,
+            res.transaction_data.as_ref().unwrap()
+        )
+        .unwrap(),
+        vec![
+            SacTransferPreflightIssue::MissingTrustline {
+                account_id: other_account.clone(),
+                asset: trustline_key.asset,
+            },
+            SacTransferPreflightIssue::InsufficientReserve {
+                account_id: other_account,
+                balance: 10_000_000,
+                min_balance: 15_000_000,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_preflight_check_sac_transfers_reports_every_issue_once() {
+    let missing_account = get_account_id([111; 32]);
+    let low_reserve_account = get_account_id([222; 32]);
+    let liabilities_account = get_account_id([223; 32]);
+    let asset = TrustLineAsset::CreditAlphanum4(AlphaNum4 {
+        asset_code: AssetCode4([b'a'; 4]),
+        issuer: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([0; 32]))),
+    });
+    let trustline_key = |account_id: &AccountId| {
+        LedgerKey::Trustline(LedgerKeyTrustLine {
+            account_id: account_id.clone(),
+            asset: asset.clone(),
+        })
+    };
+    let account_key = |account_id: &AccountId| {
+        LedgerKey::Account(LedgerKeyAccount {
+            account_id: account_id.clone(),
+        })
+    };
+    // The account is below the reserve even without any new trustlines.
+    let mut low_reserve_le = account_entry(&low_reserve_account);
+    if let LedgerEntryData::Account(account) = &mut low_reserve_le.data {
+        account.num_sub_entries = 1;
+    }
+    // The account can't receive any more native balance due to the buying
+    // liabilities, and its trustline is at the limit.
+    let mut liabilities_le = account_entry(&liabilities_account);
+    if let LedgerEntryData::Account(account) = &mut liabilities_le.data {
+        account.ext = AccountEntryExt::V1(AccountEntryExtensionV1 {
+            liabilities: Liabilities {
+                buying: i64::MAX - account.balance,
+                selling: 0,
+            },
+            ext: AccountEntryExtensionV1Ext::V0,
+        });
+    }
+    let trustline_le = ledger_entry(LedgerEntryData::Trustline(TrustLineEntry {
+        account_id: liabilities_account.clone(),
+        asset: asset.clone(),
+        balance: 900,
+        limit: 1000,
+        flags: TrustLineFlags::AuthorizedFlag as u32,
+        ext: TrustLineEntryExt::V1(TrustLineEntryV1 {
+            liabilities: Liabilities {
+                buying: 100,
+                selling: 0,
+            },
+            ext: TrustLineEntryV1Ext::V0,
+        }),
+    }));
+    let ledger_info = default_ledger_info();
+    let snapshot_source = MockSnapshotSource::from_entries(vec![
+        (low_reserve_le, None),
+        (liabilities_le, None),
+        (trustline_le, None),
+    ])
+    .unwrap();
+    let transaction_data = SorobanTransactionData {
+        ext: SorobanTransactionDataExt::V0,
+        resources: SorobanResources {
+            footprint: LedgerFootprint {
+                read_only: vec![
+                    trustline_key(&missing_account),
+                    trustline_key(&liabilities_account),
+                    account_key(&missing_account),
+                ]
+                .try_into()
+                .unwrap(),
+                read_write: vec![
+                    account_key(&missing_account),
+                    account_key(&low_reserve_account),
+                    account_key(&liabilities_account),
+                ]
+                .try_into()
+                .unwrap(),
+            },
+            instructions: 0,
+            disk_read_bytes: 0,
+            write_bytes: 0,
+        },
+        resource_fee: 0,
+    };
+    assert_eq!(
+        preflight_check_sac_transfers(&snapshot_source, &ledger_info, &transaction_data).unwrap(),
+        vec![
+            SacTransferPreflightIssue::MissingAccount {
+                account_id: missing_account.clone(),
+            },
+            SacTransferPreflightIssue::MissingTrustline {
+                account_id: missing_account,
+                asset: asset.clone(),
+            },
+            SacTransferPreflightIssue::InsufficientReserve {
+                account_id: low_reserve_account,
+                balance: 10_000_000,
+                min_balance: 15_000_000,
+            },
+            SacTransferPreflightIssue::BalanceAtLimit {
+                account_id: liabilities_account.clone(),
+                asset: None,
+                balance: 10_000_000,
+                max_balance: 10_000_000,
+            },
+            SacTransferPreflightIssue::BalanceAtLimit {
+                account_id: liabilities_account,
+                asset: Some(asset),
+                balance: 900,
+                max_balance: 900,
+            },
+        ]
+    );
 }